@@ -0,0 +1,274 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode};
+use sqlx::{Connection, SqliteConnection};
+
+use crate::database::Database;
+use crate::error::TimeboxError;
+
+pub const DATABASE_FILE: &str = "timebox.db";
+// A verified backup waiting to replace the database on the next start
+const PENDING_RESTORE_FILE: &str = "timebox.db.restore";
+const BACKUP_DIR: &str = "backups";
+const BACKUP_PREFIX: &str = "timebox-";
+
+const BACKUP_INTERVAL: Duration = Duration::hours(24);
+// Number of most recent regular backups kept; older ones are deleted after each backup
+const RETAINED_BACKUPS: usize = 14;
+// Exit backups keep the newest one of each of this many days
+const RETAINED_EXIT_DAYS: usize = 7;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupKind {
+    // Taken by the scheduler or by hand
+    Regular,
+    // Taken when the app quits
+    Exit,
+    // The database a restore replaced; never pruned
+    BeforeRestore,
+}
+
+impl BackupKind {
+    fn suffix(&self) -> &'static str {
+        match self {
+            BackupKind::Regular => "",
+            BackupKind::Exit => "-exit",
+            BackupKind::BeforeRestore => "-before-restore",
+        }
+    }
+
+    fn of(name: &str) -> Self {
+        let stem = name.strip_suffix(".db").unwrap_or(name);
+        if stem.ends_with(BackupKind::Exit.suffix()) {
+            BackupKind::Exit
+        } else if stem.ends_with(BackupKind::BeforeRestore.suffix()) {
+            BackupKind::BeforeRestore
+        } else {
+            BackupKind::Regular
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BackupInfo {
+    pub name: String,
+    pub path: String,
+    pub kind: BackupKind,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+pub fn backup_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(BACKUP_DIR)
+}
+
+/// Writes a consistent copy of the live database with `VACUUM INTO`, verifies it and prunes old backups.
+pub async fn create_backup(db: &Database, data_dir: &Path, kind: BackupKind) -> Result<BackupInfo, TimeboxError> {
+    let dir = backup_dir(data_dir);
+    fs::create_dir_all(&dir)?;
+
+    let path = dir.join(backup_name(kind));
+    // VACUUM INTO refuses to overwrite, and a backup from the same second is just as recent
+    if !path.exists() {
+        db.backup_into(&path).await?;
+
+        if let Err(e) = verify_backup(&path).await {
+            let _ = fs::remove_file(&path);
+            return Err(e);
+        }
+    }
+
+    prune_backups(&dir)?;
+
+    backup_info(&path)
+}
+
+/// Runs `PRAGMA integrity_check` against a backup file.
+pub async fn verify_backup(path: &Path) -> Result<(), TimeboxError> {
+    let problems = match integrity_check(path).await {
+        Ok(problems) => problems,
+        // Files that are not SQLite databases at all fail to open
+        Err(e) => vec![e.to_string()],
+    };
+
+    if problems.len() == 1 && problems[0] == "ok" {
+        Ok(())
+    } else {
+        Err(TimeboxError::Validation(format!(
+            "Backup {} failed the integrity check: {}",
+            path.display(),
+            problems.join("; ")
+        )))
+    }
+}
+
+async fn integrity_check(path: &Path) -> Result<Vec<String>, sqlx::Error> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(false)
+        .journal_mode(SqliteJournalMode::Delete);
+    let mut conn = SqliteConnection::connect_with(&options).await?;
+
+    let problems = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(&mut conn)
+        .await?;
+    conn.close().await?;
+
+    Ok(problems)
+}
+
+/// Backups in the data directory, newest first.
+pub fn list_backups(data_dir: &Path) -> Result<Vec<BackupInfo>, TimeboxError> {
+    let dir = backup_dir(data_dir);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = backup_paths(&dir)?
+        .iter()
+        .map(|path| backup_info(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+
+    Ok(backups)
+}
+
+/// Verifies a backup and stages it to replace the database the next time the app starts,
+/// before any connection to the current file is opened.
+pub async fn stage_restore(data_dir: &Path, name: &str) -> Result<(), TimeboxError> {
+    let path = backup_dir(data_dir).join(name);
+    // Only plain file names from list_backups are accepted, never paths
+    if name.contains(['/', '\\']) || !name.starts_with(BACKUP_PREFIX) || !path.is_file() {
+        return Err(TimeboxError::not_found("Backup", name));
+    }
+
+    verify_backup(&path).await?;
+    fs::copy(&path, data_dir.join(PENDING_RESTORE_FILE))?;
+
+    Ok(())
+}
+
+/// Swaps a staged backup into place. Must run before the database is opened.
+/// The replaced database is kept in the backup directory.
+pub fn apply_pending_restore(data_dir: &Path) -> Result<bool, TimeboxError> {
+    let pending = data_dir.join(PENDING_RESTORE_FILE);
+    if !pending.exists() {
+        return Ok(false);
+    }
+
+    let db_path = data_dir.join(DATABASE_FILE);
+    if db_path.exists() {
+        let dir = backup_dir(data_dir);
+        fs::create_dir_all(&dir)?;
+        let replaced = dir.join(backup_name(BackupKind::BeforeRestore));
+        fs::rename(&db_path, replaced)?;
+    }
+
+    // Leftover WAL files belong to the replaced database and must not be replayed onto the backup
+    for suffix in ["-wal", "-shm"] {
+        let sidecar = data_dir.join(format!("{}{}", DATABASE_FILE, suffix));
+        if sidecar.exists() {
+            fs::remove_file(sidecar)?;
+        }
+    }
+
+    fs::rename(&pending, &db_path)?;
+
+    Ok(true)
+}
+
+/// Spawns the task that backs up the database every `BACKUP_INTERVAL`.
+/// The first backup is taken right away when the newest regular one is already older than that.
+pub fn spawn_scheduler(db: Arc<Database>, data_dir: PathBuf) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let last_backup = list_backups(&data_dir)
+                .ok()
+                .and_then(|backups| backups.into_iter().find(|b| b.kind == BackupKind::Regular))
+                .map(|b| b.created_at);
+            let due = last_backup.map_or(Utc::now(), |last| last + BACKUP_INTERVAL);
+
+            if due <= Utc::now() {
+                if let Err(e) = create_backup(&db, &data_dir, BackupKind::Regular).await {
+                    eprintln!("Scheduled backup failed: {}", e);
+                }
+                // Retry a failed backup after a full interval rather than spinning
+                tokio::time::sleep(BACKUP_INTERVAL.to_std().unwrap_or(StdDuration::from_secs(86400))).await;
+            } else {
+                tokio::time::sleep((due - Utc::now()).to_std().unwrap_or(StdDuration::ZERO)).await;
+            }
+        }
+    });
+}
+
+fn backup_name(kind: BackupKind) -> String {
+    format!("{}{}{}.db", BACKUP_PREFIX, Utc::now().format("%Y%m%d-%H%M%S"), kind.suffix())
+}
+
+fn backup_paths(dir: &Path) -> Result<Vec<PathBuf>, TimeboxError> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_backup = path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(BACKUP_PREFIX) && name.ends_with(".db"));
+        if is_backup && path.is_file() {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+fn backup_info(path: &Path) -> Result<BackupInfo, TimeboxError> {
+    let metadata = fs::metadata(path)?;
+
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+
+    Ok(BackupInfo {
+        name: name.to_string(),
+        path: path.display().to_string(),
+        kind: BackupKind::of(name),
+        created_at: metadata.modified().map(DateTime::<Utc>::from).unwrap_or_else(|_| Utc::now()),
+        size_bytes: metadata.len(),
+    })
+}
+
+// Names embed the timestamp, so sorting by name orders backups oldest first.
+// Regular and exit backups are retained separately so frequent restarts can't push out the daily ones.
+fn prune_backups(dir: &Path) -> Result<(), TimeboxError> {
+    let mut paths = backup_paths(dir)?;
+    paths.sort();
+
+    let mut regular = 0;
+    let mut exit_days: Vec<String> = Vec::new();
+    for path in paths.iter().rev() {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let keep = match BackupKind::of(name) {
+            BackupKind::Regular => {
+                regular += 1;
+                regular <= RETAINED_BACKUPS
+            }
+            BackupKind::Exit => {
+                // The YYYYMMDD part of the timestamp
+                let day = name.get(BACKUP_PREFIX.len()..BACKUP_PREFIX.len() + 8).unwrap_or(name);
+                if exit_days.iter().any(|kept| kept == day) {
+                    false
+                } else {
+                    exit_days.push(day.to_string());
+                    exit_days.len() <= RETAINED_EXIT_DAYS
+                }
+            }
+            BackupKind::BeforeRestore => true,
+        };
+        if !keep {
+            fs::remove_file(path)?;
+        }
+    }
+
+    Ok(())
+}
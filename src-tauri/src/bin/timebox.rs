@@ -0,0 +1,4 @@
+// Command-line interface sharing the app's database; see cli.rs
+fn main() -> std::process::ExitCode {
+    timebox_main_lib::cli::run()
+}
@@ -0,0 +1,693 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use tokio::sync::{Notify, RwLock};
+
+use crate::backup;
+use crate::commands::{
+    self, AppState, RecordInterruptionRequest, StartSessionRequest, build_timer_status,
+};
+use crate::database::{Database, InterruptionKind, ManualSession, Project, ProjectTagFilter, SessionType, Task, TaskChanges, TaskPriority, DEFAULT_UPCOMING_DAYS};
+use crate::error::TimeboxError;
+use crate::importer;
+use crate::recurrence::Recurrence;
+use crate::timezone;
+
+// Must match `identifier` in tauri.conf.json, which names the app data directory
+const APP_IDENTIFIER: &str = "com.mbugua.timebox";
+
+/// Control TimeBox from the terminal. Works alongside a running TimeBox window,
+/// which picks up changes made here within a couple of seconds.
+#[derive(Parser)]
+#[command(name = "timebox", version)]
+struct Cli {
+    /// Database file to use instead of the app's timebox.db (also TIMEBOX_DB)
+    #[arg(long, global = true)]
+    db: Option<PathBuf>,
+
+    #[arg(long, global = true, default_value = "default_user")]
+    user: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start a session, replacing the running one unless strict mode is on
+    Start {
+        /// Task id or title
+        #[arg(long)]
+        task: Option<String>,
+        #[arg(long = "type", value_enum, default_value_t = Phase::Focus)]
+        phase: Phase,
+    },
+    /// Pause the running session
+    Pause {
+        /// What the session was paused for
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Resume the paused session
+    Resume,
+    /// Stop the running session
+    Stop {
+        /// Why a focus session ended early; required in strict mode
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Log an interruption of the running session
+    Interrupt {
+        /// The interruption came from yourself rather than someone else
+        #[arg(long)]
+        internal: bool,
+        #[arg(long)]
+        reason: Option<String>,
+        /// Source of the interruption, e.g. Slack or meeting
+        #[arg(long)]
+        category: Option<String>,
+    },
+    /// Show the running session
+    Status {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Manage tasks
+    Tasks {
+        #[command(subcommand)]
+        command: TasksCommand,
+    },
+    /// Log a session that was not timed in TimeBox
+    Log(LogArgs),
+    /// Show the tasks planned for a day and whether they fit into it
+    Plan {
+        /// "today", "tomorrow" or YYYY-MM-DD
+        #[arg(long)]
+        date: Option<String>,
+        /// Working time to plan against
+        #[arg(long)]
+        hours: Option<f64>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarize focus time for today or the current week
+    Report {
+        #[arg(long)]
+        week: bool,
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Args)]
+struct FilterArgs {
+    /// Project id or name
+    #[arg(long)]
+    project: Option<String>,
+    #[arg(long)]
+    tag: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum TasksCommand {
+    /// Add a task
+    Add {
+        title: String,
+        #[arg(long)]
+        estimate: Option<i32>,
+        /// Project id or name
+        #[arg(long)]
+        project: Option<String>,
+        /// Add as a subtask of this task (id or title)
+        #[arg(long)]
+        parent: Option<String>,
+        /// "today", "tomorrow" or YYYY-MM-DD
+        #[arg(long)]
+        due: Option<String>,
+        #[arg(long, value_enum)]
+        priority: Option<Priority>,
+        /// Repeat rule, see `tasks repeat`
+        #[arg(long)]
+        repeat: Option<String>,
+    },
+    /// List open tasks
+    List {
+        /// Include completed tasks
+        #[arg(long)]
+        all: bool,
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Mark a task completed
+    Done {
+        /// Task id or title
+        task: String,
+    },
+    /// Queue a task in a day's plan
+    Plan {
+        /// Task id or title
+        task: String,
+        /// "today", "tomorrow" or YYYY-MM-DD; defaults to today
+        #[arg(long)]
+        date: Option<String>,
+        /// Take the task out of its plan instead
+        #[arg(long, conflicts_with = "date")]
+        remove: bool,
+    },
+    /// Make a task a routine that comes back when completed or when its day is over
+    Repeat {
+        /// Task id or title
+        task: String,
+        /// daily, weekdays, weekly, weekly:mon,thu, monthly, or an RRULE like "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO"
+        #[arg(required_unless_present = "stop")]
+        rule: Option<String>,
+        /// Stop repeating; instances already created stay
+        #[arg(long, conflicts_with = "rule")]
+        stop: bool,
+    },
+    /// List the days routines come up on
+    Upcoming {
+        #[arg(long, default_value_t = DEFAULT_UPCOMING_DAYS)]
+        days: i64,
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Args)]
+struct LogArgs {
+    /// Start time, e.g. "2024-03-01 09:00" in your timezone or RFC 3339
+    #[arg(long)]
+    start: Option<String>,
+    /// End time; defaults to now
+    #[arg(long)]
+    end: Option<String>,
+    /// Length in minutes, counted back from the end when no start is given
+    #[arg(long)]
+    minutes: Option<i64>,
+    /// Task id or title
+    #[arg(long)]
+    task: Option<String>,
+    #[arg(long = "type", value_enum, default_value_t = Phase::Focus)]
+    phase: Phase,
+    /// Tag the session; repeat for several tags
+    #[arg(long = "tag")]
+    tags: Vec<String>,
+    /// Only log the parts of the range that no session covers yet
+    #[arg(long)]
+    fill_gaps: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Phase {
+    Focus,
+    ShortBreak,
+    LongBreak,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl From<Priority> for TaskPriority {
+    fn from(priority: Priority) -> Self {
+        match priority {
+            Priority::Low => TaskPriority::Low,
+            Priority::Medium => TaskPriority::Medium,
+            Priority::High => TaskPriority::High,
+        }
+    }
+}
+
+impl From<Phase> for SessionType {
+    fn from(phase: Phase) -> Self {
+        match phase {
+            Phase::Focus => SessionType::Focus,
+            Phase::ShortBreak => SessionType::ShortBreak,
+            Phase::LongBreak => SessionType::LongBreak,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ReportDay {
+    date: String,
+    pomodoros: i32,
+    focus_minutes: i64,
+}
+
+#[derive(Serialize)]
+struct ReportTask {
+    title: String,
+    pomodoros: i32,
+    focus_minutes: i64,
+}
+
+#[derive(Serialize)]
+struct ReportCompletedTask {
+    date: String,
+    title: String,
+}
+
+#[derive(Serialize)]
+struct Report {
+    from: String,
+    to: String,
+    pomodoros: i32,
+    focus_minutes: i64,
+    days: Vec<ReportDay>,
+    tasks: Vec<ReportTask>,
+    completed_tasks: Vec<ReportCompletedTask>,
+}
+
+/// Entry point of the `timebox` binary.
+pub fn run() -> ExitCode {
+    let cli = Cli::parse();
+
+    match tauri::async_runtime::block_on(execute(cli)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn database_path(cli: &Cli) -> Result<PathBuf, TimeboxError> {
+    if let Some(path) = cli.db.clone().or_else(|| std::env::var_os("TIMEBOX_DB").map(PathBuf::from)) {
+        return Ok(path);
+    }
+
+    // Same location Tauri resolves app_data_dir to
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER).join(backup::DATABASE_FILE))
+        .ok_or_else(|| TimeboxError::Validation("Could not locate the app data directory, pass --db".to_string()))
+}
+
+async fn execute(cli: Cli) -> Result<(), TimeboxError> {
+    let db = Database::open(&database_path(&cli)?).await?;
+
+    // Adopt the persisted timer only; closing orphaned sessions is left to app startup, since the
+    // app may be between writes of a session it is starting
+    let mut active_session = None;
+    commands::sync_with_database(&db, &mut active_session).await?;
+    let state = AppState {
+        active_session: RwLock::new(active_session),
+        db: Arc::new(db),
+        timer_notify: Notify::new(),
+    };
+    let user_id = cli.user.as_str();
+
+    // The app rolls routines over in the background; the CLI catches up whenever it runs
+    state.db.roll_over_recurring_tasks().await?;
+
+    match cli.command {
+        Command::Start { task, phase } => {
+            let task = match task {
+                Some(task) => Some(find_task(&state.db, user_id, &task).await?),
+                None => None,
+            };
+            let session = commands::begin_session(&state, StartSessionRequest {
+                user_id: Some(user_id.to_string()),
+                task_id: task.as_ref().map(|t| t.id.clone()),
+                session_type: phase.into(),
+            }).await?;
+
+            let label = phase_label(&session.session_type);
+            match task {
+                Some(task) => println!("Started {} on \"{}\"", label, task.title),
+                None => println!("Started {}", label),
+            }
+        }
+        Command::Pause { reason } => {
+            require_active_session(&state).await?;
+            commands::pause_active_session(&state, reason).await?;
+            println!("Paused");
+        }
+        Command::Resume => {
+            require_active_session(&state).await?;
+            commands::resume_active_session(&state).await?;
+            println!("Resumed");
+        }
+        Command::Stop { reason } => {
+            require_active_session(&state).await?;
+            commands::stop_active_session(&state, reason).await?;
+            println!("Stopped");
+        }
+        Command::Interrupt { internal, reason, category } => {
+            require_active_session(&state).await?;
+            let kind = if internal { InterruptionKind::Internal } else { InterruptionKind::External };
+            let count = commands::record_active_interruption(&state, RecordInterruptionRequest {
+                kind: Some(kind),
+                reason,
+                category,
+            }).await?;
+            println!("Logged interruption ({} this session)", count);
+        }
+        Command::Status { json } => {
+            let active_session = state.active_session.read().await;
+            let status = build_timer_status(&state.db, active_session.as_ref()).await;
+
+            if json {
+                print_json(&status)?;
+            } else if !status.is_running {
+                println!("No active session");
+            } else {
+                let mut line = format!(
+                    "{} {:02}:{:02} left",
+                    phase_label(&status.session_type),
+                    status.time_remaining / 60,
+                    status.time_remaining % 60
+                );
+                if status.is_paused {
+                    line.push_str(" (paused)");
+                }
+                if let Some(title) = &status.task_title {
+                    line.push_str(&format!(" - {}", title));
+                }
+                println!("{}", line);
+            }
+        }
+        Command::Tasks { command } => match command {
+            TasksCommand::Add { title, estimate, project, parent, due, priority, repeat } => {
+                let project_id = match project {
+                    Some(project) => Some(find_project(&state.db, user_id, &project).await?.id),
+                    None => None,
+                };
+                let parent_task_id = match parent {
+                    Some(parent) => Some(find_task(&state.db, user_id, &parent).await?.id),
+                    None => None,
+                };
+                // Checked before the task exists so a bad date or rule doesn't leave it half set up
+                let due_date = match due {
+                    Some(due) => Some(parse_day(&state.db, user_id, &due).await?),
+                    None => None,
+                };
+                if let Some(rule) = repeat.as_deref() {
+                    let start = match due_date {
+                        Some(due_date) => due_date,
+                        None => timezone::today(state.db.get_user_timezone(user_id).await?),
+                    };
+                    Recurrence::schedule(rule, start)?;
+                }
+                let task = state.db.create_task(
+                    user_id,
+                    &title,
+                    estimate,
+                    None,
+                    project_id.as_deref(),
+                    parent_task_id.as_deref(),
+                ).await?;
+                if due_date.is_some() || priority.is_some() || repeat.is_some() {
+                    state.db.update_task(&task.id, TaskChanges {
+                        due_date,
+                        priority: priority.map(TaskPriority::from),
+                        recurrence: repeat,
+                        ..Default::default()
+                    }).await?;
+                }
+                println!("Added {} {}", task.id, task.title);
+            }
+            TasksCommand::List { all, filter, json } => {
+                let filter = resolve_filter(&state.db, user_id, filter).await?;
+                let tasks: Vec<Task> = state.db.get_tasks(user_id, &filter).await?
+                    .into_iter()
+                    .filter(|task| all || !task.completed)
+                    .collect();
+
+                if json {
+                    print_json(&tasks)?;
+                } else {
+                    print_task_tree(&tasks, None, 0);
+                }
+            }
+            TasksCommand::Done { task } => {
+                let task = find_task(&state.db, user_id, &task).await?;
+                state.db.update_task(&task.id, TaskChanges { completed: Some(true), ..Default::default() }).await?;
+                println!("Completed {}", task.title);
+            }
+            TasksCommand::Plan { task, date, remove } => {
+                let task = find_task(&state.db, user_id, &task).await?;
+                if remove {
+                    state.db.update_task(&task.id, TaskChanges { clear_planned_for: true, ..Default::default() }).await?;
+                    println!("Unplanned {}", task.title);
+                } else {
+                    let day = parse_day(&state.db, user_id, date.as_deref().unwrap_or("today")).await?;
+                    state.db.update_task(&task.id, TaskChanges { planned_for: Some(day), ..Default::default() }).await?;
+                    println!("Planned {} for {}", task.title, day.format("%Y-%m-%d"));
+                }
+            }
+            TasksCommand::Repeat { task, rule, stop } => {
+                let task = find_task(&state.db, user_id, &task).await?;
+                if stop {
+                    state.db.update_task(&task.id, TaskChanges { clear_recurrence: true, ..Default::default() }).await?;
+                    println!("{} no longer repeats", task.title);
+                } else {
+                    let task = state.db.update_task(&task.id, TaskChanges { recurrence: rule, ..Default::default() }).await?;
+                    let next = task.due_date.map(|date| date.format("%Y-%m-%d").to_string()).unwrap_or_default();
+                    println!("{} repeats {}, next on {}", task.title, task.recurrence.unwrap_or_default(), next);
+                }
+            }
+            TasksCommand::Upcoming { days, json } => {
+                let occurrences = state.db.get_upcoming_occurrences(user_id, days).await?;
+
+                if json {
+                    print_json(&occurrences)?;
+                } else {
+                    for occurrence in &occurrences {
+                        let id = occurrence.task_id.as_deref().unwrap_or("-");
+                        println!("{}  {}  {}", occurrence.date.format("%Y-%m-%d"), occurrence.title, id);
+                    }
+                }
+            }
+        },
+        Command::Log(args) => {
+            let tz = state.db.get_user_timezone(user_id).await?;
+            let parse = |value: &str| importer::parse_datetime(value, tz).map_err(TimeboxError::Validation);
+
+            let end = match args.end.as_deref() {
+                Some(end) => parse(end)?,
+                None => Utc::now(),
+            };
+            let start = match (args.start.as_deref(), args.minutes) {
+                (Some(start), _) => parse(start)?,
+                (None, Some(minutes)) => end - Duration::minutes(minutes),
+                (None, None) => return Err(TimeboxError::Validation("Pass --start or --minutes".to_string())),
+            };
+
+            let task_id = match args.task {
+                Some(task) => Some(find_task(&state.db, user_id, &task).await?.id),
+                None => None,
+            };
+            let sessions = state.db.create_manual_session(user_id, ManualSession {
+                task_id,
+                session_type: Some(args.phase.into()),
+                start_time: start,
+                end_time: end,
+                duration_seconds: None,
+                tags: args.tags,
+                fill_gaps: args.fill_gaps,
+            }).await?;
+            let seconds: i32 = sessions.iter().filter_map(|session| session.duration_seconds).sum();
+            println!("Logged {} min", seconds / 60);
+        }
+        Command::Plan { date, hours, json } => {
+            let date = match date {
+                Some(date) => Some(parse_day(&state.db, user_id, &date).await?),
+                None => None,
+            };
+            let plan = state.db.get_daily_plan(user_id, date, hours.map(|hours| (hours * 60.0).round() as i32)).await?;
+
+            if json {
+                print_json(&plan)?;
+            } else {
+                println!("{}: {} of {} pomodoros planned", plan.date, plan.planned_pomodoros, plan.capacity_pomodoros);
+                for task in &plan.tasks {
+                    let mark = if task.completed { "x" } else { " " };
+                    println!("  [{}] {}  {} ({} est.)", mark, task.id, task.title, task.estimated_pomodoros);
+                }
+                if let Some(warning) = &plan.warning {
+                    println!("Warning: {}", warning);
+                }
+            }
+        }
+        Command::Report { week, filter, json } => {
+            let filter = resolve_filter(&state.db, user_id, filter).await?;
+            let report = build_report(&state.db, user_id, week, &filter).await?;
+
+            if json {
+                print_json(&report)?;
+            } else {
+                println!("{} to {}: {} pomodoros, {} min focused", report.from, report.to, report.pomodoros, report.focus_minutes);
+                for day in &report.days {
+                    println!("  {}  {:>3} pomodoros  {:>4} min", day.date, day.pomodoros, day.focus_minutes);
+                }
+                for task in &report.tasks {
+                    println!("  {:>3} pomodoros  {:>4} min  {}", task.pomodoros, task.focus_minutes, task.title);
+                }
+                for task in &report.completed_tasks {
+                    println!("  {}  done  {}", task.date, task.title);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn require_active_session(state: &AppState) -> Result<(), TimeboxError> {
+    if state.active_session.read().await.is_none() {
+        return Err(TimeboxError::NoActiveSession);
+    }
+    Ok(())
+}
+
+// Matches a task by id, or by title among the user's tasks (case-insensitive, open tasks first)
+async fn find_task(db: &Database, user_id: &str, task: &str) -> Result<Task, TimeboxError> {
+    let tasks = db.get_tasks(user_id, &ProjectTagFilter::default()).await?;
+    if let Some(found) = tasks.iter().find(|t| t.id == task) {
+        return Ok(found.clone());
+    }
+
+    let mut matches: Vec<&Task> = tasks.iter().filter(|t| t.title.eq_ignore_ascii_case(task)).collect();
+    matches.sort_by_key(|t| t.completed);
+    match matches.as_slice() {
+        [] => Err(TimeboxError::not_found("Task", task)),
+        [only] => Ok((*only).clone()),
+        [first, second, ..] if first.completed != second.completed => Ok((*first).clone()),
+        _ => Err(TimeboxError::Validation(format!("Several tasks are titled \"{}\", use the task id", task))),
+    }
+}
+
+// Matches a project by id or by name; names are unique per user regardless of case
+async fn find_project(db: &Database, user_id: &str, project: &str) -> Result<Project, TimeboxError> {
+    db.get_projects(user_id, true).await?
+        .into_iter()
+        .find(|p| p.id == project || p.name.eq_ignore_ascii_case(project))
+        .ok_or_else(|| TimeboxError::not_found("Project", project))
+}
+
+async fn resolve_filter(db: &Database, user_id: &str, args: FilterArgs) -> Result<ProjectTagFilter, TimeboxError> {
+    let project_id = match args.project {
+        Some(project) => Some(find_project(db, user_id, &project).await?.id),
+        None => None,
+    };
+    Ok(ProjectTagFilter { project_id, tag: args.tag })
+}
+
+// "today", "tomorrow" or a YYYY-MM-DD date, in the user's timezone
+async fn parse_day(db: &Database, user_id: &str, value: &str) -> Result<NaiveDate, TimeboxError> {
+    let today = timezone::today(db.get_user_timezone(user_id).await?);
+    match value {
+        "today" => Ok(today),
+        "tomorrow" => Ok(today + Duration::days(1)),
+        _ => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| TimeboxError::Validation(format!("Invalid date \"{}\", use YYYY-MM-DD", value))),
+    }
+}
+
+async fn build_report(db: &Database, user_id: &str, week: bool, filter: &ProjectTagFilter) -> Result<Report, TimeboxError> {
+    let tz = db.get_user_timezone(user_id).await?;
+    let today = timezone::today(tz);
+    let first_day = if week {
+        today - Duration::days(today.weekday().num_days_from_monday() as i64)
+    } else {
+        today
+    };
+    let last_day = if week { first_day + Duration::days(6) } else { today };
+
+    let from = timezone::start_of_day(tz, first_day);
+    let to = timezone::start_of_day(tz, last_day + Duration::days(1));
+    let mut sessions = db.get_sessions_by_date_range(user_id, from, to, Some(SessionType::Focus), filter).await?;
+    sessions.retain(|session| session.duration_seconds.unwrap_or(0) > 0);
+    sessions.sort_by_key(|session| session.start_time);
+
+    let mut days: Vec<ReportDay> = Vec::new();
+    let mut tasks: Vec<ReportTask> = Vec::new();
+    for session in &sessions {
+        let minutes = session.duration_seconds.unwrap_or(0) as i64 / 60;
+        let date = local_day(tz, session.start_time);
+
+        match days.iter_mut().find(|day| day.date == date) {
+            Some(day) => {
+                day.pomodoros += 1;
+                day.focus_minutes += minutes;
+            }
+            None => days.push(ReportDay { date, pomodoros: 1, focus_minutes: minutes }),
+        }
+
+        let title = session.task_title.clone().unwrap_or_else(|| "(no task)".to_string());
+        match tasks.iter_mut().find(|task| task.title == title) {
+            Some(task) => {
+                task.pomodoros += 1;
+                task.focus_minutes += minutes;
+            }
+            None => tasks.push(ReportTask { title, pomodoros: 1, focus_minutes: minutes }),
+        }
+    }
+    tasks.sort_by_key(|task| std::cmp::Reverse(task.focus_minutes));
+
+    let completed_tasks = db.get_completed_tasks(user_id, from, to, filter).await?
+        .into_iter()
+        .rev()
+        .map(|task| ReportCompletedTask {
+            date: task.completed_at.map(|at| local_day(tz, at)).unwrap_or_default(),
+            title: task.title,
+        })
+        .collect();
+
+    Ok(Report {
+        from: first_day.format("%Y-%m-%d").to_string(),
+        to: last_day.format("%Y-%m-%d").to_string(),
+        pomodoros: sessions.len() as i32,
+        focus_minutes: days.iter().map(|day| day.focus_minutes).sum(),
+        days,
+        tasks,
+        completed_tasks,
+    })
+}
+
+// Lists subtasks indented under their parent; tasks whose parent is not listed start a new tree
+fn print_task_tree(tasks: &[Task], parent_task_id: Option<&str>, depth: usize) {
+    let listed = |id: &str| tasks.iter().any(|t| t.id == id);
+    for task in tasks {
+        let is_child = match parent_task_id {
+            Some(parent_task_id) => task.parent_task_id.as_deref() == Some(parent_task_id),
+            None => !task.parent_task_id.as_deref().is_some_and(listed),
+        };
+        if !is_child {
+            continue;
+        }
+        let mark = if task.completed { "x" } else { " " };
+        println!("{}[{}] {}  {} ({} est.)", "  ".repeat(depth), mark, task.id, task.title, task.estimated_pomodoros);
+        print_task_tree(tasks, Some(&task.id), depth + 1);
+    }
+}
+
+fn local_day(tz: chrono_tz::Tz, instant: DateTime<Utc>) -> String {
+    timezone::local_date(tz, instant).format("%Y-%m-%d").to_string()
+}
+
+fn phase_label(session_type: &SessionType) -> &'static str {
+    match session_type {
+        SessionType::Focus => "focus session",
+        SessionType::ShortBreak => "short break",
+        SessionType::LongBreak => "long break",
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), TimeboxError> {
+    let json = serde_json::to_string_pretty(value).map_err(std::io::Error::from)?;
+    println!("{}", json);
+    Ok(())
+}
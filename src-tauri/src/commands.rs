@@ -1,14 +1,24 @@
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use chrono::{DateTime, Utc, Duration};
+use tokio::sync::{Notify, RwLock};
+use chrono::{DateTime, NaiveDate, Utc, Duration};
 use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
 
-use crate::database::{Database, PomodoroSettings, Task, PomodoroSession, SessionType, Goal, DailyReflection, DayActivities};
+use crate::backup::{self, BackupInfo, BackupKind};
+use crate::cycle::{self, CycleState};
+use crate::error::{StrictModeRule, TimeboxError};
+use crate::export::{self, ExportSummary};
+use crate::http_api::HttpApi;
+use crate::importer::{self, ImportMode, ImportReport, ImportRequest};
+use crate::database::{ApiSettings, Database, User, PomodoroSettings, Task, TaskChanges, TaskCounts, DailyPlan, UpcomingOccurrence, DEFAULT_UPCOMING_DAYS, ChecklistItem, ChecklistItemChanges, Project, ProjectChanges, ProjectTagFilter, Tag, PomodoroSession, SessionPause, Interruption, InterruptionKind, InterruptionSource, ManualSession, SessionAuditEntry, SessionChanges, SessionType, Goal, GoalProgress, DailyReflection, DayActivities, ActiveTimerState, FocusStats, Granularity, DailyTargetUnit, StreakSummary};
 
 // Global state to hold the database connection and active session
 pub struct AppState {
     pub db: Arc<Database>,
     pub active_session: RwLock<Option<ActiveSession>>,
+    // Wakes the timer engine whenever the active session changes
+    pub timer_notify: Notify,
 }
 
 pub struct ActiveSession {
@@ -19,6 +29,87 @@ pub struct ActiveSession {
     pub is_paused: bool,
 }
 
+impl ActiveSession {
+    // Seconds left on the timer, accounting for time elapsed since the last resume
+    pub fn time_remaining(&self) -> i64 {
+        if self.is_paused {
+            self.remaining_duration.num_seconds().max(0)
+        } else {
+            let elapsed = (Utc::now() - self.start_time).num_seconds();
+            (self.remaining_duration.num_seconds() - elapsed).max(0)
+        }
+    }
+
+    // Moment the running timer reaches zero; None while paused
+    pub fn deadline(&self) -> Option<DateTime<Utc>> {
+        if self.is_paused {
+            None
+        } else {
+            Some(self.start_time + self.remaining_duration)
+        }
+    }
+
+    pub fn to_timer_state(&self) -> ActiveTimerState {
+        ActiveTimerState {
+            session_id: self.session.id.clone(),
+            remaining_seconds: self.remaining_duration.num_seconds(),
+            total_seconds: self.total_duration.num_seconds(),
+            is_paused: self.is_paused,
+            anchor_time: self.start_time,
+            updated_at: Utc::now(),
+        }
+    }
+
+    // Whether `timer` is the state this session last persisted
+    pub fn matches_timer_state(&self, timer: &ActiveTimerState) -> bool {
+        self.session.id == timer.session_id
+            && self.is_paused == timer.is_paused
+            && self.start_time == timer.anchor_time
+            && self.remaining_duration.num_seconds() == timer.remaining_seconds
+    }
+
+    pub fn from_timer_state(session: PomodoroSession, timer: &ActiveTimerState) -> Self {
+        ActiveSession {
+            session,
+            start_time: timer.anchor_time,
+            remaining_duration: Duration::seconds(timer.remaining_seconds),
+            total_duration: Duration::seconds(timer.total_seconds),
+            is_paused: timer.is_paused,
+        }
+    }
+}
+
+/// Rebuilds the active session persisted by a previous run.
+/// A timer whose deadline passed while the app was down is closed out at that deadline,
+/// and any other session left without an `end_time` is closed as an orphan.
+pub async fn restore_active_session(db: &Database) -> Result<Option<ActiveSession>, TimeboxError> {
+    let restored = match db.get_active_timer().await? {
+        Some(timer) => match db.get_session(&timer.session_id).await? {
+            Some(session) if session.end_time.is_none() => {
+                let active = ActiveSession::from_timer_state(session, &timer);
+                if !active.is_paused && active.time_remaining() <= 0 {
+                    let end_time = timer.anchor_time + Duration::seconds(timer.remaining_seconds);
+                    db.update_session(&timer.session_id, end_time, timer.total_seconds as i32).await?;
+                    db.clear_active_timer().await?;
+                    None
+                } else {
+                    Some(active)
+                }
+            }
+            _ => {
+                db.clear_active_timer().await?;
+                None
+            }
+        },
+        None => None,
+    };
+
+    let keep_session_id = restored.as_ref().map(|active| active.session.id.as_str());
+    db.close_orphaned_sessions(keep_session_id).await?;
+
+    Ok(restored)
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct StartSessionRequest {
     pub user_id: Option<String>,
@@ -26,7 +117,7 @@ pub struct StartSessionRequest {
     pub session_type: SessionType,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct TimerStatusResponse {
     pub time_remaining: i64,  // seconds
     pub is_running: bool,
@@ -48,25 +139,28 @@ pub struct SettingsUpdateRequest {
     pub auto_start_breaks: Option<bool>,
     pub sound_enabled: Option<bool>,
     pub sound_volume: Option<i32>,
+    pub daily_target_unit: Option<DailyTargetUnit>,
+    pub daily_target: Option<i32>,
+    pub rest_days: Option<Vec<u8>>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct UpdateTaskRequest {
     pub task_id: String,
-    pub title: Option<String>,
-    pub estimated_pomodoros: Option<i32>,
-    pub completed: Option<bool>,
+    #[serde(flatten)]
+    pub changes: TaskChanges,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct TaskWithPomodoroCount {
     #[serde(flatten)]
     pub task: Task,
-    pub actual_pomodoros: i64,
+    #[serde(flatten)]
+    pub counts: TaskCounts,
 }
 
 #[tauri::command]
-pub async fn initialize_app(_state: tauri::State<'_, Arc<AppState>>) -> Result<(), String> {
+pub async fn initialize_app(_state: tauri::State<'_, Arc<AppState>>) -> Result<(), TimeboxError> {
     // Initialization logic can go here if needed
     Ok(())
 }
@@ -75,12 +169,29 @@ pub async fn initialize_app(_state: tauri::State<'_, Arc<AppState>>) -> Result<(
 pub async fn start_session(
     state: tauri::State<'_, Arc<AppState>>,
     req: StartSessionRequest,
-) -> Result<PomodoroSession, String> {
-    let user = state.db.get_or_create_user(req.user_id, Some("Default User".to_string())).await
-        .map_err(|e| e.to_string())?;
+) -> Result<PomodoroSession, TimeboxError> {
+    begin_session(&state, req).await
+}
+
+// Shared by the start_session command and the timer engine when it auto-starts a break
+pub async fn begin_session(state: &AppState, req: StartSessionRequest) -> Result<PomodoroSession, TimeboxError> {
+    let user = state.db.get_or_create_user(req.user_id, Some("Default User".to_string())).await?;
     
     let user_id = user.id.clone();
     let session_type = req.session_type.clone();
+
+    let settings = state.db.get_settings(&user_id).await?
+        .unwrap_or_else(|| PomodoroSettings::defaults(&user_id));
+
+    let mut active_session = state.active_session.write().await;
+    sync_with_database(&state.db, &mut active_session).await?;
+    if let Some(running) = active_session.as_ref() {
+        if settings.strict_mode {
+            return Err(TimeboxError::StrictModeViolation(StrictModeRule::SessionAlreadyRunning));
+        }
+        // Close out the session being replaced so it isn't left without an end time
+        finish_session(&state.db, running).await?;
+    }
     
     let session_id = uuid::Uuid::new_v4().to_string();
     let now = Utc::now();
@@ -95,134 +206,196 @@ pub async fn start_session(
         duration_seconds: None,
         interrupted: false,
         interruption_count: 0,
+        pause_count: 0,
+        paused_seconds: 0,
         manual_override: false,
+        stop_reason: None,
         created_at: now,
         task_title: None,
+        tags: Vec::new(),
     };
     
-    state.db.create_session(&session).await
-        .map_err(|e| e.to_string())?;
-    
     // Update active session in state
-    {
-        let settings = state.db.get_settings(&user_id).await
-            .map_err(|e| e.to_string())?
-            .unwrap_or_else(|| PomodoroSettings {
-                user_id: user_id.clone(),
-                focus_minutes: 25,
-                short_break_minutes: 5,
-                long_break_minutes: 15,
-                cycles_before_long_break: 4,
-                strict_mode: false,
-                auto_start_breaks: false,
-                sound_enabled: true,
-                sound_volume: 70,
-            });
-
-        let duration = match session_type {
-            SessionType::Focus => Duration::minutes(settings.focus_minutes as i64),
-            SessionType::ShortBreak => Duration::minutes(settings.short_break_minutes as i64),
-            SessionType::LongBreak => Duration::minutes(settings.long_break_minutes as i64),
-        };
+    let duration = match session_type {
+        SessionType::Focus => Duration::minutes(settings.focus_minutes as i64),
+        SessionType::ShortBreak => Duration::minutes(settings.short_break_minutes as i64),
+        SessionType::LongBreak => Duration::minutes(settings.long_break_minutes as i64),
+    };
 
-        let mut active_session = state.active_session.write().await;
-        *active_session = Some(ActiveSession {
-            session: session.clone(),
-            start_time: now,
-            remaining_duration: duration,
-            total_duration: duration,
-            is_paused: false,
-        });
-    }
+    let new_active = ActiveSession {
+        session: session.clone(),
+        start_time: now,
+        remaining_duration: duration,
+        total_duration: duration,
+        is_paused: false,
+    };
+    state.db.create_session(&session, &new_active.to_timer_state()).await?;
+
+    *active_session = Some(new_active);
+    state.timer_notify.notify_one();
     
     Ok(session)
 }
 
+// Closes the session in the database with the time actually spent on it
+async fn finish_session(db: &Database, session: &ActiveSession) -> Result<(), TimeboxError> {
+    let now = Utc::now();
+    // Total actual duration = total configured duration - remaining
+    let duration = (session.total_duration.num_seconds() - session.time_remaining()) as i32;
+    if session.is_paused {
+        db.end_session_pause(&session.session.id, now).await?;
+    }
+    db.update_session(&session.session.id, now, duration).await?;
+    db.clear_active_timer().await
+}
+
 #[tauri::command]
-pub async fn pause_session(state: tauri::State<'_, Arc<AppState>>) -> Result<(), String> {
+pub async fn pause_session(
+    state: tauri::State<'_, Arc<AppState>>,
+    reason: Option<String>,
+) -> Result<(), TimeboxError> {
+    pause_active_session(&state, reason).await
+}
+
+pub async fn pause_active_session(state: &AppState, reason: Option<String>) -> Result<(), TimeboxError> {
     let mut active_session = state.active_session.write().await;
-    if let Some(session) = active_session.as_mut() {
+    sync_with_database(&state.db, &mut active_session).await?;
+    // A timer that has already run out is left for the timer engine to complete
+    if let Some(session) = active_session.as_mut().filter(|session| !session.is_paused && session.time_remaining() > 0) {
+        if matches!(session.session.session_type, SessionType::Focus) {
+            let settings = state.db.get_or_create_settings(&session.session.user_id).await?;
+            if settings.strict_mode {
+                return Err(TimeboxError::StrictModeViolation(StrictModeRule::PauseDuringFocus));
+            }
+        }
+
+        let now = Utc::now();
+        let reason = reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+        state.db.start_session_pause(&session.session.id, now, reason).await?;
+        session.session.pause_count += 1;
+
         session.is_paused = true;
         // Calculate remaining duration based on elapsed time
-        let elapsed = Utc::now() - session.start_time;
-        session.remaining_duration = session.remaining_duration - elapsed;
-        session.start_time = Utc::now(); // Reset start time for when resuming
+        let elapsed = now - session.start_time;
+        session.remaining_duration = (session.remaining_duration - elapsed).max(Duration::zero());
+        session.start_time = now; // Reset start time for when resuming
+        state.db.save_active_timer(&session.to_timer_state()).await?;
     }
+    state.timer_notify.notify_one();
     Ok(())
 }
 
 #[tauri::command]
-pub async fn resume_session(state: tauri::State<'_, Arc<AppState>>) -> Result<(), String> {
+pub async fn resume_session(state: tauri::State<'_, Arc<AppState>>) -> Result<(), TimeboxError> {
+    resume_active_session(&state).await
+}
+
+pub async fn resume_active_session(state: &AppState) -> Result<(), TimeboxError> {
     let mut active_session = state.active_session.write().await;
-    if let Some(session) = active_session.as_mut() {
+    sync_with_database(&state.db, &mut active_session).await?;
+    if let Some(session) = active_session.as_mut().filter(|session| session.is_paused) {
+        let now = Utc::now();
+        state.db.end_session_pause(&session.session.id, now).await?;
+        session.session.paused_seconds += (now - session.start_time).num_seconds();
+
         session.is_paused = false;
-        session.start_time = Utc::now(); // Reset start time
+        session.start_time = now; // Reset start time
+        state.db.save_active_timer(&session.to_timer_state()).await?;
     }
+    state.timer_notify.notify_one();
     Ok(())
 }
 
 #[tauri::command]
-pub async fn stop_session(state: tauri::State<'_, Arc<AppState>>) -> Result<(), String> {
+pub async fn stop_session(
+    state: tauri::State<'_, Arc<AppState>>,
+    reason: Option<String>,
+) -> Result<(), TimeboxError> {
+    stop_active_session(&state, reason).await
+}
+
+pub async fn stop_active_session(state: &AppState, reason: Option<String>) -> Result<(), TimeboxError> {
     let mut active_session = state.active_session.write().await;
-    if let Some(session) = active_session.take() {
-        // Calculate time remaining correctly
-        let time_remaining = if session.is_paused {
-            session.remaining_duration.num_seconds()
-        } else {
-            let elapsed = (Utc::now() - session.start_time).num_seconds();
-            (session.remaining_duration.num_seconds() - elapsed).max(0)
-        };
-        // Total actual duration = total configured duration - remaining
-        let duration = (session.total_duration.num_seconds() - time_remaining) as i32;
-        state.db.update_session(&session.session.id, Utc::now(), duration)
-            .await
-            .map_err(|e| e.to_string())?;
+    sync_with_database(&state.db, &mut active_session).await?;
+    if let Some(session) = active_session.as_ref() {
+        let stopped_early = matches!(session.session.session_type, SessionType::Focus) && session.time_remaining() > 0;
+        // An early stop is recorded in every mode so the session doesn't count as completed;
+        // strict mode only adds that a reason must be given
+        if stopped_early {
+            let settings = state.db.get_or_create_settings(&session.session.user_id).await?;
+            let reason = reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+            if settings.strict_mode && reason.is_none() {
+                return Err(TimeboxError::StrictModeViolation(StrictModeRule::StopReasonRequired));
+            }
+            state.db.mark_session_interrupted(&session.session.id, reason).await?;
+        }
+
+        finish_session(&state.db, session).await?;
+        *active_session = None;
     }
+    state.timer_notify.notify_one();
     Ok(())
 }
 
-#[tauri::command]
-pub async fn has_active_session(state: tauri::State<'_, Arc<AppState>>) -> Result<bool, String> {
-    let active_session = state.active_session.read().await;
-    Ok(active_session.is_some())
+/// Adopts the timer persisted in the database when another process (the CLI) changed it.
+/// Returns true when the in-memory session was replaced.
+pub async fn sync_with_database(db: &Database, active_session: &mut Option<ActiveSession>) -> Result<bool, TimeboxError> {
+    let persisted = db.get_active_timer().await?;
+
+    let unchanged = match (active_session.as_ref(), persisted.as_ref()) {
+        (None, None) => true,
+        (Some(active), Some(timer)) => active.matches_timer_state(timer),
+        _ => false,
+    };
+    if unchanged {
+        return Ok(false);
+    }
+
+    *active_session = match persisted {
+        Some(timer) => match db.get_session(&timer.session_id).await? {
+            Some(session) if session.end_time.is_none() => Some(ActiveSession::from_timer_state(session, &timer)),
+            _ => None,
+        },
+        None => None,
+    };
+
+    Ok(true)
 }
 
 #[tauri::command]
-pub async fn save_active_session(state: tauri::State<'_, Arc<AppState>>) -> Result<(), String> {
+pub async fn has_active_session(state: tauri::State<'_, Arc<AppState>>) -> Result<bool, TimeboxError> {
     let mut active_session = state.active_session.write().await;
-    if let Some(session) = active_session.take() {
-        let time_remaining = if session.is_paused {
-            session.remaining_duration.num_seconds()
-        } else {
-            let elapsed = (Utc::now() - session.start_time).num_seconds();
-            (session.remaining_duration.num_seconds() - elapsed).max(0)
-        };
-        let duration = (session.total_duration.num_seconds() - time_remaining) as i32;
-        state.db.update_session(&session.session.id, Utc::now(), duration)
-            .await
-            .map_err(|e| e.to_string())?;
-    }
-    Ok(())
+    sync_with_database(&state.db, &mut active_session).await?;
+    Ok(active_session.is_some())
 }
 
 #[tauri::command]
-pub async fn get_timer_status(state: tauri::State<'_, Arc<AppState>>) -> Result<TimerStatusResponse, String> {
-    let active_session = state.active_session.read().await;
-    
+pub async fn save_active_session(state: tauri::State<'_, Arc<AppState>>) -> Result<(), TimeboxError> {
+    let mut active_session = state.active_session.write().await;
+    sync_with_database(&state.db, &mut active_session).await?;
     if let Some(session) = active_session.as_ref() {
-        let mut time_remaining = session.remaining_duration.num_seconds();
-        
-        if !session.is_paused {
-            let elapsed_since_resume = (Utc::now() - session.start_time).num_seconds();
-            time_remaining -= elapsed_since_resume;
-            
-            if time_remaining < 0 {
-                time_remaining = 0;
+        let stopped_early = matches!(session.session.session_type, SessionType::Focus) && session.time_remaining() > 0;
+        // Closing the app doesn't cut a strict focus session short: the persisted timer
+        // keeps running and is restored, or closed at its deadline, on the next start
+        let keep_running = stopped_early && state.db.get_or_create_settings(&session.session.user_id).await?.strict_mode;
+        if !keep_running {
+            if stopped_early {
+                state.db.mark_session_interrupted(&session.session.id, None).await?;
             }
+            finish_session(&state.db, session).await?;
+            *active_session = None;
         }
+    }
+    state.timer_notify.notify_one();
+    Ok(())
+}
+
+pub async fn build_timer_status(db: &Database, active_session: Option<&ActiveSession>) -> TimerStatusResponse {
+    if let Some(session) = active_session {
+        let time_remaining = session.time_remaining();
         
         let task_title = if let Some(task_id) = &session.session.task_id {
-            if let Ok(Some(task)) = state.db.get_task(task_id).await {
+            if let Ok(Some(task)) = db.get_task(task_id).await {
                 Some(task.title)
             } else {
                 None
@@ -231,7 +404,7 @@ pub async fn get_timer_status(state: tauri::State<'_, Arc<AppState>>) -> Result<
             None
         };
         
-        Ok(TimerStatusResponse {
+        TimerStatusResponse {
             time_remaining,
             is_running: true,
             is_paused: session.is_paused,
@@ -239,10 +412,10 @@ pub async fn get_timer_status(state: tauri::State<'_, Arc<AppState>>) -> Result<
             task_title,
             duration_minutes: session.total_duration.num_minutes(),
             interruption_count: session.session.interruption_count,
-        })
+        }
     } else {
         // Return default values when no active session
-        Ok(TimerStatusResponse {
+        TimerStatusResponse {
             time_remaining: 0,
             is_running: false,
             is_paused: false,
@@ -250,31 +423,31 @@ pub async fn get_timer_status(state: tauri::State<'_, Arc<AppState>>) -> Result<
             task_title: None,
             duration_minutes: 25,
             interruption_count: 0,
-        })
+        }
     }
 }
 
 #[tauri::command]
-pub async fn get_settings(state: tauri::State<'_, Arc<AppState>>, user_id: String) -> Result<PomodoroSettings, String> {
+pub async fn get_timer_status(state: tauri::State<'_, Arc<AppState>>) -> Result<TimerStatusResponse, TimeboxError> {
+    let mut active_session = state.active_session.write().await;
+    sync_with_database(&state.db, &mut active_session).await?;
+    Ok(build_timer_status(&state.db, active_session.as_ref()).await)
+}
+
+#[tauri::command]
+pub async fn get_cycle_state(state: tauri::State<'_, Arc<AppState>>, user_id: String) -> Result<CycleState, TimeboxError> {
+    cycle::current_cycle_state(&state.db, &user_id).await
+}
+
+#[tauri::command]
+pub async fn get_settings(state: tauri::State<'_, Arc<AppState>>, user_id: String) -> Result<PomodoroSettings, TimeboxError> {
     state.db.get_or_create_settings(&user_id).await
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn update_settings(state: tauri::State<'_, Arc<AppState>>, req: SettingsUpdateRequest) -> Result<(), String> {
-    let current_settings = state.db.get_settings(&req.user_id).await
-        .map_err(|e| e.to_string())?
-        .unwrap_or_else(|| PomodoroSettings {
-            user_id: req.user_id.clone(),
-            focus_minutes: 25,
-            short_break_minutes: 5,
-            long_break_minutes: 15,
-            cycles_before_long_break: 4,
-            strict_mode: false,
-            auto_start_breaks: false,
-            sound_enabled: true,
-            sound_volume: 70,
-        });
+pub async fn update_settings(state: tauri::State<'_, Arc<AppState>>, req: SettingsUpdateRequest) -> Result<(), TimeboxError> {
+    let current_settings = state.db.get_settings(&req.user_id).await?
+        .unwrap_or_else(|| PomodoroSettings::defaults(&req.user_id));
 
     let updated_settings = PomodoroSettings {
         user_id: req.user_id,
@@ -286,41 +459,63 @@ pub async fn update_settings(state: tauri::State<'_, Arc<AppState>>, req: Settin
         auto_start_breaks: req.auto_start_breaks.unwrap_or(current_settings.auto_start_breaks),
         sound_enabled: req.sound_enabled.unwrap_or(current_settings.sound_enabled),
         sound_volume: req.sound_volume.unwrap_or(current_settings.sound_volume),
+        daily_target_unit: req.daily_target_unit.unwrap_or(current_settings.daily_target_unit),
+        daily_target: req.daily_target.unwrap_or(current_settings.daily_target),
+        rest_days: req.rest_days.unwrap_or(current_settings.rest_days),
     };
 
-    state.db.update_settings(&updated_settings).await
-        .map_err(|e| e.to_string())?;
+    state.db.update_settings(&updated_settings.validated()?).await?;
     
     Ok(())
 }
 
+#[tauri::command]
+pub async fn set_user_timezone(
+    state: tauri::State<'_, Arc<AppState>>,
+    user_id: String,
+    timezone: String,
+) -> Result<User, TimeboxError> {
+    state.db.set_user_timezone(&user_id, &timezone).await
+}
+
 #[tauri::command]
 pub async fn create_task(
     state: tauri::State<'_, Arc<AppState>>,
     user_id: String,
     title: String,
     estimated_pomodoros: Option<i32>,
-) -> Result<Task, String> {
-    state.db.create_task(&user_id, &title, estimated_pomodoros).await
-        .map_err(|e| e.to_string())
+    goal_id: Option<String>,
+    project_id: Option<String>,
+    parent_task_id: Option<String>,
+) -> Result<Task, TimeboxError> {
+    state.db.create_task(
+        &user_id,
+        &title,
+        estimated_pomodoros,
+        goal_id.as_deref(),
+        project_id.as_deref(),
+        parent_task_id.as_deref(),
+    ).await
 }
 
 #[tauri::command]
-pub async fn get_tasks(state: tauri::State<'_, Arc<AppState>>, user_id: String) -> Result<Vec<Task>, String> {
-    state.db.get_tasks(&user_id).await
-        .map_err(|e| e.to_string())
+pub async fn get_tasks(
+    state: tauri::State<'_, Arc<AppState>>,
+    user_id: String,
+    project_id: Option<String>,
+    tag: Option<String>,
+) -> Result<Vec<Task>, TimeboxError> {
+    state.db.get_tasks(&user_id, &ProjectTagFilter { project_id, tag }).await
 }
 
 #[tauri::command]
-pub async fn get_sessions(state: tauri::State<'_, Arc<AppState>>, user_id: String) -> Result<Vec<PomodoroSession>, String> {
+pub async fn get_sessions(state: tauri::State<'_, Arc<AppState>>, user_id: String) -> Result<Vec<PomodoroSession>, TimeboxError> {
     state.db.get_sessions(&user_id, None).await
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn get_today_sessions(state: tauri::State<'_, Arc<AppState>>, user_id: String) -> Result<Vec<PomodoroSession>, String> {
+pub async fn get_today_sessions(state: tauri::State<'_, Arc<AppState>>, user_id: String) -> Result<Vec<PomodoroSession>, TimeboxError> {
     state.db.get_today_sessions(&user_id).await
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -333,33 +528,44 @@ pub async fn create_goal(
     motivation: Option<String>,
     target_date: Option<DateTime<Utc>>,
     description: Option<String>,
-) -> Result<Goal, String> {
+) -> Result<Goal, TimeboxError> {
     state.db.create_goal(&user_id, &title, target_pomodoros, category, motivation, target_date, description).await
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn get_goals(state: tauri::State<'_, Arc<AppState>>, user_id: String) -> Result<Vec<Goal>, String> {
+pub async fn get_goals(state: tauri::State<'_, Arc<AppState>>, user_id: String) -> Result<Vec<Goal>, TimeboxError> {
     state.db.get_goals(&user_id).await
-        .map_err(|e| e.to_string())
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct RecordInterruptionRequest {
+    // Defaults to external
+    pub kind: Option<InterruptionKind>,
+    pub reason: Option<String>,
+    pub category: Option<String>,
 }
 
 #[tauri::command]
-pub async fn record_interruption(state: tauri::State<'_, Arc<AppState>>) -> Result<i32, String> {
+pub async fn record_interruption(
+    state: tauri::State<'_, Arc<AppState>>,
+    req: Option<RecordInterruptionRequest>,
+) -> Result<i32, TimeboxError> {
+    record_active_interruption(&state, req.unwrap_or_default()).await
+}
+
+pub async fn record_active_interruption(state: &AppState, req: RecordInterruptionRequest) -> Result<i32, TimeboxError> {
     let mut active_session = state.active_session.write().await;
+    sync_with_database(&state.db, &mut active_session).await?;
     if let Some(session) = active_session.as_mut() {
-        // Increment interruption count in memory
+        let reason = req.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+        let category = req.category.as_deref().map(str::trim).filter(|c| !c.is_empty());
+        state.db.record_interruption(&session.session, req.kind.unwrap_or_default(), reason, category).await?;
+
+        // Keep the in-memory count in step with the database
         session.session.interruption_count += 1;
-        let new_count = session.session.interruption_count;
-        let session_id = session.session.id.clone();
-        
-        // Update in database
-        state.db.increment_interruption_count(&session_id).await
-            .map_err(|e| e.to_string())?;
-        
-        Ok(new_count)
+        Ok(session.session.interruption_count)
     } else {
-        Err("No active session".to_string())
+        Err(TimeboxError::NoActiveSession)
     }
 }
 
@@ -367,23 +573,134 @@ pub async fn record_interruption(state: tauri::State<'_, Arc<AppState>>) -> Resu
 pub async fn update_task(
     state: tauri::State<'_, Arc<AppState>>,
     req: UpdateTaskRequest,
-) -> Result<Task, String> {
-    state.db.update_task(
-        &req.task_id,
-        req.title.as_deref(),
-        req.estimated_pomodoros,
-        req.completed,
-    ).await
-    .map_err(|e| e.to_string())
+) -> Result<Task, TimeboxError> {
+    state.db.update_task(&req.task_id, req.changes).await
+}
+
+#[tauri::command]
+pub async fn reorder_tasks(
+    state: tauri::State<'_, Arc<AppState>>,
+    user_id: String,
+    task_ids: Vec<String>,
+) -> Result<Vec<Task>, TimeboxError> {
+    state.db.reorder_tasks(&user_id, &task_ids).await
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetDailyPlanRequest {
+    pub user_id: String,
+    // Defaults to today in the user's timezone
+    pub date: Option<NaiveDate>,
+    pub available_minutes: Option<i32>,
+}
+
+#[tauri::command]
+pub async fn get_daily_plan(state: tauri::State<'_, Arc<AppState>>, req: GetDailyPlanRequest) -> Result<DailyPlan, TimeboxError> {
+    state.db.get_daily_plan(&req.user_id, req.date, req.available_minutes).await
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetUpcomingOccurrencesRequest {
+    pub user_id: String,
+    // Defaults to two weeks
+    pub days: Option<i64>,
+}
+
+#[tauri::command]
+pub async fn get_upcoming_occurrences(
+    state: tauri::State<'_, Arc<AppState>>,
+    req: GetUpcomingOccurrencesRequest,
+) -> Result<Vec<UpcomingOccurrence>, TimeboxError> {
+    state.db.get_upcoming_occurrences(&req.user_id, req.days.unwrap_or(DEFAULT_UPCOMING_DAYS)).await
 }
 
 #[tauri::command]
 pub async fn delete_task(
     state: tauri::State<'_, Arc<AppState>>,
     task_id: String,
-) -> Result<(), String> {
+) -> Result<(), TimeboxError> {
     state.db.delete_task(&task_id).await
-        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn assign_task_to_goal(
+    state: tauri::State<'_, Arc<AppState>>,
+    task_id: String,
+    goal_id: Option<String>,
+) -> Result<Task, TimeboxError> {
+    state.db.set_task_goal(&task_id, goal_id.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn assign_task_to_project(
+    state: tauri::State<'_, Arc<AppState>>,
+    task_id: String,
+    project_id: Option<String>,
+) -> Result<Task, TimeboxError> {
+    state.db.set_task_project(&task_id, project_id.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn set_task_tags(
+    state: tauri::State<'_, Arc<AppState>>,
+    task_id: String,
+    tags: Vec<String>,
+) -> Result<Task, TimeboxError> {
+    state.db.set_task_tags(&task_id, &tags).await
+}
+
+#[tauri::command]
+pub async fn set_task_parent(
+    state: tauri::State<'_, Arc<AppState>>,
+    task_id: String,
+    parent_task_id: Option<String>,
+) -> Result<Task, TimeboxError> {
+    state.db.set_task_parent(&task_id, parent_task_id.as_deref()).await
+}
+
+// Checklist Commands
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateChecklistItemRequest {
+    pub item_id: String,
+    #[serde(flatten)]
+    pub changes: ChecklistItemChanges,
+}
+
+#[tauri::command]
+pub async fn get_checklist_items(state: tauri::State<'_, Arc<AppState>>, task_id: String) -> Result<Vec<ChecklistItem>, TimeboxError> {
+    state.db.get_checklist_items(&task_id).await
+}
+
+#[tauri::command]
+pub async fn add_checklist_item(
+    state: tauri::State<'_, Arc<AppState>>,
+    task_id: String,
+    title: String,
+) -> Result<ChecklistItem, TimeboxError> {
+    state.db.add_checklist_item(&task_id, &title).await
+}
+
+#[tauri::command]
+pub async fn update_checklist_item(
+    state: tauri::State<'_, Arc<AppState>>,
+    req: UpdateChecklistItemRequest,
+) -> Result<ChecklistItem, TimeboxError> {
+    state.db.update_checklist_item(&req.item_id, req.changes).await
+}
+
+#[tauri::command]
+pub async fn reorder_checklist_items(
+    state: tauri::State<'_, Arc<AppState>>,
+    task_id: String,
+    item_ids: Vec<String>,
+) -> Result<Vec<ChecklistItem>, TimeboxError> {
+    state.db.reorder_checklist_items(&task_id, &item_ids).await
+}
+
+#[tauri::command]
+pub async fn delete_checklist_item(state: tauri::State<'_, Arc<AppState>>, item_id: String) -> Result<(), TimeboxError> {
+    state.db.delete_checklist_item(&item_id).await
 }
 
 #[derive(Serialize, Deserialize)]
@@ -402,15 +719,11 @@ pub struct UpdateGoalRequest {
 pub async fn get_tasks_with_pomodoro_counts(
     state: tauri::State<'_, Arc<AppState>>,
     user_id: String,
-) -> Result<Vec<TaskWithPomodoroCount>, String> {
-    let tasks_with_counts = state.db.get_tasks_with_pomodoro_counts(&user_id).await
-        .map_err(|e| e.to_string())?;
+) -> Result<Vec<TaskWithPomodoroCount>, TimeboxError> {
+    let tasks_with_counts = state.db.get_tasks_with_pomodoro_counts(&user_id).await?;
     
     let result = tasks_with_counts.into_iter()
-        .map(|(task, count)| TaskWithPomodoroCount {
-            task,
-            actual_pomodoros: count,
-        })
+        .map(|(task, counts)| TaskWithPomodoroCount { task, counts })
         .collect();
     
     Ok(result)
@@ -420,7 +733,7 @@ pub async fn get_tasks_with_pomodoro_counts(
 pub async fn update_goal(
     state: tauri::State<'_, Arc<AppState>>,
     req: UpdateGoalRequest,
-) -> Result<(), String> {
+) -> Result<(), TimeboxError> {
     state.db.update_goal(
         &req.goal_id, 
         req.title.as_deref(), 
@@ -431,16 +744,22 @@ pub async fn update_goal(
         req.target_date,
         req.description
     ).await
-    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn delete_goal(
     state: tauri::State<'_, Arc<AppState>>,
     goal_id: String,
-) -> Result<(), String> {
+) -> Result<(), TimeboxError> {
     state.db.delete_goal(&goal_id).await
-        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_goal_progress(
+    state: tauri::State<'_, Arc<AppState>>,
+    goal_id: String,
+) -> Result<GoalProgress, TimeboxError> {
+    state.db.get_goal_progress(&goal_id).await
 }
 
 #[derive(Serialize, Deserialize)]
@@ -449,20 +768,82 @@ pub struct GetSessionsByDateRangeRequest {
     pub start_date: DateTime<Utc>,
     pub end_date: DateTime<Utc>,
     pub session_type: Option<SessionType>,
+    #[serde(flatten)]
+    pub filter: ProjectTagFilter,
 }
 
 #[tauri::command]
 pub async fn get_sessions_by_date_range(
     state: tauri::State<'_, Arc<AppState>>,
     req: GetSessionsByDateRangeRequest,
-) -> Result<Vec<PomodoroSession>, String> {
+) -> Result<Vec<PomodoroSession>, TimeboxError> {
     state.db.get_sessions_by_date_range(
         &req.user_id,
         req.start_date,
         req.end_date,
         req.session_type,
+        &req.filter,
     ).await
-    .map_err(|e| e.to_string())
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FocusStatsRequest {
+    pub user_id: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    // Defaults to day
+    pub granularity: Option<Granularity>,
+    #[serde(flatten)]
+    pub filter: ProjectTagFilter,
+}
+
+#[tauri::command]
+pub async fn get_focus_stats(
+    state: tauri::State<'_, Arc<AppState>>,
+    req: FocusStatsRequest,
+) -> Result<FocusStats, TimeboxError> {
+    state.db.get_focus_stats(
+        &req.user_id,
+        req.start_date,
+        req.end_date,
+        req.granularity.unwrap_or(Granularity::Day),
+        &req.filter,
+    ).await
+}
+
+#[tauri::command]
+pub async fn get_streaks(state: tauri::State<'_, Arc<AppState>>, user_id: String) -> Result<StreakSummary, TimeboxError> {
+    state.db.get_streaks(&user_id).await
+}
+
+#[tauri::command]
+pub async fn get_session_pauses(state: tauri::State<'_, Arc<AppState>>, session_id: String) -> Result<Vec<SessionPause>, TimeboxError> {
+    state.db.get_session_pauses(&session_id).await
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InterruptionRangeRequest {
+    pub user_id: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    // Number of sources returned by get_interruption_sources, 10 when omitted
+    pub limit: Option<i64>,
+}
+
+#[tauri::command]
+pub async fn get_interruptions(
+    state: tauri::State<'_, Arc<AppState>>,
+    req: InterruptionRangeRequest,
+) -> Result<Vec<Interruption>, TimeboxError> {
+    state.db.get_interruptions(&req.user_id, req.start_date, req.end_date).await
+}
+
+#[tauri::command]
+pub async fn get_interruption_sources(
+    state: tauri::State<'_, Arc<AppState>>,
+    req: InterruptionRangeRequest,
+) -> Result<Vec<InterruptionSource>, TimeboxError> {
+    state.db.get_interruption_sources(&req.user_id, req.start_date, req.end_date, req.limit.unwrap_or(10).max(1)).await
 }
 
 // Manual Session Logging
@@ -470,25 +851,145 @@ pub async fn get_sessions_by_date_range(
 #[derive(Serialize, Deserialize)]
 pub struct LogManualSessionRequest {
     pub user_id: String,
-    pub task_id: Option<String>,
-    pub start_time: DateTime<Utc>,
-    pub end_time: DateTime<Utc>,
-    pub duration_seconds: i32,
+    #[serde(flatten)]
+    pub session: ManualSession,
 }
 
 #[tauri::command]
 pub async fn log_manual_session(
     state: tauri::State<'_, Arc<AppState>>,
     req: LogManualSessionRequest,
-) -> Result<PomodoroSession, String> {
-    state.db.create_manual_session(
-        &req.user_id,
-        req.task_id,
-        req.start_time,
-        req.end_time,
-        req.duration_seconds,
-    ).await
-    .map_err(|e| e.to_string())
+) -> Result<Vec<PomodoroSession>, TimeboxError> {
+    state.db.create_manual_session(&req.user_id, req.session).await
+}
+
+// Project Commands
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateProjectRequest {
+    pub user_id: String,
+    pub name: String,
+    pub color: Option<String>,
+    pub hourly_rate: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateProjectRequest {
+    pub project_id: String,
+    #[serde(flatten)]
+    pub changes: ProjectChanges,
+}
+
+#[tauri::command]
+pub async fn create_project(
+    state: tauri::State<'_, Arc<AppState>>,
+    req: CreateProjectRequest,
+) -> Result<Project, TimeboxError> {
+    state.db.create_project(&req.user_id, &req.name, req.color.as_deref(), req.hourly_rate).await
+}
+
+#[tauri::command]
+pub async fn get_projects(
+    state: tauri::State<'_, Arc<AppState>>,
+    user_id: String,
+    include_archived: Option<bool>,
+) -> Result<Vec<Project>, TimeboxError> {
+    state.db.get_projects(&user_id, include_archived.unwrap_or(false)).await
+}
+
+#[tauri::command]
+pub async fn update_project(
+    state: tauri::State<'_, Arc<AppState>>,
+    req: UpdateProjectRequest,
+) -> Result<Project, TimeboxError> {
+    state.db.update_project(&req.project_id, req.changes).await
+}
+
+#[tauri::command]
+pub async fn delete_project(state: tauri::State<'_, Arc<AppState>>, project_id: String) -> Result<(), TimeboxError> {
+    state.db.delete_project(&project_id).await
+}
+
+// Tag Commands
+
+#[tauri::command]
+pub async fn create_tag(state: tauri::State<'_, Arc<AppState>>, user_id: String, name: String) -> Result<Tag, TimeboxError> {
+    state.db.create_tag(&user_id, &name).await
+}
+
+#[tauri::command]
+pub async fn get_tags(state: tauri::State<'_, Arc<AppState>>, user_id: String) -> Result<Vec<Tag>, TimeboxError> {
+    state.db.get_tags(&user_id).await
+}
+
+#[tauri::command]
+pub async fn rename_tag(state: tauri::State<'_, Arc<AppState>>, tag_id: String, name: String) -> Result<Tag, TimeboxError> {
+    state.db.rename_tag(&tag_id, &name).await
+}
+
+#[tauri::command]
+pub async fn delete_tag(state: tauri::State<'_, Arc<AppState>>, tag_id: String) -> Result<(), TimeboxError> {
+    state.db.delete_tag(&tag_id).await
+}
+
+// Session History Commands
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateSessionRequest {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub changes: SessionChanges,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SplitSessionRequest {
+    pub session_id: String,
+    pub split_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MergeSessionsRequest {
+    pub first_session_id: String,
+    pub second_session_id: String,
+}
+
+#[tauri::command]
+pub async fn update_session(
+    state: tauri::State<'_, Arc<AppState>>,
+    req: UpdateSessionRequest,
+) -> Result<PomodoroSession, TimeboxError> {
+    state.db.edit_session(&req.session_id, req.changes).await
+}
+
+#[tauri::command]
+pub async fn delete_session(state: tauri::State<'_, Arc<AppState>>, session_id: String) -> Result<(), TimeboxError> {
+    state.db.delete_session(&session_id).await
+}
+
+#[tauri::command]
+pub async fn split_session(
+    state: tauri::State<'_, Arc<AppState>>,
+    req: SplitSessionRequest,
+) -> Result<Vec<PomodoroSession>, TimeboxError> {
+    let (first, second) = state.db.split_session(&req.session_id, req.split_at).await?;
+    Ok(vec![first, second])
+}
+
+#[tauri::command]
+pub async fn merge_sessions(
+    state: tauri::State<'_, Arc<AppState>>,
+    req: MergeSessionsRequest,
+) -> Result<PomodoroSession, TimeboxError> {
+    state.db.merge_sessions(&req.first_session_id, &req.second_session_id).await
+}
+
+#[tauri::command]
+pub async fn get_session_audit(
+    state: tauri::State<'_, Arc<AppState>>,
+    user_id: String,
+    session_id: Option<String>,
+) -> Result<Vec<SessionAuditEntry>, TimeboxError> {
+    state.db.get_session_audit(&user_id, session_id.as_deref()).await
 }
 
 // Daily Reflection Commands
@@ -528,7 +1029,7 @@ pub struct GetDayActivitiesRequest {
 pub async fn save_daily_reflection(
     state: tauri::State<'_, Arc<AppState>>,
     req: SaveReflectionRequest,
-) -> Result<DailyReflection, String> {
+) -> Result<DailyReflection, TimeboxError> {
     state.db.create_or_update_reflection(
         &req.user_id,
         req.reflection_date,
@@ -539,32 +1040,144 @@ pub async fn save_daily_reflection(
         req.mood_rating,
         req.productivity_rating,
     ).await
-    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn get_daily_reflection(
     state: tauri::State<'_, Arc<AppState>>,
     req: GetReflectionRequest,
-) -> Result<Option<DailyReflection>, String> {
+) -> Result<Option<DailyReflection>, TimeboxError> {
     state.db.get_reflection_by_date(&req.user_id, req.reflection_date).await
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn get_reflections_by_month(
     state: tauri::State<'_, Arc<AppState>>,
     req: GetReflectionsByMonthRequest,
-) -> Result<Vec<DailyReflection>, String> {
+) -> Result<Vec<DailyReflection>, TimeboxError> {
     state.db.get_reflections_by_month(&req.user_id, req.year, req.month).await
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn get_day_activities(
     state: tauri::State<'_, Arc<AppState>>,
     req: GetDayActivitiesRequest,
-) -> Result<DayActivities, String> {
+) -> Result<DayActivities, TimeboxError> {
     state.db.get_day_activities(&req.user_id, req.date).await
-        .map_err(|e| e.to_string())
-}
\ No newline at end of file
+}
+
+#[tauri::command]
+pub async fn export_data(
+    app: AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    directory: Option<String>,
+) -> Result<ExportSummary, TimeboxError> {
+    let directory = match directory {
+        Some(directory) => PathBuf::from(directory),
+        None => {
+            let downloads = app.path().download_dir()
+                .map_err(|_| TimeboxError::Validation("No download directory available, choose an export directory".to_string()))?;
+            downloads.join(export::default_directory_name(Utc::now()))
+        }
+    };
+
+    export::export_to_directory(&state.db, &directory).await
+}
+
+#[tauri::command]
+pub async fn import_data(
+    state: tauri::State<'_, Arc<AppState>>,
+    req: ImportRequest,
+) -> Result<ImportReport, TimeboxError> {
+    // Replacing would delete the session the timer is counting down, including one the CLI just started
+    if req.mode == Some(ImportMode::Replace) {
+        let mut active_session = state.active_session.write().await;
+        sync_with_database(&state.db, &mut active_session).await?;
+        if active_session.is_some() {
+            return Err(TimeboxError::Conflict("Stop the running session before replacing all data".to_string()));
+        }
+    }
+
+    importer::import_file(&state.db, &req).await
+}
+
+#[tauri::command]
+pub async fn create_backup(
+    app: AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<BackupInfo, TimeboxError> {
+    backup::create_backup(&state.db, &app_data_dir(&app)?, BackupKind::Regular).await
+}
+
+#[tauri::command]
+pub async fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, TimeboxError> {
+    backup::list_backups(&app_data_dir(&app)?)
+}
+
+/// Replaces the database with a backup by staging it and restarting the app,
+/// so the swap happens before the database is opened again.
+#[tauri::command]
+pub async fn restore_backup(
+    app: AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    name: String,
+) -> Result<(), TimeboxError> {
+    {
+        let mut active_session = state.active_session.write().await;
+        sync_with_database(&state.db, &mut active_session).await?;
+        if active_session.is_some() {
+            return Err(TimeboxError::Conflict("Stop the running session before restoring a backup".to_string()));
+        }
+    }
+
+    backup::stage_restore(&app_data_dir(&app)?, &name).await?;
+    state.db.close().await;
+
+    app.restart()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateApiSettingsRequest {
+    pub enabled: Option<bool>,
+    pub port: Option<u16>,
+}
+
+#[tauri::command]
+pub async fn get_api_settings(state: tauri::State<'_, Arc<AppState>>) -> Result<ApiSettings, TimeboxError> {
+    state.db.get_api_settings().await
+}
+
+/// Saves the HTTP API settings and starts, restarts or stops the server to match.
+/// If the server can't start on the new settings the previous ones are put back.
+#[tauri::command]
+pub async fn update_api_settings(
+    state: tauri::State<'_, Arc<AppState>>,
+    api: tauri::State<'_, HttpApi>,
+    req: UpdateApiSettingsRequest,
+) -> Result<ApiSettings, TimeboxError> {
+    let previous = state.db.get_api_settings().await?;
+    let settings = state.db.update_api_settings(req.enabled, req.port).await?;
+
+    if let Err(e) = api.apply(state.inner().clone(), &settings).await {
+        state.db.update_api_settings(Some(previous.enabled), Some(previous.port)).await?;
+        api.apply(state.inner().clone(), &previous).await?;
+        return Err(e);
+    }
+
+    Ok(settings)
+}
+
+#[tauri::command]
+pub async fn regenerate_api_token(
+    state: tauri::State<'_, Arc<AppState>>,
+    api: tauri::State<'_, HttpApi>,
+) -> Result<ApiSettings, TimeboxError> {
+    let settings = state.db.regenerate_api_token().await?;
+    api.apply(state.inner().clone(), &settings).await?;
+    Ok(settings)
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, TimeboxError> {
+    app.path().app_data_dir()
+        .map_err(|e| TimeboxError::Io(std::io::Error::new(std::io::ErrorKind::NotFound, e.to_string())))
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use crate::database::{Database, SessionType};
+use crate::error::TimeboxError;
+use crate::timezone;
+
+// Position in the Pomodoro cycle: focus sessions completed since the last long break,
+// and which phase should come next.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CycleState {
+    pub completed_in_cycle: i32,
+    pub cycles_before_long_break: i32,
+    pub next_phase: SessionType,
+}
+
+impl CycleState {
+    pub fn new(completed_in_cycle: i32, cycles_before_long_break: i32, last_phase: Option<SessionType>) -> Self {
+        let cycles_before_long_break = cycles_before_long_break.max(1);
+
+        // A break always follows a focus session; anything else is followed by focus
+        let next_phase = match last_phase {
+            Some(SessionType::Focus) if completed_in_cycle >= cycles_before_long_break => SessionType::LongBreak,
+            Some(SessionType::Focus) => SessionType::ShortBreak,
+            _ => SessionType::Focus,
+        };
+
+        CycleState {
+            completed_in_cycle,
+            cycles_before_long_break,
+            next_phase,
+        }
+    }
+}
+
+/// Derives the cycle from the finished sessions of the user's current local day, so it survives restarts.
+/// A long break (or the start of a new day) resets the count.
+pub async fn current_cycle_state(db: &Database, user_id: &str) -> Result<CycleState, TimeboxError> {
+    let settings = db.get_or_create_settings(user_id).await?;
+    let tz = db.get_user_timezone(user_id).await?;
+    let today_start = timezone::start_of_day(tz, timezone::today(tz));
+
+    let (completed_in_cycle, last_phase) = db.get_cycle_progress(user_id, today_start).await?;
+
+    Ok(CycleState::new(completed_in_cycle, settings.cycles_before_long_break, last_phase))
+}
@@ -1,8 +1,19 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Row, Sqlite, SqlitePool};
+use std::collections::HashMap;
+use std::path::Path;
+use sqlx::query::Query;
+use sqlx::sqlite::{SqliteArguments, SqliteRow};
+use sqlx::{Pool, Row, Sqlite, SqlitePool, Transaction};
 use uuid::Uuid;
 
+use crate::error::TimeboxError;
+use crate::importer::{ImportMode, ImportReport, ImportTableReport, ImportedSession};
+use crate::migrations;
+use crate::recurrence::Recurrence;
+use crate::timezone;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct User {
     pub id: String,
@@ -22,6 +33,193 @@ pub struct PomodoroSettings {
     pub auto_start_breaks: bool,
     pub sound_enabled: bool,
     pub sound_volume: i32,
+    // Archives exported before daily targets existed lack these fields
+    #[serde(default)]
+    pub daily_target_unit: DailyTargetUnit,
+    // Minutes or Pomodoros of focus per day; 0 turns the target and streaks off
+    #[serde(default)]
+    pub daily_target: i32,
+    // Weekdays that don't break a streak when the target is missed, 0 = Monday ... 6 = Sunday
+    #[serde(default)]
+    pub rest_days: Vec<u8>,
+}
+
+impl PomodoroSettings {
+    pub fn defaults(user_id: &str) -> Self {
+        PomodoroSettings {
+            user_id: user_id.to_string(),
+            focus_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            cycles_before_long_break: 4,
+            strict_mode: false,
+            auto_start_breaks: false,
+            sound_enabled: true,
+            sound_volume: 70,
+            daily_target_unit: DailyTargetUnit::Pomodoros,
+            daily_target: 0,
+            rest_days: Vec::new(),
+        }
+    }
+
+    /// Checks the values the timer and daily plan rely on, and sorts the rest days.
+    pub fn validated(mut self) -> Result<Self, TimeboxError> {
+        let durations = [
+            self.focus_minutes,
+            self.short_break_minutes,
+            self.long_break_minutes,
+            self.cycles_before_long_break,
+        ];
+        if durations.iter().any(|&value| value <= 0) {
+            return Err(TimeboxError::Validation("Durations and cycle length must be greater than zero".to_string()));
+        }
+        if !(0..=100).contains(&self.sound_volume) {
+            return Err(TimeboxError::Validation("Sound volume must be between 0 and 100".to_string()));
+        }
+        if self.daily_target < 0 {
+            return Err(TimeboxError::Validation("Daily target can't be negative".to_string()));
+        }
+        if self.rest_days.iter().any(|&day| day > 6) {
+            return Err(TimeboxError::Validation("Rest days must be weekdays from 0 (Monday) to 6 (Sunday)".to_string()));
+        }
+
+        self.rest_days.sort_unstable();
+        self.rest_days.dedup();
+        if self.rest_days.len() == 7 {
+            return Err(TimeboxError::Validation("At least one day of the week must not be a rest day".to_string()));
+        }
+
+        Ok(self)
+    }
+
+    /// Focus sessions that fit into `minutes` when every one is followed by its break.
+    /// The break after the last session doesn't have to fit.
+    pub fn focus_capacity(&self, minutes: i32) -> i64 {
+        if self.focus_minutes <= 0 {
+            return 0;
+        }
+        let mut elapsed = 0;
+        let mut sessions = 0;
+        while elapsed + self.focus_minutes <= minutes {
+            elapsed += self.focus_minutes;
+            sessions += 1;
+            elapsed += if self.cycles_before_long_break > 0 && sessions % self.cycles_before_long_break as i64 == 0 {
+                self.long_break_minutes
+            } else {
+                self.short_break_minutes
+            };
+        }
+        sessions
+    }
+}
+
+// Working time a daily plan is measured against unless the caller says otherwise
+pub const DEFAULT_PLAN_MINUTES: i32 = 8 * 60;
+
+// How far ahead upcoming occurrences of routines are listed unless the caller says otherwise
+pub const DEFAULT_UPCOMING_DAYS: i64 = 14;
+pub const MAX_UPCOMING_DAYS: i64 = 366;
+
+// Longest range focus statistics and streaks are computed over; timezone offsets are resolved across the
+// whole range, so it has to stay bounded
+pub const MAX_STATS_DAYS: i64 = 10 * 366;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DailyTargetUnit {
+    Minutes,
+    #[default]
+    Pomodoros,
+}
+
+impl DailyTargetUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DailyTargetUnit::Minutes => "minutes",
+            DailyTargetUnit::Pomodoros => "pomodoros",
+        }
+    }
+
+    pub fn from_db_str(value: &str) -> DailyTargetUnit {
+        match value {
+            "minutes" => DailyTargetUnit::Minutes,
+            _ => DailyTargetUnit::Pomodoros,
+        }
+    }
+}
+
+// Stored as a comma-separated list such as "5,6"
+fn rest_days_to_db(rest_days: &[u8]) -> String {
+    rest_days.iter().map(|day| day.to_string()).collect::<Vec<_>>().join(",")
+}
+
+fn rest_days_from_db(value: &str) -> Vec<u8> {
+    value.split(',').filter_map(|day| day.trim().parse().ok()).collect()
+}
+
+// Internal interruptions come from the person focusing, external ones from someone or something else
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptionKind {
+    Internal,
+    #[default]
+    External,
+}
+
+impl InterruptionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InterruptionKind::Internal => "internal",
+            InterruptionKind::External => "external",
+        }
+    }
+
+    pub fn from_db_str(value: &str) -> InterruptionKind {
+        match value {
+            "internal" => InterruptionKind::Internal,
+            _ => InterruptionKind::External,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskPriority {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+impl TaskPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskPriority::None => "none",
+            TaskPriority::Low => "low",
+            TaskPriority::Medium => "medium",
+            TaskPriority::High => "high",
+        }
+    }
+
+    // Stored as a number so tasks can be sorted by priority in SQL
+    pub fn as_db(&self) -> i32 {
+        match self {
+            TaskPriority::None => 0,
+            TaskPriority::Low => 1,
+            TaskPriority::Medium => 2,
+            TaskPriority::High => 3,
+        }
+    }
+
+    pub fn from_db(value: i32) -> TaskPriority {
+        match value {
+            1 => TaskPriority::Low,
+            2 => TaskPriority::Medium,
+            3 => TaskPriority::High,
+            _ => TaskPriority::None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -31,6 +229,67 @@ pub struct Task {
     pub title: String,
     pub estimated_pomodoros: i32,
     pub completed: bool,
+    // Missing from archives written before completion times were recorded
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+    pub goal_id: Option<String>,
+    // Missing from archives written before projects and tags existed
+    #[serde(default)]
+    pub project_id: Option<String>,
+    // Tag names, sorted
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub parent_task_id: Option<String>,
+    // Local date in the user's timezone
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub priority: TaskPriority,
+    // Manual order of the task list, lowest first
+    #[serde(default)]
+    pub position: i32,
+    // Day the task is queued for in the daily plan
+    #[serde(default)]
+    pub planned_for: Option<NaiveDate>,
+    // Normalized RRULE of a routine; `due_date` is the occurrence this instance stands for
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    // Instance of the routine this one was created after
+    #[serde(default)]
+    pub recurs_from: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Project {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    // CSS color such as "#4f46e5"
+    pub color: Option<String>,
+    // Archived projects are hidden from pickers but keep their tasks and history
+    pub archived: bool,
+    pub hourly_rate: Option<f64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Tag {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ChecklistItem {
+    pub id: String,
+    pub task_id: String,
+    pub title: String,
+    pub completed: bool,
+    // Items are listed by position, starting at 0
+    pub position: i32,
     pub created_at: DateTime<Utc>,
 }
 
@@ -54,6 +313,17 @@ impl ToString for SessionType {
     }
 }
 
+impl SessionType {
+    pub fn from_db_str(value: &str) -> SessionType {
+        match value {
+            "FOCUS" => SessionType::Focus,
+            "SHORT_BREAK" => SessionType::ShortBreak,
+            "LONG_BREAK" => SessionType::LongBreak,
+            _ => SessionType::Focus,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct PomodoroSession {
     pub id: String,
@@ -65,12 +335,70 @@ pub struct PomodoroSession {
     pub duration_seconds: Option<i32>,
     pub interrupted: bool,
     pub interruption_count: i32,
+    // Totals of the session's rows in session_pauses, kept up to date as pauses end
+    #[serde(default)]
+    pub pause_count: i32,
+    #[serde(default)]
+    pub paused_seconds: i64,
     pub manual_override: bool,
+    // Reason given when a focus session was stopped early; required under strict mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
     pub created_at: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub task_title: Option<String>,
+    // Tags of the session itself, sorted; the task's tags are not repeated here
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+// One interval a session spent paused; `resumed_at` is None while it is still paused
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionPause {
+    pub id: String,
+    pub session_id: String,
+    pub paused_at: DateTime<Utc>,
+    pub resumed_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Interruption {
+    pub id: String,
+    pub session_id: String,
+    pub user_id: String,
+    pub occurred_at: DateTime<Utc>,
+    pub kind: InterruptionKind,
+    pub reason: Option<String>,
+    // Free-form source such as "Slack", "meeting" or "self"
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_title: Option<String>,
+}
+
+// Snapshot of the running timer, persisted so it survives crashes and restarts.
+// `anchor_time` is the moment `remaining_seconds` was measured at.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ActiveTimerState {
+    pub session_id: String,
+    pub remaining_seconds: i64,
+    pub total_seconds: i64,
+    pub is_paused: bool,
+    pub anchor_time: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
+// Per-install settings of the local HTTP API, which is off until the user enables it
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String,
+}
+
+pub const DEFAULT_API_PORT: u16 = 7315;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Goal {
     pub id: String,
@@ -106,7 +434,7 @@ pub struct Database {
 }
 
 impl Database {
-    pub async fn new(db_path: &str) -> Result<Self, sqlx::Error> {
+    pub async fn new(db_path: &str) -> Result<Self, TimeboxError> {
         let pool = SqlitePool::connect(db_path).await?;
         
         // Run migrations
@@ -115,123 +443,45 @@ impl Database {
         Ok(Database { pool })
     }
 
-    pub async fn run_migrations(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
-        // Create tables
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS users (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                timezone TEXT DEFAULT 'UTC',
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )
-            "#
-        ).execute(pool).await?;
+    /// Opens the database file at `path`, creating it and its directory if needed.
+    pub async fn open(path: &Path) -> Result<Self, TimeboxError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
 
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS pomodoro_settings (
-                user_id TEXT PRIMARY KEY,
-                focus_minutes INTEGER DEFAULT 25,
-                short_break_minutes INTEGER DEFAULT 5,
-                long_break_minutes INTEGER DEFAULT 15,
-                cycles_before_long_break INTEGER DEFAULT 4,
-                strict_mode BOOLEAN DEFAULT 0,
-                auto_start_breaks BOOLEAN DEFAULT 0,
-                sound_enabled BOOLEAN DEFAULT 1,
-                sound_volume INTEGER DEFAULT 70,
-                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-            )
-            "#
-        ).execute(pool).await?;
+        // SQLx requires the file to exist before connecting
+        if !path.exists() {
+            std::fs::File::create(path)?;
+        }
 
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS tasks (
-                id TEXT PRIMARY KEY,
-                user_id TEXT NOT NULL,
-                title TEXT NOT NULL,
-                estimated_pomodoros INTEGER DEFAULT 1,
-                completed BOOLEAN DEFAULT 0,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-            )
-            "#
-        ).execute(pool).await?;
+        let path_str = path.to_str()
+            .ok_or_else(|| TimeboxError::Validation(format!("Invalid database path {}", path.display())))?;
 
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS pomodoro_sessions (
-                id TEXT PRIMARY KEY,
-                user_id TEXT NOT NULL,
-                task_id TEXT,
-                session_type TEXT CHECK(session_type IN ('FOCUS', 'SHORT_BREAK', 'LONG_BREAK')) NOT NULL,
-                start_time DATETIME NOT NULL,
-                end_time DATETIME,
-                duration_seconds INTEGER,
-                interrupted BOOLEAN DEFAULT 0,
-                interruption_count INTEGER DEFAULT 0,
-                manual_override BOOLEAN DEFAULT 0,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
-                FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE SET NULL
-            )
-            "#
-        ).execute(pool).await?;
+        // Use absolute path with sqlite:// protocol (3 slashes for absolute paths)
+        Self::new(&format!("sqlite://{}", path_str)).await
+    }
 
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS goals (
-                id TEXT PRIMARY KEY,
-                user_id TEXT NOT NULL,
-                title TEXT NOT NULL,
-                target_pomodoros INTEGER NOT NULL,
-                completed_pomodoros INTEGER DEFAULT 0,
-                completed BOOLEAN DEFAULT 0,
-                
-                category TEXT,
-                motivation TEXT,
-                target_date DATETIME,
-                description TEXT,
-
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-            )
-            "#
-        ).execute(pool).await?;
-
-        // Attempt to add new columns if they don't exist (migrations for existing dbs)
-        // We ignore errors here as the column might already exist.
-        // In a production app top-notch migrations would be better, but this suffices for local embedded app.
-        let _ = sqlx::query("ALTER TABLE goals ADD COLUMN category TEXT").execute(pool).await;
-        let _ = sqlx::query("ALTER TABLE goals ADD COLUMN motivation TEXT").execute(pool).await;
-        let _ = sqlx::query("ALTER TABLE goals ADD COLUMN target_date DATETIME").execute(pool).await;
-        let _ = sqlx::query("ALTER TABLE goals ADD COLUMN description TEXT").execute(pool).await;
-        
-        // Migration for sound settings
-        let _ = sqlx::query("ALTER TABLE pomodoro_settings ADD COLUMN sound_enabled BOOLEAN DEFAULT 1").execute(pool).await;
-        let _ = sqlx::query("ALTER TABLE pomodoro_settings ADD COLUMN sound_volume INTEGER DEFAULT 70").execute(pool).await;
+    /// Writes a consistent copy of the database to `path`, which must not exist yet.
+    pub async fn backup_into(&self, path: &Path) -> Result<(), TimeboxError> {
+        let path = path.to_str()
+            .ok_or_else(|| TimeboxError::Validation(format!("Invalid backup path {}", path.display())))?;
 
-        // Migration for daily reflections table
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS daily_reflections (
-                id TEXT PRIMARY KEY,
-                user_id TEXT NOT NULL,
-                reflection_date DATE NOT NULL,
-                title TEXT,
-                duration_reflection TEXT,
-                purpose_reflection TEXT,
-                general_notes TEXT,
-                mood_rating INTEGER,
-                productivity_rating INTEGER,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
-                UNIQUE(user_id, reflection_date)
-            )
-            "#
-        ).execute(pool).await?;
+        sqlx::query("VACUUM INTO ?")
+            .bind(path)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // Waits for open connections to finish and closes the pool; later queries fail
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    pub async fn run_migrations(pool: &Pool<Sqlite>) -> Result<(), TimeboxError> {
+        // Bring the schema up to date; see migrations.rs
+        migrations::run(pool).await?;
 
         // Insert default user if none exists
         sqlx::query(
@@ -257,7 +507,7 @@ impl Database {
         Ok(())
     }
 
-    pub async fn get_or_create_user(&self, user_id: Option<String>, name: Option<String>) -> Result<User, sqlx::Error> {
+    pub async fn get_or_create_user(&self, user_id: Option<String>, name: Option<String>) -> Result<User, TimeboxError> {
         let user_id = match user_id {
             Some(id) => id,
             None => "default_user".to_string(),
@@ -273,17 +523,17 @@ impl Database {
             return Ok(user);
         }
 
-        // Create user if doesn't exist
+        // Create user if doesn't exist, in the system timezone
         let new_user = User {
             id: user_id.clone(),
             name: user_name,
-            timezone: "UTC".to_string(),
+            timezone: timezone::detect_system_timezone(),
             created_at: Utc::now(),
         };
 
         sqlx::query(
             r#"
-            INSERT INTO users (id, name, timezone) VALUES (?, ?, ?)
+            INSERT INTO users (id, name, timezone, timezone_source) VALUES (?, ?, ?, 'detected')
             "#,
         )
         .bind(&new_user.id)
@@ -305,7 +555,7 @@ impl Database {
         Ok(new_user)
     }
 
-    pub async fn get_user(&self, user_id: &str) -> Result<Option<User>, sqlx::Error> {
+    pub async fn get_user(&self, user_id: &str) -> Result<Option<User>, TimeboxError> {
         let row = sqlx::query(
             r#"
             SELECT id, name, timezone, created_at
@@ -317,40 +567,73 @@ impl Database {
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(User {
-                id: row.get("id"),
-                name: row.get("name"),
-                timezone: row.get("timezone"),
-                created_at: row.get("created_at"),
-            }))
-        } else {
-            Ok(None)
+        Ok(row.as_ref().map(user_from_row))
+    }
+
+    // Timezone used for the user's day boundaries; unknown or missing zones fall back to UTC
+    pub async fn get_user_timezone(&self, user_id: &str) -> Result<Tz, TimeboxError> {
+        let timezone: Option<Option<String>> = sqlx::query_scalar("SELECT timezone FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(timezone.flatten().and_then(|name| name.parse().ok()).unwrap_or(Tz::UTC))
+    }
+
+    pub async fn set_user_timezone(&self, user_id: &str, timezone_name: &str) -> Result<User, TimeboxError> {
+        timezone::parse_timezone(timezone_name)?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET timezone = ?, timezone_source = 'user'
+            WHERE id = ?
+            "#,
+        )
+        .bind(timezone_name)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(TimeboxError::not_found("User", user_id));
         }
+
+        self.get_user(user_id).await?
+            .ok_or_else(|| TimeboxError::not_found("User", user_id))
+    }
+
+    /// Gives users still on the built-in default timezone the detected system zone.
+    /// A zone chosen through `set_user_timezone` is never overwritten.
+    pub async fn apply_detected_timezone(&self, timezone_name: &str) -> Result<u64, TimeboxError> {
+        timezone::parse_timezone(timezone_name)?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET timezone = ?, timezone_source = 'detected'
+            WHERE timezone_source = 'default'
+            "#,
+        )
+        .bind(timezone_name)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
     }
 
-    pub async fn get_or_create_settings(&self, user_id: &str) -> Result<PomodoroSettings, sqlx::Error> {
+    pub async fn get_or_create_settings(&self, user_id: &str) -> Result<PomodoroSettings, TimeboxError> {
         if let Some(settings) = self.get_settings(user_id).await? {
             return Ok(settings);
         }
 
         // Create default settings
-        let settings = PomodoroSettings {
-            user_id: user_id.to_string(),
-            focus_minutes: 25,
-            short_break_minutes: 5,
-            long_break_minutes: 15,
-            cycles_before_long_break: 4,
-            strict_mode: false,
-            auto_start_breaks: false,
-            sound_enabled: true,
-            sound_volume: 70,
-        };
+        let settings = PomodoroSettings::defaults(user_id);
 
         sqlx::query(
             r#"
-            INSERT INTO pomodoro_settings (user_id, focus_minutes, short_break_minutes, long_break_minutes, cycles_before_long_break, strict_mode, auto_start_breaks, sound_enabled, sound_volume)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO pomodoro_settings (user_id, focus_minutes, short_break_minutes, long_break_minutes, cycles_before_long_break, strict_mode, auto_start_breaks, sound_enabled, sound_volume, daily_target_unit, daily_target, rest_days)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&settings.user_id)
@@ -362,16 +645,20 @@ impl Database {
         .bind(settings.auto_start_breaks)
         .bind(settings.sound_enabled)
         .bind(settings.sound_volume)
+        .bind(settings.daily_target_unit.as_str())
+        .bind(settings.daily_target)
+        .bind(rest_days_to_db(&settings.rest_days))
         .execute(&self.pool)
         .await?;
 
         Ok(settings)
     }
 
-    pub async fn get_settings(&self, user_id: &str) -> Result<Option<PomodoroSettings>, sqlx::Error> {
+    pub async fn get_settings(&self, user_id: &str) -> Result<Option<PomodoroSettings>, TimeboxError> {
         let row = sqlx::query(
             r#"
-            SELECT user_id, focus_minutes, short_break_minutes, long_break_minutes, cycles_before_long_break, strict_mode, auto_start_breaks, sound_enabled, sound_volume
+            SELECT user_id, focus_minutes, short_break_minutes, long_break_minutes, cycles_before_long_break, strict_mode, auto_start_breaks, sound_enabled, sound_volume,
+                   daily_target_unit, daily_target, rest_days
             FROM pomodoro_settings
             WHERE user_id = ?
             "#
@@ -380,30 +667,17 @@ impl Database {
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(PomodoroSettings {
-                user_id: row.get("user_id"),
-                focus_minutes: row.get("focus_minutes"),
-                short_break_minutes: row.get("short_break_minutes"),
-                long_break_minutes: row.get("long_break_minutes"),
-                cycles_before_long_break: row.get("cycles_before_long_break"),
-                strict_mode: row.get::<i32, &str>("strict_mode") != 0,
-                auto_start_breaks: row.get::<i32, &str>("auto_start_breaks") != 0,
-                sound_enabled: row.get::<i32, &str>("sound_enabled") != 0,
-                sound_volume: row.get("sound_volume"),
-            }))
-        } else {
-            Ok(None)
-        }
+        Ok(row.as_ref().map(settings_from_row))
     }
 
-    pub async fn update_settings(&self, settings: &PomodoroSettings) -> Result<(), sqlx::Error> {
+    pub async fn update_settings(&self, settings: &PomodoroSettings) -> Result<(), TimeboxError> {
         sqlx::query(
             r#"
             UPDATE pomodoro_settings
             SET focus_minutes = ?, short_break_minutes = ?, long_break_minutes = ?, 
                 cycles_before_long_break = ?, strict_mode = ?, auto_start_breaks = ?,
-                sound_enabled = ?, sound_volume = ?
+                sound_enabled = ?, sound_volume = ?,
+                daily_target_unit = ?, daily_target = ?, rest_days = ?
             WHERE user_id = ?
             "#,
         )
@@ -415,6 +689,9 @@ impl Database {
         .bind(settings.auto_start_breaks)
         .bind(settings.sound_enabled)
         .bind(settings.sound_volume)
+        .bind(settings.daily_target_unit.as_str())
+        .bind(settings.daily_target)
+        .bind(rest_days_to_db(&settings.rest_days))
         .bind(&settings.user_id)
         .execute(&self.pool)
         .await?;
@@ -422,22 +699,63 @@ impl Database {
         Ok(())
     }
 
-    pub async fn create_task(&self, user_id: &str, title: &str, estimated_pomodoros: Option<i32>) -> Result<Task, sqlx::Error> {
+    pub async fn create_task(
+        &self,
+        user_id: &str,
+        title: &str,
+        estimated_pomodoros: Option<i32>,
+        goal_id: Option<&str>,
+        project_id: Option<&str>,
+        parent_task_id: Option<&str>,
+    ) -> Result<Task, TimeboxError> {
+        if title.trim().is_empty() {
+            return Err(TimeboxError::Validation("Task title cannot be empty".to_string()));
+        }
+        if let Some(goal_id) = goal_id {
+            let goal = self.get_goal(goal_id).await?
+                .ok_or_else(|| TimeboxError::not_found("Goal", goal_id))?;
+            if goal.user_id != user_id {
+                return Err(TimeboxError::Validation("Task and goal belong to different users".to_string()));
+            }
+        }
+        if let Some(project_id) = project_id {
+            self.get_assignable_project(user_id, project_id).await?;
+        }
+        // Subtasks land in their parent's project unless given one
+        let mut project_id = project_id.map(str::to_string);
+        if let Some(parent_task_id) = parent_task_id {
+            let parent = self.get_parent_task(user_id, parent_task_id).await?;
+            project_id = project_id.or(parent.project_id);
+        }
+
         let task_id = Uuid::new_v4().to_string();
         let estimated = estimated_pomodoros.unwrap_or(1);
 
+        let mut tx = self.pool.begin().await?;
+        // New tasks go to the top of the list
+        let position: i32 = sqlx::query_scalar("SELECT COALESCE(MIN(position) - 1, 0) FROM tasks WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
         sqlx::query(
             r#"
-            INSERT INTO tasks (id, user_id, title, estimated_pomodoros)
-            VALUES (?, ?, ?, ?)
+            INSERT INTO tasks (id, user_id, title, estimated_pomodoros, goal_id, project_id, parent_task_id, position)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&task_id)
         .bind(user_id)
         .bind(title)
         .bind(estimated)
-        .execute(&self.pool)
+        .bind(goal_id)
+        .bind(&project_id)
+        .bind(parent_task_id)
+        .bind(position)
+        .execute(&mut *tx)
         .await?;
+        // A new open subtask means its parents are not done anymore
+        reopen_parent_tasks(&mut tx, &task_id).await?;
+        tx.commit().await?;
 
         Ok(Task {
             id: task_id,
@@ -445,39 +763,47 @@ impl Database {
             title: title.to_string(),
             estimated_pomodoros: estimated,
             completed: false,
+            completed_at: None,
+            goal_id: goal_id.map(str::to_string),
+            project_id,
+            tags: Vec::new(),
+            parent_task_id: parent_task_id.map(str::to_string),
+            due_date: None,
+            priority: TaskPriority::None,
+            position,
+            planned_for: None,
+            recurrence: None,
+            recurs_from: None,
             created_at: Utc::now(),
         })
     }
 
-    pub async fn get_tasks(&self, user_id: &str) -> Result<Vec<Task>, sqlx::Error> {
-        let rows = sqlx::query(
+    pub async fn get_tasks(&self, user_id: &str, filter: &ProjectTagFilter) -> Result<Vec<Task>, TimeboxError> {
+        let query = format!(
             r#"
-            SELECT id, user_id, title, estimated_pomodoros, completed, created_at
-            FROM tasks
-            WHERE user_id = ?
-            ORDER BY created_at DESC
-            "#
-        )
-        .bind(user_id)
-        .fetch_all(&self.pool)
-        .await?;
+            SELECT t.id, t.user_id, t.title, t.estimated_pomodoros, t.completed, t.completed_at, t.goal_id, t.project_id, t.parent_task_id, t.due_date, t.priority, t.position, t.planned_for, t.recurrence, t.recurs_from, t.created_at,
+                   (SELECT json_group_array(tg.name) FROM task_tags tt JOIN tags tg ON tt.tag_id = tg.id WHERE tt.task_id = t.id) AS tags
+            FROM tasks t
+            WHERE t.user_id = ? AND {}
+            ORDER BY t.position, t.created_at DESC
+            "#,
+            TASK_FILTER
+        );
 
-        let tasks = rows.into_iter().map(|row| Task {
-            id: row.get("id"),
-            user_id: row.get("user_id"),
-            title: row.get("title"),
-            estimated_pomodoros: row.get("estimated_pomodoros"),
-            completed: row.get::<i32, &str>("completed") != 0,
-            created_at: row.get("created_at"),
-        }).collect();
+        let rows = filter.bind(sqlx::query(&query).bind(user_id))
+            .fetch_all(&self.pool)
+            .await?;
+
+        let tasks = rows.into_iter().map(|row| task_from_row(&row)).collect();
 
         Ok(tasks)
     }
 
-    pub async fn get_task(&self, task_id: &str) -> Result<Option<Task>, sqlx::Error> {
+    pub async fn get_task(&self, task_id: &str) -> Result<Option<Task>, TimeboxError> {
         let row = sqlx::query(
             r#"
-            SELECT id, user_id, title, estimated_pomodoros, completed, created_at
+            SELECT id, user_id, title, estimated_pomodoros, completed, completed_at, goal_id, project_id, parent_task_id, due_date, priority, position, planned_for, recurrence, recurs_from, created_at,
+                   (SELECT json_group_array(tg.name) FROM task_tags tt JOIN tags tg ON tt.tag_id = tg.id WHERE tt.task_id = tasks.id) AS tags
             FROM tasks
             WHERE id = ?
             "#
@@ -486,21 +812,13 @@ impl Database {
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(Task {
-                id: row.get("id"),
-                user_id: row.get("user_id"),
-                title: row.get("title"),
-                estimated_pomodoros: row.get("estimated_pomodoros"),
-                completed: row.get::<i32, &str>("completed") != 0,
-                created_at: row.get("created_at"),
-            }))
-        } else {
-            Ok(None)
-        }
+        Ok(row.as_ref().map(task_from_row))
     }
 
-    pub async fn create_session(&self, session: &PomodoroSession) -> Result<(), sqlx::Error> {
+    /// Inserts a session that has just started and makes it the persisted active timer, in one transaction
+    /// so other processes never see the session without its timer.
+    pub async fn create_session(&self, session: &PomodoroSession, timer: &ActiveTimerState) -> Result<(), TimeboxError> {
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
             INSERT INTO pomodoro_sessions (id, user_id, task_id, session_type, start_time, interrupted, interruption_count, manual_override)
@@ -515,13 +833,15 @@ impl Database {
         .bind(session.interrupted)
         .bind(session.interruption_count)
         .bind(session.manual_override)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
+        write_active_timer(&mut tx, timer).await?;
+        tx.commit().await?;
 
         Ok(())
     }
 
-    pub async fn update_session(&self, session_id: &str, end_time: DateTime<Utc>, duration_seconds: i32) -> Result<(), sqlx::Error> {
+    pub async fn update_session(&self, session_id: &str, end_time: DateTime<Utc>, duration_seconds: i32) -> Result<(), TimeboxError> {
         sqlx::query(
             r#"
             UPDATE pomodoro_sessions
@@ -535,13 +855,47 @@ impl Database {
         .execute(&self.pool)
         .await?;
 
+        self.refresh_goal_progress_for_session(session_id).await
+    }
+
+    pub async fn get_session(&self, session_id: &str) -> Result<Option<PomodoroSession>, TimeboxError> {
+        let row = sqlx::query(
+            r#"
+            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time, ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.pause_count, ps.paused_seconds, ps.manual_override, ps.stop_reason, ps.created_at, t.title as task_title,
+                   (SELECT json_group_array(tg.name) FROM session_tags st JOIN tags tg ON st.tag_id = tg.id WHERE st.session_id = ps.id) AS tags
+            FROM pomodoro_sessions ps
+            LEFT JOIN tasks t ON ps.task_id = t.id
+            WHERE ps.id = ?
+            "#
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| session_from_row(&row)))
+    }
+
+    pub async fn mark_session_interrupted(&self, session_id: &str, stop_reason: Option<&str>) -> Result<(), TimeboxError> {
+        sqlx::query(
+            r#"
+            UPDATE pomodoro_sessions
+            SET interrupted = 1, stop_reason = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(stop_reason)
+        .bind(session_id)
+        .execute(&self.pool)
+        .await?;
+
         Ok(())
     }
 
-    pub async fn get_sessions(&self, user_id: &str, limit: Option<i32>) -> Result<Vec<PomodoroSession>, sqlx::Error> {
+    pub async fn get_sessions(&self, user_id: &str, limit: Option<i32>) -> Result<Vec<PomodoroSession>, TimeboxError> {
         let query = if let Some(_lim) = limit {
             r#"
-            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time, ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.manual_override, ps.created_at, t.title as task_title
+            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time, ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.pause_count, ps.paused_seconds, ps.manual_override, ps.stop_reason, ps.created_at, t.title as task_title,
+                   (SELECT json_group_array(tg.name) FROM session_tags st JOIN tags tg ON st.tag_id = tg.id WHERE st.session_id = ps.id) AS tags
             FROM pomodoro_sessions ps
             LEFT JOIN tasks t ON ps.task_id = t.id
             WHERE ps.user_id = ?
@@ -550,7 +904,8 @@ impl Database {
             "#
         } else {
             r#"
-            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time, ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.manual_override, ps.created_at, t.title as task_title
+            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time, ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.pause_count, ps.paused_seconds, ps.manual_override, ps.stop_reason, ps.created_at, t.title as task_title,
+                   (SELECT json_group_array(tg.name) FROM session_tags st JOIN tags tg ON st.tag_id = tg.id WHERE st.session_id = ps.id) AS tags
             FROM pomodoro_sessions ps
             LEFT JOIN tasks t ON ps.task_id = t.id
             WHERE ps.user_id = ?
@@ -571,41 +926,19 @@ impl Database {
                 .await?
         };
 
-        let sessions = rows.into_iter().map(|row: sqlx::sqlite::SqliteRow| {
-            let session_type_str: String = row.get("session_type");
-            let session_type = match session_type_str.as_str() {
-                "FOCUS" => SessionType::Focus,
-                "SHORT_BREAK" => SessionType::ShortBreak,
-                "LONG_BREAK" => SessionType::LongBreak,
-                _ => SessionType::Focus,
-            };
-
-            PomodoroSession {
-                id: row.get("id"),
-                user_id: row.get("user_id"),
-                task_id: row.get("task_id"),
-                session_type,
-                start_time: row.get("start_time"),
-                end_time: row.get("end_time"),
-                duration_seconds: row.get("duration_seconds"),
-                interrupted: row.get::<i32, &str>("interrupted") != 0,
-                interruption_count: row.get("interruption_count"),
-                manual_override: row.get::<i32, &str>("manual_override") != 0,
-                created_at: row.get("created_at"),
-                task_title: row.get("task_title"),
-            }
-        }).collect();
+        let sessions = rows.iter().map(session_from_row).collect();
 
         Ok(sessions)
     }
 
-    pub async fn get_today_sessions(&self, user_id: &str) -> Result<Vec<PomodoroSession>, sqlx::Error> {
-        let today_start = Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap().and_local_timezone(Utc).unwrap();
-        let today_end = today_start + chrono::Duration::days(1);
+    pub async fn get_today_sessions(&self, user_id: &str) -> Result<Vec<PomodoroSession>, TimeboxError> {
+        let tz = self.get_user_timezone(user_id).await?;
+        let (today_start, today_end) = timezone::day_bounds(tz, timezone::today(tz));
 
         let rows = sqlx::query(
             r#"
-            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time, ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.manual_override, ps.created_at, t.title as task_title
+            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time, ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.pause_count, ps.paused_seconds, ps.manual_override, ps.stop_reason, ps.created_at, t.title as task_title,
+                   (SELECT json_group_array(tg.name) FROM session_tags st JOIN tags tg ON st.tag_id = tg.id WHERE st.session_id = ps.id) AS tags
             FROM pomodoro_sessions ps
             LEFT JOIN tasks t ON ps.task_id = t.id
             WHERE ps.user_id = ? AND ps.start_time >= ? AND ps.start_time < ?
@@ -618,34 +951,180 @@ impl Database {
         .fetch_all(&self.pool)
         .await?;
 
-        let sessions = rows.into_iter().map(|row| {
-            let session_type_str: String = row.get("session_type");
-            let session_type = match session_type_str.as_str() {
-                "FOCUS" => SessionType::Focus,
-                "SHORT_BREAK" => SessionType::ShortBreak,
-                "LONG_BREAK" => SessionType::LongBreak,
-                _ => SessionType::Focus,
-            };
-
-            PomodoroSession {
-                id: row.get("id"),
-                user_id: row.get("user_id"),
-                task_id: row.get("task_id"),
-                session_type,
-                start_time: row.get("start_time"),
-                end_time: row.get("end_time"),
-                duration_seconds: row.get("duration_seconds"),
-                interrupted: row.get::<i32, &str>("interrupted") != 0,
-                interruption_count: row.get("interruption_count"),
-                manual_override: row.get::<i32, &str>("manual_override") != 0,
-                created_at: row.get("created_at"),
-                task_title: row.get("task_title"),
-            }
-        }).collect();
+        let sessions = rows.iter().map(session_from_row).collect();
 
         Ok(sessions)
     }
 
+    /// Returns the number of focus sessions completed since `since` or the last long break after it,
+    /// together with the type of the most recently finished session.
+    /// Focus sessions that were interrupted or stopped early don't count towards the cycle.
+    pub async fn get_cycle_progress(&self, user_id: &str, since: DateTime<Utc>) -> Result<(i32, Option<SessionType>), TimeboxError> {
+        let last_long_break: Option<DateTime<Utc>> = sqlx::query_scalar(
+            r#"
+            SELECT MAX(end_time)
+            FROM pomodoro_sessions
+            WHERE user_id = ? AND session_type = 'LONG_BREAK' AND end_time IS NOT NULL AND start_time >= ?
+            "#
+        )
+        .bind(user_id)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let cycle_start = last_long_break.map_or(since, |end| end.max(since));
+
+        let sql = format!(
+            r#"
+            SELECT COUNT(*)
+            FROM pomodoro_sessions
+            WHERE user_id = ? AND session_type = 'FOCUS' AND end_time IS NOT NULL
+            AND {} AND start_time >= ?
+            "#,
+            COMPLETED_FOCUS
+        );
+        let completed: i32 = sqlx::query_scalar(&sql)
+            .bind(user_id)
+            .bind(cycle_start)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let last_type: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT session_type
+            FROM pomodoro_sessions
+            WHERE user_id = ? AND end_time IS NOT NULL AND duration_seconds > 0 AND start_time >= ?
+            ORDER BY end_time DESC
+            LIMIT 1
+            "#
+        )
+        .bind(user_id)
+        .bind(since)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok((completed, last_type.as_deref().map(SessionType::from_db_str)))
+    }
+
+    // Active Timer Methods
+
+    pub async fn save_active_timer(&self, timer: &ActiveTimerState) -> Result<(), TimeboxError> {
+        let mut tx = self.pool.begin().await?;
+        write_active_timer(&mut tx, timer).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_active_timer(&self) -> Result<Option<ActiveTimerState>, TimeboxError> {
+        let row = sqlx::query(
+            r#"
+            SELECT session_id, remaining_seconds, total_seconds, is_paused, anchor_time, updated_at
+            FROM active_timer
+            WHERE id = 1
+            "#
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(row) = row {
+            Ok(Some(ActiveTimerState {
+                session_id: row.get("session_id"),
+                remaining_seconds: row.get("remaining_seconds"),
+                total_seconds: row.get("total_seconds"),
+                is_paused: row.get::<i32, &str>("is_paused") != 0,
+                anchor_time: row.get("anchor_time"),
+                updated_at: row.get("updated_at"),
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn clear_active_timer(&self) -> Result<(), TimeboxError> {
+        sqlx::query("DELETE FROM active_timer")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Loads the HTTP API settings, generating the install's token the first time.
+    pub async fn get_api_settings(&self) -> Result<ApiSettings, TimeboxError> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO api_settings (id, enabled, port, token)
+            VALUES (1, 0, ?, ?)
+            "#
+        )
+        .bind(DEFAULT_API_PORT as i64)
+        .bind(generate_api_token())
+        .execute(&self.pool)
+        .await?;
+
+        let row = sqlx::query("SELECT enabled, port, token FROM api_settings WHERE id = 1")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(ApiSettings {
+            enabled: row.get::<i32, &str>("enabled") != 0,
+            port: row.get::<i64, &str>("port") as u16,
+            token: row.get("token"),
+        })
+    }
+
+    pub async fn update_api_settings(&self, enabled: Option<bool>, port: Option<u16>) -> Result<ApiSettings, TimeboxError> {
+        if port == Some(0) {
+            return Err(TimeboxError::Validation("Port must be between 1 and 65535".to_string()));
+        }
+
+        let current = self.get_api_settings().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE api_settings
+            SET enabled = ?, port = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = 1
+            "#
+        )
+        .bind(enabled.unwrap_or(current.enabled))
+        .bind(port.unwrap_or(current.port) as i64)
+        .execute(&self.pool)
+        .await?;
+
+        self.get_api_settings().await
+    }
+
+    /// Replaces the API token, locking out every client that used the old one.
+    pub async fn regenerate_api_token(&self) -> Result<ApiSettings, TimeboxError> {
+        self.get_api_settings().await?;
+
+        sqlx::query("UPDATE api_settings SET token = ?, updated_at = CURRENT_TIMESTAMP WHERE id = 1")
+            .bind(generate_api_token())
+            .execute(&self.pool)
+            .await?;
+
+        self.get_api_settings().await
+    }
+
+    /// Closes every session left with `end_time = NULL` (e.g. after a crash) except `keep_session_id`.
+    /// There is no record of how long those sessions actually ran, so they are closed at their
+    /// start time with zero duration and flagged as interrupted.
+    pub async fn close_orphaned_sessions(&self, keep_session_id: Option<&str>) -> Result<u64, TimeboxError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE pomodoro_sessions
+            SET end_time = start_time, duration_seconds = 0, interrupted = 1
+            WHERE end_time IS NULL AND (? IS NULL OR id != ?)
+            "#,
+        )
+        .bind(keep_session_id)
+        .bind(keep_session_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     // Goal Methods
 
     pub async fn create_goal(
@@ -657,7 +1136,14 @@ impl Database {
         motivation: Option<String>,
         target_date: Option<DateTime<Utc>>,
         description: Option<String>
-    ) -> Result<Goal, sqlx::Error> {
+    ) -> Result<Goal, TimeboxError> {
+        if title.trim().is_empty() {
+            return Err(TimeboxError::Validation("Goal title cannot be empty".to_string()));
+        }
+        if target_pomodoros <= 0 {
+            return Err(TimeboxError::Validation("Target pomodoros must be greater than zero".to_string()));
+        }
+
         let goal_id = Uuid::new_v4().to_string();
 
         sqlx::query(
@@ -692,7 +1178,7 @@ impl Database {
         })
     }
 
-    pub async fn get_goals(&self, user_id: &str) -> Result<Vec<Goal>, sqlx::Error> {
+    pub async fn get_goals(&self, user_id: &str) -> Result<Vec<Goal>, TimeboxError> {
         let rows = sqlx::query(
             r#"
             SELECT id, user_id, title, target_pomodoros, completed_pomodoros, completed, 
@@ -706,22 +1192,26 @@ impl Database {
         .fetch_all(&self.pool)
         .await?;
 
-        let goals = rows.into_iter().map(|row| Goal {
-            id: row.get("id"),
-            user_id: row.get("user_id"),
-            title: row.get("title"),
-            target_pomodoros: row.get("target_pomodoros"),
-            completed_pomodoros: row.get("completed_pomodoros"),
-            completed: row.get::<i32, &str>("completed") != 0,
-            category: row.get("category"),
-            motivation: row.get("motivation"),
-            target_date: row.get("target_date"),
-            description: row.get("description"),
-            created_at: row.get("created_at"),
-        }).collect();
+        let goals = rows.iter().map(goal_from_row).collect();
 
         Ok(goals)
     }
+
+    pub async fn get_goal(&self, goal_id: &str) -> Result<Option<Goal>, TimeboxError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, user_id, title, target_pomodoros, completed_pomodoros, completed,
+                   category, motivation, target_date, description, created_at
+            FROM goals
+            WHERE id = ?
+            "#
+        )
+        .bind(goal_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(goal_from_row))
+    }
     
     pub async fn update_goal(
         &self, 
@@ -733,7 +1223,7 @@ impl Database {
         motivation: Option<String>,
         target_date: Option<DateTime<Utc>>,
         description: Option<String>
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<(), TimeboxError> {
         
         // Dynamic update to handle partial updates would be better, but implementing explicit update for now
         // NOTE: In a real world scenario checking for existence or building a dynamic query is better
@@ -755,6 +1245,7 @@ impl Database {
              let cur_title: String = row.get("title");
              let cur_target: i32 = row.get("target_pomodoros");
              let cur_completed: bool = row.get::<i32, &str>("completed") != 0;
+             let cur_count: i32 = row.get("completed_pomodoros");
              let cur_category: Option<String> = row.get("category");
              let cur_motivation: Option<String> = row.get("motivation");
              let cur_target_date: Option<DateTime<Utc>> = row.get("target_date");
@@ -762,7 +1253,9 @@ impl Database {
 
              let new_title = title.unwrap_or(&cur_title);
              let new_target = target_pomodoros.unwrap_or(cur_target);
-             let new_completed = completed.unwrap_or(cur_completed);
+             // Completion that came from meeting the old target is re-checked against the new one below
+             let completed_by_count = cur_completed && cur_count >= cur_target;
+             let new_completed = completed.unwrap_or(cur_completed && !completed_by_count);
              
              // For Option<String> arguments, if they are None (in the function arg), we keep existing. 
              // If we wanted to clear them, we'd need Option<Option<String>>. For now assume we don't clear fields via this API easily unless explicit.
@@ -788,13 +1281,131 @@ impl Database {
              .bind(goal_id)
              .execute(&self.pool)
              .await?;
+        } else {
+            return Err(TimeboxError::not_found("Goal", goal_id));
         }
-        
+
+        // A changed target may be met or no longer met, unless the caller set the completion state explicitly
+        if completed.is_none() {
+            self.refresh_goal_progress(goal_id).await?;
+        }
+
         Ok(())
     }
 
-    pub async fn delete_goal(&self, goal_id: &str) -> Result<(), sqlx::Error> {
-        sqlx::query(
+    /// Recounts a goal's completed pomodoros from the completed focus sessions of its tasks.
+    /// The goal is marked completed once the target is reached, and no longer completed when a count
+    /// that met the target drops below it; a goal completed by hand before reaching its target stays so.
+    pub async fn refresh_goal_progress(&self, goal_id: &str) -> Result<(), TimeboxError> {
+        // SET expressions see the row as it was, so `completed_pomodoros` is the previous count
+        let sql = format!(
+            r#"
+            WITH progress AS (
+                SELECT COUNT(*) AS pomodoros
+                FROM pomodoro_sessions ps
+                JOIN tasks t ON ps.task_id = t.id
+                WHERE t.goal_id = ? AND ps.session_type = 'FOCUS'
+                AND ps.end_time IS NOT NULL AND {}
+            )
+            UPDATE goals
+            SET completed = CASE
+                    WHEN (SELECT pomodoros FROM progress) >= target_pomodoros THEN 1
+                    WHEN completed_pomodoros >= target_pomodoros THEN 0
+                    ELSE completed
+                END,
+                completed_pomodoros = (SELECT pomodoros FROM progress)
+            WHERE id = ?
+            "#,
+            COMPLETED_FOCUS
+        );
+        sqlx::query(&sql)
+            .bind(goal_id)
+            .bind(goal_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn refresh_all_goal_progress(&self) -> Result<(), TimeboxError> {
+        let goal_ids: Vec<String> = sqlx::query_scalar("SELECT id FROM goals")
+            .fetch_all(&self.pool)
+            .await?;
+
+        for goal_id in goal_ids {
+            self.refresh_goal_progress(&goal_id).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn refresh_goal_progress_for_session(&self, session_id: &str) -> Result<(), TimeboxError> {
+        let goal_id: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT t.goal_id
+            FROM pomodoro_sessions ps
+            JOIN tasks t ON ps.task_id = t.id
+            WHERE ps.id = ?
+            "#
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .flatten();
+
+        match goal_id {
+            Some(goal_id) => self.refresh_goal_progress(&goal_id).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Completed pomodoros and focus time per local day for the tasks linked to a goal.
+    pub async fn get_goal_progress(&self, goal_id: &str) -> Result<GoalProgress, TimeboxError> {
+        let goal = self.get_goal(goal_id).await?
+            .ok_or_else(|| TimeboxError::not_found("Goal", goal_id))?;
+        let tz = self.get_user_timezone(&goal.user_id).await?;
+
+        let sql = format!(
+            r#"
+            SELECT ps.start_time, ps.duration_seconds
+            FROM pomodoro_sessions ps
+            JOIN tasks t ON ps.task_id = t.id
+            WHERE t.goal_id = ? AND ps.session_type = 'FOCUS'
+            AND ps.end_time IS NOT NULL AND {}
+            ORDER BY ps.start_time ASC
+            "#,
+            COMPLETED_FOCUS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(goal_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        // Sessions are ordered by start time, so each local day is contiguous
+        let mut days: Vec<GoalDayProgress> = Vec::new();
+        for row in rows {
+            let start_time: DateTime<Utc> = row.get("start_time");
+            let duration_seconds: i32 = row.get("duration_seconds");
+            let date = timezone::local_date(tz, start_time).format("%Y-%m-%d").to_string();
+
+            match days.last_mut() {
+                Some(day) if day.date == date => {
+                    day.completed_pomodoros += 1;
+                    day.focus_seconds += duration_seconds as i64;
+                }
+                _ => days.push(GoalDayProgress {
+                    date,
+                    completed_pomodoros: 1,
+                    focus_seconds: duration_seconds as i64,
+                }),
+            }
+        }
+
+        Ok(GoalProgress { goal, days })
+    }
+
+    pub async fn delete_goal(&self, goal_id: &str) -> Result<(), TimeboxError> {
+        let result = sqlx::query(
             r#"
             DELETE FROM goals
             WHERE id = ?
@@ -804,439 +1415,3773 @@ impl Database {
         .execute(&self.pool)
         .await?;
 
+        if result.rows_affected() == 0 {
+            return Err(TimeboxError::not_found("Goal", goal_id));
+        }
+
         Ok(())
     }
 
-    pub async fn create_manual_session(
-        &self,
-        user_id: &str,
-        task_id: Option<String>,
-        start_time: DateTime<Utc>,
-        end_time: DateTime<Utc>,
-        duration_seconds: i32,
-    ) -> Result<PomodoroSession, sqlx::Error> {
-        let session_id = Uuid::new_v4().to_string();
+    /// Logs a session that wasn't timed. The duration defaults to the time between start and end and can't exceed it.
+    /// Sessions can't end in the future or overlap tracked ones; with `fill_gaps` only the untracked parts
+    /// of the range are logged instead, one session per gap, each with its share of the duration.
+    pub async fn create_manual_session(&self, user_id: &str, entry: ManualSession) -> Result<Vec<PomodoroSession>, TimeboxError> {
+        if entry.end_time <= entry.start_time {
+            return Err(TimeboxError::Validation("A session must end after it starts".to_string()));
+        }
+        let now = Utc::now();
+        if entry.end_time > now {
+            return Err(TimeboxError::Validation("Manual sessions can't end in the future".to_string()));
+        }
+        let tags = normalize_tags(&entry.tags)?;
+        let span = (entry.end_time - entry.start_time).num_seconds();
+        let duration = entry.duration_seconds.unwrap_or(span as i32);
+        if duration <= 0 || duration as i64 > span {
+            return Err(TimeboxError::Validation(format!(
+                "Duration must be between 1 and {} seconds, the time between start and end",
+                span
+            )));
+        }
 
-        sqlx::query(
+        let mut tx = self.pool.begin().await?;
+        if let Some(task_id) = &entry.task_id {
+            let task_exists = sqlx::query_scalar::<_, i64>("SELECT 1 FROM tasks WHERE id = ? AND user_id = ?")
+                .bind(task_id)
+                .bind(user_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .is_some();
+            if !task_exists {
+                return Err(TimeboxError::not_found("Task", task_id));
+            }
+        }
+
+        let tracked = sqlx::query(
             r#"
-            INSERT INTO pomodoro_sessions (id, user_id, task_id, session_type, start_time, end_time, duration_seconds, interrupted, interruption_count, manual_override)
-            VALUES (?, ?, ?, 'FOCUS', ?, ?, ?, 0, 0, 1)
-            "#,
+            SELECT id, start_time, end_time
+            FROM pomodoro_sessions
+            WHERE user_id = ? AND start_time < ? AND (end_time IS NULL OR end_time > ?)
+            ORDER BY start_time
+            "#
         )
-        .bind(&session_id)
         .bind(user_id)
-        .bind(&task_id)
-        .bind(&start_time)
-        .bind(&end_time)
-        .bind(duration_seconds)
-        .execute(&self.pool)
+        .bind(entry.end_time)
+        .bind(entry.start_time)
+        .fetch_all(&mut *tx)
         .await?;
 
-        Ok(PomodoroSession {
-            id: session_id,
-            user_id: user_id.to_string(),
-            task_id,
-            session_type: SessionType::Focus,
-            start_time,
-            end_time: Some(end_time),
-            duration_seconds: Some(duration_seconds),
-            interrupted: false,
-            interruption_count: 0,
-            manual_override: true,
-            created_at: Utc::now(),
-            task_title: None,
-        })
+        let ranges = if entry.fill_gaps {
+            // The running session counts as tracked up to now
+            let tracked = tracked.iter().map(|row| {
+                let end_time: Option<DateTime<Utc>> = row.get("end_time");
+                (row.get::<DateTime<Utc>, &str>("start_time"), end_time.unwrap_or(now))
+            });
+            let gaps = untracked_gaps(entry.start_time, entry.end_time, tracked);
+            if gaps.is_empty() {
+                return Err(TimeboxError::Conflict("The whole range is already tracked".to_string()));
+            }
+            gaps
+        } else if let Some(row) = tracked.first() {
+            return Err(TimeboxError::Conflict(format!(
+                "The session would overlap session {}",
+                row.get::<String, &str>("id")
+            )));
+        } else {
+            vec![(entry.start_time, entry.end_time)]
+        };
+
+        let parts = share_duration(duration, span, &ranges);
+        if parts.is_empty() {
+            return Err(TimeboxError::Validation("The duration is too short to share across the untracked parts of the range".to_string()));
+        }
+
+        let mut session_ids = Vec::new();
+        for (start_time, end_time, part_duration) in parts {
+            let session = PomodoroSession {
+                id: Uuid::new_v4().to_string(),
+                user_id: user_id.to_string(),
+                task_id: entry.task_id.clone(),
+                session_type: entry.session_type.clone().unwrap_or(SessionType::Focus),
+                start_time,
+                end_time: Some(end_time),
+                duration_seconds: Some(part_duration),
+                interrupted: false,
+                interruption_count: 0,
+                pause_count: 0,
+                paused_seconds: 0,
+                manual_override: true,
+                stop_reason: None,
+                created_at: now,
+                task_title: None,
+                tags: tags.clone(),
+            };
+            insert_session_row(&mut tx, &session).await?;
+            session_ids.push(session.id);
+        }
+        tx.commit().await?;
+
+        self.refresh_goal_progress_for_tasks(&[entry.task_id.as_deref()]).await?;
+
+        let mut sessions = Vec::new();
+        for session_id in &session_ids {
+            sessions.extend(self.get_session(session_id).await?);
+        }
+        Ok(sessions)
     }
 
-    pub async fn increment_interruption_count(&self, session_id: &str) -> Result<(), sqlx::Error> {
+    // Session History Methods
+
+    /// Changes the task, type, times, duration or tags of a completed session.
+    /// A duration that no longer fits between the new start and end is shortened to fit; the new end can't be in the future.
+    pub async fn edit_session(&self, session_id: &str, changes: SessionChanges) -> Result<PomodoroSession, TimeboxError> {
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_completed_session(&mut tx, session_id).await?;
+
+        let mut after = before.clone();
+        if changes.clear_task {
+            after.task_id = None;
+        } else if let Some(task_id) = changes.task_id {
+            let task_exists = sqlx::query_scalar::<_, i64>("SELECT 1 FROM tasks WHERE id = ? AND user_id = ?")
+                .bind(&task_id)
+                .bind(&before.user_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .is_some();
+            if !task_exists {
+                return Err(TimeboxError::not_found("Task", &task_id));
+            }
+            after.task_id = Some(task_id);
+        }
+        if let Some(session_type) = changes.session_type {
+            after.session_type = session_type;
+        }
+        let tags = changes.tags.as_deref().map(normalize_tags).transpose()?;
+        after.start_time = changes.start_time.unwrap_or(before.start_time);
+        after.end_time = changes.end_time.or(before.end_time);
+
+        let span = validate_session_span(&after)?;
+        if changes.end_time.is_some_and(|end_time| end_time > Utc::now()) {
+            return Err(TimeboxError::Validation("Sessions can't end in the future".to_string()));
+        }
+        after.duration_seconds = Some(match changes.duration_seconds {
+            Some(duration) if duration < 0 || duration as i64 > span => {
+                return Err(TimeboxError::Validation(format!(
+                    "Duration must be between 0 and {} seconds, the time between start and end",
+                    span
+                )));
+            }
+            Some(duration) => duration,
+            None => before.duration_seconds.unwrap_or(0).min(span as i32),
+        });
+        ensure_no_overlap(&mut tx, &after, &before.id).await?;
+
         sqlx::query(
             r#"
             UPDATE pomodoro_sessions
-            SET interruption_count = interruption_count + 1,
-                interrupted = 1
+            SET task_id = ?, session_type = ?, start_time = ?, end_time = ?, duration_seconds = ?
             WHERE id = ?
             "#,
         )
-        .bind(session_id)
-        .execute(&self.pool)
+        .bind(&after.task_id)
+        .bind(after.session_type.to_string())
+        .bind(after.start_time)
+        .bind(after.end_time)
+        .bind(after.duration_seconds)
+        .bind(&after.id)
+        .execute(&mut *tx)
         .await?;
+        if let Some(tags) = &tags {
+            replace_tag_links(&mut tx, "session_tags", "session_id", &after.id, &after.user_id, tags).await?;
+        }
 
-        Ok(())
+        let after = fetch_completed_session(&mut tx, session_id).await?;
+        insert_session_audit(&mut tx, &before.id, &before.user_id, SessionAuditAction::Edit, &[&before], &[&after]).await?;
+        tx.commit().await?;
+
+        self.refresh_goal_progress_for_tasks(&[before.task_id.as_deref(), after.task_id.as_deref()]).await?;
+
+        Ok(after)
+    }
+
+    /// Deletes a completed session along with its pauses and interruptions.
+    pub async fn delete_session(&self, session_id: &str) -> Result<(), TimeboxError> {
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_completed_session(&mut tx, session_id).await?;
+
+        insert_session_audit(&mut tx, &before.id, &before.user_id, SessionAuditAction::Delete, &[&before], &[]).await?;
+        sqlx::query("DELETE FROM pomodoro_sessions WHERE id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        self.refresh_goal_progress_for_tasks(&[before.task_id.as_deref()]).await
     }
 
-    pub async fn update_task(&self, task_id: &str, title: Option<&str>, estimated_pomodoros: Option<i32>, completed: Option<bool>) -> Result<Task, sqlx::Error> {
-        let current_task = self.get_task(task_id).await?.ok_or_else(|| sqlx::Error::RowNotFound)?;
+    /// Splits a completed session in two at `at`. The focus duration is shared in proportion to each part's length;
+    /// pauses and interruptions go to the part they started in, and the stop reason stays with the second part.
+    pub async fn split_session(&self, session_id: &str, at: DateTime<Utc>) -> Result<(PomodoroSession, PomodoroSession), TimeboxError> {
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_completed_session(&mut tx, session_id).await?;
+        let end_time = before.end_time.unwrap_or(before.start_time);
+        if at <= before.start_time || at >= end_time {
+            return Err(TimeboxError::Validation("The split time must fall between the session's start and end".to_string()));
+        }
+
+        let span = (end_time - before.start_time).num_milliseconds();
+        let duration = before.duration_seconds.unwrap_or(0) as i64;
+        let first_duration = (duration * (at - before.start_time).num_milliseconds() + span / 2) / span;
+
+        let moved_pauses: Vec<SessionPause> = sqlx::query(
+            r#"
+            SELECT id, session_id, paused_at, resumed_at, reason
+            FROM session_pauses
+            WHERE session_id = ? AND paused_at >= ?
+            "#
+        )
+        .bind(session_id)
+        .bind(at)
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(pause_from_row)
+        .collect();
+        let moved_paused_seconds: i64 = moved_pauses.iter()
+            .filter_map(|pause| pause.resumed_at.map(|resumed| (resumed - pause.paused_at).num_seconds().max(0)))
+            .sum();
+        let moved_interruptions: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM session_interruptions WHERE session_id = ? AND occurred_at >= ?"
+        )
+        .bind(session_id)
+        .bind(at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut second = before.clone();
+        second.id = Uuid::new_v4().to_string();
+        second.start_time = at;
+        second.duration_seconds = Some((duration - first_duration) as i32);
+        second.interruption_count = moved_interruptions as i32;
+        second.pause_count = moved_pauses.len() as i32;
+        second.paused_seconds = moved_paused_seconds;
+        second.created_at = Utc::now();
 
-        let new_title = title.unwrap_or(&current_task.title);
-        let new_estimated = estimated_pomodoros.unwrap_or(current_task.estimated_pomodoros);
-        let new_completed = completed.unwrap_or(current_task.completed);
+        let mut first = before.clone();
+        first.end_time = Some(at);
+        first.duration_seconds = Some(first_duration as i32);
+        first.interruption_count = (before.interruption_count - second.interruption_count).max(0);
+        first.interrupted = first.interruption_count > 0;
+        first.stop_reason = None;
+        first.pause_count = (before.pause_count - second.pause_count).max(0);
+        first.paused_seconds = (before.paused_seconds - second.paused_seconds).max(0);
 
         sqlx::query(
             r#"
-            UPDATE tasks
-            SET title = ?, estimated_pomodoros = ?, completed = ?
+            UPDATE pomodoro_sessions
+            SET end_time = ?, duration_seconds = ?, interrupted = ?, interruption_count = ?,
+                pause_count = ?, paused_seconds = ?, stop_reason = NULL
             WHERE id = ?
             "#,
         )
-        .bind(new_title)
-        .bind(new_estimated)
-        .bind(new_completed)
-        .bind(task_id)
-        .execute(&self.pool)
+        .bind(first.end_time)
+        .bind(first.duration_seconds)
+        .bind(first.interrupted)
+        .bind(first.interruption_count)
+        .bind(first.pause_count)
+        .bind(first.paused_seconds)
+        .bind(&first.id)
+        .execute(&mut *tx)
         .await?;
+        insert_session_row(&mut tx, &second).await?;
+        sqlx::query("UPDATE session_pauses SET session_id = ? WHERE session_id = ? AND paused_at >= ?")
+            .bind(&second.id)
+            .bind(session_id)
+            .bind(at)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE session_interruptions SET session_id = ? WHERE session_id = ? AND occurred_at >= ?")
+            .bind(&second.id)
+            .bind(session_id)
+            .bind(at)
+            .execute(&mut *tx)
+            .await?;
 
-        Ok(Task {
-            id: task_id.to_string(),
-            user_id: current_task.user_id,
-            title: new_title.to_string(),
-            estimated_pomodoros: new_estimated,
-            completed: new_completed,
-            created_at: current_task.created_at,
-        })
+        let first = fetch_completed_session(&mut tx, &first.id).await?;
+        let second = fetch_completed_session(&mut tx, &second.id).await?;
+        insert_session_audit(&mut tx, &before.id, &before.user_id, SessionAuditAction::Split, &[&before], &[&first, &second]).await?;
+        tx.commit().await?;
+
+        self.refresh_goal_progress_for_tasks(&[before.task_id.as_deref()]).await?;
+
+        Ok((first, second))
     }
 
-    pub async fn delete_task(&self, task_id: &str) -> Result<(), sqlx::Error> {
+    /// Merges two adjacent completed sessions of the same type into the earlier one.
+    /// Sessions are adjacent when no other session starts between them; they may only differ in task if one has none.
+    pub async fn merge_sessions(&self, first_id: &str, second_id: &str) -> Result<PomodoroSession, TimeboxError> {
+        if first_id == second_id {
+            return Err(TimeboxError::Validation("A session can't be merged with itself".to_string()));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let mut first = fetch_completed_session(&mut tx, first_id).await?;
+        let mut second = fetch_completed_session(&mut tx, second_id).await?;
+        if second.start_time < first.start_time {
+            std::mem::swap(&mut first, &mut second);
+        }
+
+        if first.user_id != second.user_id {
+            return Err(TimeboxError::Validation("Sessions of different users can't be merged".to_string()));
+        }
+        if first.session_type.to_string() != second.session_type.to_string() {
+            return Err(TimeboxError::Validation("Only sessions of the same type can be merged".to_string()));
+        }
+        let task_id = match (&first.task_id, &second.task_id) {
+            (Some(a), Some(b)) if a != b => {
+                return Err(TimeboxError::Validation("Sessions for different tasks can't be merged".to_string()));
+            }
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+
+        let first_end = first.end_time.unwrap_or(first.start_time);
+        let between: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT id
+            FROM pomodoro_sessions
+            WHERE user_id = ? AND id NOT IN (?, ?) AND start_time >= ? AND start_time < ?
+            LIMIT 1
+            "#
+        )
+        .bind(&first.user_id)
+        .bind(&first.id)
+        .bind(&second.id)
+        .bind(first.start_time)
+        .bind(second.start_time)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if first_end > second.start_time || between.is_some() {
+            return Err(TimeboxError::Validation("Only adjacent sessions can be merged".to_string()));
+        }
+
+        let mut merged = first.clone();
+        merged.task_id = task_id;
+        merged.end_time = second.end_time;
+        merged.duration_seconds = Some(first.duration_seconds.unwrap_or(0) + second.duration_seconds.unwrap_or(0));
+        merged.interrupted = first.interrupted || second.interrupted;
+        merged.interruption_count = first.interruption_count + second.interruption_count;
+        merged.pause_count = first.pause_count + second.pause_count;
+        merged.paused_seconds = first.paused_seconds + second.paused_seconds;
+        merged.manual_override = first.manual_override || second.manual_override;
+        merged.stop_reason = second.stop_reason.clone();
+
+        // Tags both sessions have are left on the second one and deleted with it
+        for table in ["session_pauses", "session_interruptions", "session_tags"] {
+            sqlx::query(&format!("UPDATE OR IGNORE {} SET session_id = ? WHERE session_id = ?", table))
+                .bind(&first.id)
+                .bind(&second.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query("DELETE FROM pomodoro_sessions WHERE id = ?")
+            .bind(&second.id)
+            .execute(&mut *tx)
+            .await?;
         sqlx::query(
             r#"
-            DELETE FROM tasks
+            UPDATE pomodoro_sessions
+            SET task_id = ?, end_time = ?, duration_seconds = ?, interrupted = ?, interruption_count = ?,
+                pause_count = ?, paused_seconds = ?, manual_override = ?, stop_reason = ?
             WHERE id = ?
             "#,
         )
-        .bind(task_id)
-        .execute(&self.pool)
+        .bind(&merged.task_id)
+        .bind(merged.end_time)
+        .bind(merged.duration_seconds)
+        .bind(merged.interrupted)
+        .bind(merged.interruption_count)
+        .bind(merged.pause_count)
+        .bind(merged.paused_seconds)
+        .bind(merged.manual_override)
+        .bind(&merged.stop_reason)
+        .bind(&merged.id)
+        .execute(&mut *tx)
         .await?;
 
-        Ok(())
+        let merged = fetch_completed_session(&mut tx, &merged.id).await?;
+        insert_session_audit(&mut tx, &merged.id, &merged.user_id, SessionAuditAction::Merge, &[&first, &second], &[&merged]).await?;
+        tx.commit().await?;
+
+        self.refresh_goal_progress_for_tasks(&[first.task_id.as_deref(), second.task_id.as_deref()]).await?;
+
+        Ok(merged)
     }
 
-    pub async fn get_tasks_with_pomodoro_counts(&self, user_id: &str) -> Result<Vec<(Task, i64)>, sqlx::Error> {
+    /// Audit trail of history changes, newest first; limited to one session's entries when `session_id` is given.
+    pub async fn get_session_audit(&self, user_id: &str, session_id: Option<&str>) -> Result<Vec<SessionAuditEntry>, TimeboxError> {
         let rows = sqlx::query(
             r#"
-            SELECT 
-                t.id, t.user_id, t.title, t.estimated_pomodoros, t.completed, t.created_at,
-                COUNT(ps.id) as pomodoro_count
-            FROM tasks t
-            LEFT JOIN pomodoro_sessions ps ON t.id = ps.task_id AND ps.session_type = 'FOCUS'
-            WHERE t.user_id = ?
-            GROUP BY t.id
-            ORDER BY t.created_at DESC
+            SELECT id, session_id, user_id, action, before_data, after_data, created_at
+            FROM session_audit
+            WHERE user_id = ? AND (? IS NULL OR session_id = ?)
+            ORDER BY created_at DESC
             "#
         )
         .bind(user_id)
+        .bind(session_id)
+        .bind(session_id)
         .fetch_all(&self.pool)
         .await?;
 
-        let tasks_with_counts = rows.into_iter().map(|row| {
-            let task = Task {
-                id: row.get("id"),
-                user_id: row.get("user_id"),
-                title: row.get("title"),
-                estimated_pomodoros: row.get("estimated_pomodoros"),
-                completed: row.get::<i32, &str>("completed") != 0,
-                created_at: row.get("created_at"),
-            };
-            let count: i64 = row.get("pomodoro_count");
-            (task, count)
-        }).collect();
-
-        Ok(tasks_with_counts)
+        Ok(rows.iter().map(audit_from_row).collect())
     }
 
-    pub async fn get_sessions_by_date_range(
-        &self,
-        user_id: &str,
-        start_date: DateTime<Utc>,
-        end_date: DateTime<Utc>,
-        session_type: Option<SessionType>,
-    ) -> Result<Vec<PomodoroSession>, sqlx::Error> {
-        let query = if session_type.is_some() {
-            r#"
-            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time, ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.manual_override, ps.created_at, t.title as task_title
-            FROM pomodoro_sessions ps
-            LEFT JOIN tasks t ON ps.task_id = t.id
-            WHERE ps.user_id = ? AND ps.start_time >= ? AND ps.start_time < ? AND ps.session_type = ? AND ps.end_time IS NOT NULL
-            ORDER BY ps.start_time DESC
-            "#
-        } else {
-            r#"
-            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time, ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.manual_override, ps.created_at, t.title as task_title
-            FROM pomodoro_sessions ps
-            LEFT JOIN tasks t ON ps.task_id = t.id
-            WHERE ps.user_id = ? AND ps.start_time >= ? AND ps.start_time < ? AND ps.end_time IS NOT NULL
-            ORDER BY ps.start_time DESC
-            "#
-        };
-
-        let rows = if let Some(st) = session_type {
-            sqlx::query(query)
-                .bind(user_id)
-                .bind(start_date)
-                .bind(end_date)
-                .bind(st.to_string())
-                .fetch_all(&self.pool)
-                .await?
-        } else {
-            sqlx::query(query)
-                .bind(user_id)
-                .bind(start_date)
-                .bind(end_date)
-                .fetch_all(&self.pool)
+    async fn refresh_goal_progress_for_tasks(&self, task_ids: &[Option<&str>]) -> Result<(), TimeboxError> {
+        let mut goal_ids: Vec<String> = Vec::new();
+        for task_id in task_ids.iter().flatten() {
+            let goal_id: Option<String> = sqlx::query_scalar("SELECT goal_id FROM tasks WHERE id = ?")
+                .bind(task_id)
+                .fetch_optional(&self.pool)
                 .await?
-        };
-
-        let sessions = rows.into_iter().map(|row| {
-            let session_type_str: String = row.get("session_type");
-            let session_type = match session_type_str.as_str() {
-                "FOCUS" => SessionType::Focus,
-                "SHORT_BREAK" => SessionType::ShortBreak,
-                "LONG_BREAK" => SessionType::LongBreak,
-                _ => SessionType::Focus,
-            };
-
-            PomodoroSession {
-                id: row.get("id"),
-                user_id: row.get("user_id"),
-                task_id: row.get("task_id"),
-                session_type,
-                start_time: row.get("start_time"),
-                end_time: row.get("end_time"),
-                duration_seconds: row.get("duration_seconds"),
-                interrupted: row.get::<i32, &str>("interrupted") != 0,
-                interruption_count: row.get("interruption_count"),
-                manual_override: row.get::<i32, &str>("manual_override") != 0,
-                created_at: row.get("created_at"),
-                task_title: row.get("task_title"),
+                .flatten();
+            if let Some(goal_id) = goal_id.filter(|goal_id| !goal_ids.contains(goal_id)) {
+                goal_ids.push(goal_id);
             }
-        }).collect();
+        }
 
-        Ok(sessions)
-    }
+        for goal_id in goal_ids {
+            self.refresh_goal_progress(&goal_id).await?;
+        }
 
-    // Daily Reflection Methods
+        Ok(())
+    }
 
-    pub async fn create_or_update_reflection(
+    /// Logs an interruption of a session and bumps its interruption count.
+    pub async fn record_interruption(
         &self,
-        user_id: &str,
-        reflection_date: DateTime<Utc>,
-        title: Option<String>,
-        duration_reflection: Option<String>,
-        purpose_reflection: Option<String>,
-        general_notes: Option<String>,
-        mood_rating: Option<i32>,
-        productivity_rating: Option<i32>,
-    ) -> Result<DailyReflection, sqlx::Error> {
-        let reflection_id = Uuid::new_v4().to_string();
-        let now = Utc::now();
-        // Normalize to date-only string so UNIQUE(user_id, reflection_date) works
-        let date_only = reflection_date.format("%Y-%m-%d").to_string();
+        session: &PomodoroSession,
+        kind: InterruptionKind,
+        reason: Option<&str>,
+        category: Option<&str>,
+    ) -> Result<Interruption, TimeboxError> {
+        let interruption = Interruption {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: session.id.clone(),
+            user_id: session.user_id.clone(),
+            occurred_at: Utc::now(),
+            kind,
+            reason: reason.map(str::to_string),
+            category: category.map(str::to_string),
+            task_title: None,
+        };
 
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
-            INSERT INTO daily_reflections (
-                id, user_id, reflection_date, title, duration_reflection,
-                purpose_reflection, general_notes, mood_rating, productivity_rating,
-                created_at, updated_at
-            )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            ON CONFLICT(user_id, reflection_date)
-            DO UPDATE SET
-                title = excluded.title,
-                duration_reflection = excluded.duration_reflection,
-                purpose_reflection = excluded.purpose_reflection,
-                general_notes = excluded.general_notes,
-                mood_rating = excluded.mood_rating,
-                productivity_rating = excluded.productivity_rating,
-                updated_at = ?
+            INSERT INTO session_interruptions (id, session_id, user_id, occurred_at, kind, reason, category)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
             "#,
         )
-        .bind(&reflection_id)
-        .bind(user_id)
-        .bind(&date_only)
-        .bind(&title)
-        .bind(&duration_reflection)
-        .bind(&purpose_reflection)
-        .bind(&general_notes)
-        .bind(mood_rating)
-        .bind(productivity_rating)
-        .bind(&now)
-        .bind(&now)
-        .bind(&now)
-        .execute(&self.pool)
+        .bind(&interruption.id)
+        .bind(&interruption.session_id)
+        .bind(&interruption.user_id)
+        .bind(interruption.occurred_at)
+        .bind(interruption.kind.as_str())
+        .bind(&interruption.reason)
+        .bind(&interruption.category)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            UPDATE pomodoro_sessions
+            SET interruption_count = interruption_count + 1,
+                interrupted = 1
+            WHERE id = ?
+            "#,
+        )
+        .bind(&session.id)
+        .execute(&mut *tx)
         .await?;
+        tx.commit().await?;
 
-        // Fetch the created/updated reflection
-        self.get_reflection_by_date(user_id, reflection_date).await
-            .map(|opt| opt.expect("Reflection should exist after create/update"))
+        Ok(interruption)
     }
 
-    pub async fn get_reflection_by_date(
+    /// Interruptions that occurred in [start_date, end_date), newest first.
+    pub async fn get_interruptions(
         &self,
         user_id: &str,
-        reflection_date: DateTime<Utc>,
-    ) -> Result<Option<DailyReflection>, sqlx::Error> {
-        let date_only = reflection_date.format("%Y-%m-%d").to_string();
-        let row = sqlx::query(
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> Result<Vec<Interruption>, TimeboxError> {
+        let rows = sqlx::query(
             r#"
-            SELECT id, user_id, reflection_date, title, duration_reflection,
-                   purpose_reflection, general_notes, mood_rating, productivity_rating,
-                   created_at, updated_at
-            FROM daily_reflections
-            WHERE user_id = ? AND reflection_date = ?
-            "#,
+            SELECT i.id, i.session_id, i.user_id, i.occurred_at, i.kind, i.reason, i.category, t.title as task_title
+            FROM session_interruptions i
+            JOIN pomodoro_sessions ps ON i.session_id = ps.id
+            LEFT JOIN tasks t ON ps.task_id = t.id
+            WHERE i.user_id = ? AND i.occurred_at >= ? AND i.occurred_at < ?
+            ORDER BY i.occurred_at DESC
+            "#
         )
         .bind(user_id)
-        .bind(&date_only)
-        .fetch_optional(&self.pool)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(DailyReflection {
-                id: row.get("id"),
-                user_id: row.get("user_id"),
-                reflection_date: row.get("reflection_date"),
-                title: row.get("title"),
-                duration_reflection: row.get("duration_reflection"),
-                purpose_reflection: row.get("purpose_reflection"),
-                general_notes: row.get("general_notes"),
-                mood_rating: row.get("mood_rating"),
-                productivity_rating: row.get("productivity_rating"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            }))
-        } else {
-            Ok(None)
-        }
+        Ok(rows.iter().map(interruption_from_row).collect())
     }
 
-    pub async fn get_reflections_by_month(
+    /// Interruption categories of a range, most frequent first. Categories differing only in case are counted together;
+    /// interruptions logged without one are grouped under `None`.
+    pub async fn get_interruption_sources(
         &self,
         user_id: &str,
-        year: i32,
-        month: i32,
-    ) -> Result<Vec<DailyReflection>, sqlx::Error> {
-        let start_date = format!("{:04}-{:02}-01", year, month);
-        let end_date = if month == 12 {
-            format!("{:04}-01-01", year + 1)
-        } else {
-            format!("{:04}-{:02}-01", year, month + 1)
-        };
-
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<InterruptionSource>, TimeboxError> {
         let rows = sqlx::query(
             r#"
-            SELECT id, user_id, reflection_date, title, duration_reflection,
-                   purpose_reflection, general_notes, mood_rating, productivity_rating,
-                   created_at, updated_at
-            FROM daily_reflections
-            WHERE user_id = ? AND reflection_date >= ? AND reflection_date < ?
-            ORDER BY reflection_date DESC
-            "#,
+            SELECT MIN(category) AS category,
+                   COUNT(*) AS interruptions,
+                   COALESCE(SUM(kind = 'internal'), 0) AS internal,
+                   COALESCE(SUM(kind = 'external'), 0) AS external,
+                   MAX(occurred_at) AS last_occurred_at
+            FROM session_interruptions
+            WHERE user_id = ? AND occurred_at >= ? AND occurred_at < ?
+            GROUP BY lower(category)
+            ORDER BY interruptions DESC, last_occurred_at DESC
+            LIMIT ?
+            "#
         )
         .bind(user_id)
-        .bind(&start_date)
-        .bind(&end_date)
+        .bind(start_date)
+        .bind(end_date)
+        .bind(limit)
         .fetch_all(&self.pool)
         .await?;
 
-        let reflections = rows.into_iter().map(|row| DailyReflection {
-            id: row.get("id"),
-            user_id: row.get("user_id"),
-            reflection_date: row.get("reflection_date"),
-            title: row.get("title"),
-            duration_reflection: row.get("duration_reflection"),
-            purpose_reflection: row.get("purpose_reflection"),
-            general_notes: row.get("general_notes"),
-            mood_rating: row.get("mood_rating"),
-            productivity_rating: row.get("productivity_rating"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        }).collect();
-
-        Ok(reflections)
+        Ok(rows.iter()
+            .map(|row| InterruptionSource {
+                category: row.get("category"),
+                interruptions: row.get("interruptions"),
+                internal: row.get("internal"),
+                external: row.get("external"),
+                last_occurred_at: row.get("last_occurred_at"),
+            })
+            .collect())
     }
 
-    pub async fn get_day_activities(
+    /// Opens a pause interval on a session.
+    pub async fn start_session_pause(
         &self,
-        user_id: &str,
-        date: DateTime<Utc>,
-    ) -> Result<DayActivities, sqlx::Error> {
-        let day_start = date.date_naive().and_hms_opt(0, 0, 0).unwrap().and_local_timezone(Utc).unwrap();
-        let day_end = day_start + chrono::Duration::days(1);
+        session_id: &str,
+        paused_at: DateTime<Utc>,
+        reason: Option<&str>,
+    ) -> Result<SessionPause, TimeboxError> {
+        let pause = SessionPause {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: session_id.to_string(),
+            paused_at,
+            resumed_at: None,
+            reason: reason.map(str::to_string),
+        };
 
-        // Get completed tasks for the day
-        let completed_tasks_rows = sqlx::query(
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
             r#"
-            SELECT id, user_id, title, estimated_pomodoros, completed, created_at
-            FROM tasks
-            WHERE user_id = ? AND completed = 1
-            AND date(created_at) = date(?)
-            ORDER BY created_at DESC
+            INSERT INTO session_pauses (id, session_id, paused_at, reason)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(&pause.id)
+        .bind(&pause.session_id)
+        .bind(pause.paused_at)
+        .bind(&pause.reason)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE pomodoro_sessions SET pause_count = pause_count + 1 WHERE id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(pause)
+    }
+
+    /// Closes the open pause interval of a session, if there is one, and adds it to the session's paused time.
+    pub async fn end_session_pause(&self, session_id: &str, resumed_at: DateTime<Utc>) -> Result<(), TimeboxError> {
+        let mut tx = self.pool.begin().await?;
+        let open_pause = sqlx::query(
+            r#"
+            SELECT id, session_id, paused_at, resumed_at, reason
+            FROM session_pauses
+            WHERE session_id = ? AND resumed_at IS NULL
+            ORDER BY paused_at DESC
+            LIMIT 1
             "#
         )
-        .bind(user_id)
-        .bind(&date)
+        .bind(session_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(|row| pause_from_row(&row));
+
+        let Some(pause) = open_pause else {
+            return Ok(());
+        };
+        let paused_seconds = (resumed_at - pause.paused_at).num_seconds().max(0);
+
+        sqlx::query("UPDATE session_pauses SET resumed_at = ? WHERE id = ?")
+            .bind(resumed_at)
+            .bind(&pause.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE pomodoro_sessions SET paused_seconds = paused_seconds + ? WHERE id = ?")
+            .bind(paused_seconds)
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn get_session_pauses(&self, session_id: &str) -> Result<Vec<SessionPause>, TimeboxError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, session_id, paused_at, resumed_at, reason
+            FROM session_pauses
+            WHERE session_id = ?
+            ORDER BY paused_at
+            "#
+        )
+        .bind(session_id)
         .fetch_all(&self.pool)
         .await?;
 
-        let completed_tasks = completed_tasks_rows.into_iter().map(|row| Task {
-            id: row.get("id"),
-            user_id: row.get("user_id"),
-            title: row.get("title"),
-            estimated_pomodoros: row.get("estimated_pomodoros"),
-            completed: row.get::<i32, &str>("completed") != 0,
-            created_at: row.get("created_at"),
-        }).collect();
+        Ok(rows.iter().map(pause_from_row).collect())
+    }
 
-        // Get pomodoro sessions for the day
-        let pomodoro_rows = sqlx::query(
+    pub async fn update_task(&self, task_id: &str, changes: TaskChanges) -> Result<Task, TimeboxError> {
+        let mut task = self.get_task(task_id).await?
+            .ok_or_else(|| TimeboxError::not_found("Task", task_id))?;
+
+        if changes.title.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            return Err(TimeboxError::Validation("Task title cannot be empty".to_string()));
+        }
+
+        let was_completed = task.completed;
+        if let Some(title) = changes.title {
+            task.title = title;
+        }
+        task.estimated_pomodoros = changes.estimated_pomodoros.unwrap_or(task.estimated_pomodoros);
+        task.completed = changes.completed.unwrap_or(task.completed);
+        if task.completed != was_completed {
+            task.completed_at = task.completed.then(Utc::now);
+        }
+        task.priority = changes.priority.unwrap_or(task.priority);
+        if changes.clear_due_date {
+            task.due_date = None;
+        } else if changes.due_date.is_some() {
+            task.due_date = changes.due_date;
+        }
+        if changes.clear_planned_for {
+            task.planned_for = None;
+        } else if changes.planned_for.is_some() {
+            task.planned_for = changes.planned_for;
+        }
+        let today = timezone::today(self.get_user_timezone(&task.user_id).await?);
+        if changes.clear_recurrence {
+            task.recurrence = None;
+        } else if let Some(rule) = changes.recurrence.as_deref() {
+            // The first occurrence from the due date, or from today, becomes the task's due date
+            let (recurrence, first) = Recurrence::schedule(rule, task.due_date.unwrap_or(today))?;
+            task.due_date = Some(first);
+            task.recurrence = Some(recurrence.to_string());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
             r#"
-            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time,
-                   ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.manual_override, ps.created_at, t.title as task_title
-            FROM pomodoro_sessions ps
-            LEFT JOIN tasks t ON ps.task_id = t.id
-            WHERE ps.user_id = ? AND ps.start_time >= ? AND ps.start_time < ? AND ps.session_type = 'FOCUS'
-            ORDER BY ps.start_time ASC
-            "#
+            UPDATE tasks
+            SET title = ?, estimated_pomodoros = ?, completed = ?, completed_at = ?, due_date = ?, priority = ?, planned_for = ?, recurrence = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&task.title)
+        .bind(task.estimated_pomodoros)
+        .bind(task.completed)
+        .bind(task.completed_at)
+        .bind(task.due_date)
+        .bind(task.priority.as_db())
+        .bind(task.planned_for)
+        .bind(&task.recurrence)
+        .bind(task_id)
+        .execute(&mut *tx)
+        .await?;
+        // Completing a task completes its subtasks; reopening one reopens the tasks above it
+        if task.completed && !was_completed {
+            complete_subtasks(&mut tx, task_id).await?;
+            // Completing an instance of a routine lines up the next one
+            create_next_instance(&mut tx, &task, today).await?;
+        } else if !task.completed && was_completed {
+            reopen_parent_tasks(&mut tx, task_id).await?;
+        }
+        tx.commit().await?;
+
+        Ok(task)
+    }
+
+    /// Creates the next instance of every routine whose current one was completed or whose day has passed,
+    /// skipping occurrences missed in between. Returns the instances created.
+    pub async fn roll_over_recurring_tasks(&self) -> Result<Vec<Task>, TimeboxError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id, t.user_id, t.title, t.estimated_pomodoros, t.completed, t.completed_at, t.goal_id, t.project_id, t.parent_task_id, t.due_date, t.priority, t.position, t.planned_for, t.recurrence, t.recurs_from, t.created_at,
+                   (SELECT json_group_array(tg.name) FROM task_tags tt JOIN tags tg ON tt.tag_id = tg.id WHERE tt.task_id = t.id) AS tags
+            FROM tasks t
+            WHERE t.recurrence IS NOT NULL AND NOT EXISTS (SELECT 1 FROM tasks n WHERE n.recurs_from = t.id)
+            ORDER BY t.created_at
+            "#,
         )
-        .bind(user_id)
-        .bind(&day_start)
-        .bind(&day_end)
         .fetch_all(&self.pool)
         .await?;
 
-        let pomodoro_sessions = pomodoro_rows.into_iter().map(|row| {
-            let session_type_str: String = row.get("session_type");
-            let session_type = match session_type_str.as_str() {
-                "FOCUS" => SessionType::Focus,
-                "SHORT_BREAK" => SessionType::ShortBreak,
-                "LONG_BREAK" => SessionType::LongBreak,
-                _ => SessionType::Focus,
+        let mut zones: HashMap<String, Tz> = HashMap::new();
+        let mut created = Vec::new();
+        let mut tx = self.pool.begin().await?;
+        for task in rows.iter().map(task_from_row) {
+            let tz = match zones.get(&task.user_id) {
+                Some(tz) => *tz,
+                None => {
+                    let tz = self.get_user_timezone(&task.user_id).await?;
+                    zones.insert(task.user_id.clone(), tz);
+                    tz
+                }
             };
+            let today = timezone::today(tz);
+            // An open instance stays current until its day is over
+            if !task.completed && task.due_date.is_none_or(|due| due >= today) {
+                continue;
+            }
+            if let Some(next) = create_next_instance(&mut tx, &task, today).await? {
+                created.push(next);
+            }
+        }
+        tx.commit().await?;
 
-            PomodoroSession {
-                id: row.get("id"),
-                user_id: row.get("user_id"),
-                task_id: row.get("task_id"),
-                session_type,
-                start_time: row.get("start_time"),
-                end_time: row.get("end_time"),
-                duration_seconds: row.get("duration_seconds"),
-                interrupted: row.get::<i32, &str>("interrupted") != 0,
-                interruption_count: row.get("interruption_count"),
-                manual_override: row.get::<i32, &str>("manual_override") != 0,
-                created_at: row.get("created_at"),
-                task_title: row.get("task_title"),
+        Ok(created)
+    }
+
+    /// Dates the user's routines come up on over the next `days` days, including today.
+    /// The current instance of each routine is listed with `task_id` set; later dates are not created yet.
+    pub async fn get_upcoming_occurrences(&self, user_id: &str, days: i64) -> Result<Vec<UpcomingOccurrence>, TimeboxError> {
+        if !(1..=MAX_UPCOMING_DAYS).contains(&days) {
+            return Err(TimeboxError::Validation(format!("Days must be between 1 and {}", MAX_UPCOMING_DAYS)));
+        }
+        let today = timezone::today(self.get_user_timezone(user_id).await?);
+        let last_day = today + chrono::Duration::days(days - 1);
+
+        let tasks = self.get_tasks(user_id, &ProjectTagFilter::default()).await?;
+        // Instances already followed by a newer one are not the routine's current instance
+        let followed: Vec<&str> = tasks.iter().filter_map(|task| task.recurs_from.as_deref()).collect();
+        let mut occurrences = Vec::new();
+        for task in tasks.iter().filter(|task| !task.completed && !followed.contains(&task.id.as_str())) {
+            let (Some(rule), Some(due)) = (task.recurrence.as_deref(), task.due_date) else {
+                continue;
+            };
+            let Ok(recurrence) = Recurrence::parse(rule) else {
+                continue;
+            };
+            if due <= last_day {
+                occurrences.push(UpcomingOccurrence { date: due, task_id: Some(task.id.clone()), title: task.title.clone(), recurrence: rule.to_string() });
             }
-        }).collect();
+            // Dates after the current instance, from today on; overdue instances are followed from today
+            let from = (due + chrono::Duration::days(1)).max(today);
+            for date in recurrence.occurrences_between(due, from, last_day) {
+                occurrences.push(UpcomingOccurrence { date, task_id: None, title: task.title.clone(), recurrence: rule.to_string() });
+            }
+        }
+        occurrences.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.title.cmp(&b.title)));
 
-        Ok(DayActivities {
-            pomodoro_sessions,
-            completed_tasks,
-        })
+        Ok(occurrences)
     }
-}
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct DayActivities {
-    pub pomodoro_sessions: Vec<PomodoroSession>,
-    pub completed_tasks: Vec<Task>,
-}
\ No newline at end of file
+    /// Moves the listed tasks into the order given. They take over the places they held
+    /// between them, so tasks left out of the list keep theirs.
+    pub async fn reorder_tasks(&self, user_id: &str, task_ids: &[String]) -> Result<Vec<Task>, TimeboxError> {
+        let mut tasks = self.get_tasks(user_id, &ProjectTagFilter::default()).await?;
+        for (index, task_id) in task_ids.iter().enumerate() {
+            if task_ids[..index].contains(task_id) {
+                return Err(TimeboxError::Validation(format!("Task {} is listed more than once", task_id)));
+            }
+            if !tasks.iter().any(|task| &task.id == task_id) {
+                return Err(TimeboxError::not_found("Task", task_id));
+            }
+        }
+
+        let slots: Vec<usize> = tasks.iter()
+            .enumerate()
+            .filter(|(_, task)| task_ids.contains(&task.id))
+            .map(|(slot, _)| slot)
+            .collect();
+        let mut moved: Vec<Task> = task_ids.iter()
+            .filter_map(|id| tasks.iter().find(|task| &task.id == id).cloned())
+            .collect();
+        for (slot, task) in slots.into_iter().zip(moved.drain(..)) {
+            tasks[slot] = task;
+        }
+
+        // Renumbering the whole list also spreads out positions that repeat, e.g. after an import
+        let mut tx = self.pool.begin().await?;
+        for (position, task) in tasks.iter_mut().enumerate() {
+            if task.position != position as i32 {
+                task.position = position as i32;
+                sqlx::query("UPDATE tasks SET position = ? WHERE id = ?")
+                    .bind(task.position)
+                    .bind(&task.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        tx.commit().await?;
+
+        Ok(tasks)
+    }
+
+    /// The tasks planned for `date` (today in the user's timezone by default), with a warning
+    /// when their estimates add up to more Pomodoros than fit into `available_minutes`.
+    pub async fn get_daily_plan(
+        &self,
+        user_id: &str,
+        date: Option<NaiveDate>,
+        available_minutes: Option<i32>,
+    ) -> Result<DailyPlan, TimeboxError> {
+        let available_minutes = available_minutes.unwrap_or(DEFAULT_PLAN_MINUTES);
+        if !(0..=24 * 60).contains(&available_minutes) {
+            return Err(TimeboxError::Validation("Available minutes must be between 0 and 1440".to_string()));
+        }
+        let settings = self.get_or_create_settings(user_id).await?;
+        let date = match date {
+            Some(date) => date,
+            None => timezone::today(self.get_user_timezone(user_id).await?),
+        };
+
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id, t.user_id, t.title, t.estimated_pomodoros, t.completed, t.completed_at, t.goal_id, t.project_id, t.parent_task_id, t.due_date, t.priority, t.position, t.planned_for, t.recurrence, t.recurs_from, t.created_at,
+                   (SELECT json_group_array(tg.name) FROM task_tags tt JOIN tags tg ON tt.tag_id = tg.id WHERE tt.task_id = t.id) AS tags
+            FROM tasks t
+            WHERE t.user_id = ? AND t.planned_for = ?
+            ORDER BY t.position, t.created_at DESC
+            "#
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+        let tasks: Vec<Task> = rows.iter().map(task_from_row).collect();
+
+        let planned_pomodoros = tasks.iter()
+            .filter(|task| !task.completed)
+            .map(|task| task.estimated_pomodoros.max(0) as i64)
+            .sum();
+        let capacity_pomodoros = settings.focus_capacity(available_minutes);
+        let overcommitted = planned_pomodoros > capacity_pomodoros;
+        let warning = overcommitted.then(|| format!(
+            "{} Pomodoros are planned but only {} fit into {}h{:02}",
+            planned_pomodoros,
+            capacity_pomodoros,
+            available_minutes / 60,
+            available_minutes % 60
+        ));
+
+        Ok(DailyPlan {
+            date: date.format("%Y-%m-%d").to_string(),
+            tasks,
+            planned_pomodoros,
+            available_minutes,
+            capacity_pomodoros,
+            overcommitted,
+            warning,
+        })
+    }
+
+    /// Links a task to a goal, or unlinks it when `goal_id` is None.
+    /// Focus sessions already spent on the task move with it, so both goals are recounted.
+    pub async fn set_task_goal(&self, task_id: &str, goal_id: Option<&str>) -> Result<Task, TimeboxError> {
+        let mut task = self.get_task(task_id).await?
+            .ok_or_else(|| TimeboxError::not_found("Task", task_id))?;
+
+        if let Some(goal_id) = goal_id {
+            let goal = self.get_goal(goal_id).await?
+                .ok_or_else(|| TimeboxError::not_found("Goal", goal_id))?;
+            if goal.user_id != task.user_id {
+                return Err(TimeboxError::Validation("Task and goal belong to different users".to_string()));
+            }
+        }
+
+        sqlx::query("UPDATE tasks SET goal_id = ? WHERE id = ?")
+            .bind(goal_id)
+            .bind(task_id)
+            .execute(&self.pool)
+            .await?;
+
+        let previous_goal_id = std::mem::replace(&mut task.goal_id, goal_id.map(str::to_string));
+        if let Some(previous_goal_id) = previous_goal_id {
+            self.refresh_goal_progress(&previous_goal_id).await?;
+        }
+        if let Some(goal_id) = goal_id {
+            self.refresh_goal_progress(goal_id).await?;
+        }
+
+        Ok(task)
+    }
+
+    /// Moves a task into a project, or out of its project when `project_id` is None.
+    pub async fn set_task_project(&self, task_id: &str, project_id: Option<&str>) -> Result<Task, TimeboxError> {
+        let mut task = self.get_task(task_id).await?
+            .ok_or_else(|| TimeboxError::not_found("Task", task_id))?;
+        if let Some(project_id) = project_id {
+            self.get_assignable_project(&task.user_id, project_id).await?;
+        }
+
+        sqlx::query("UPDATE tasks SET project_id = ? WHERE id = ?")
+            .bind(project_id)
+            .bind(task_id)
+            .execute(&self.pool)
+            .await?;
+
+        task.project_id = project_id.map(str::to_string);
+        Ok(task)
+    }
+
+    /// Replaces a task's tags. Tags the user doesn't have yet are created.
+    pub async fn set_task_tags(&self, task_id: &str, tags: &[String]) -> Result<Task, TimeboxError> {
+        let tags = normalize_tags(tags)?;
+        let task = self.get_task(task_id).await?
+            .ok_or_else(|| TimeboxError::not_found("Task", task_id))?;
+
+        let mut tx = self.pool.begin().await?;
+        replace_tag_links(&mut tx, "task_tags", "task_id", task_id, &task.user_id, &tags).await?;
+        tx.commit().await?;
+
+        self.get_task(task_id).await?
+            .ok_or_else(|| TimeboxError::not_found("Task", task_id))
+    }
+
+    /// Makes a task a subtask of `parent_task_id`, or a top-level task when it is None.
+    pub async fn set_task_parent(&self, task_id: &str, parent_task_id: Option<&str>) -> Result<Task, TimeboxError> {
+        let mut task = self.get_task(task_id).await?
+            .ok_or_else(|| TimeboxError::not_found("Task", task_id))?;
+
+        if let Some(parent_task_id) = parent_task_id {
+            self.get_parent_task(&task.user_id, parent_task_id).await?;
+            let query = format!("{} SELECT 1 FROM subtree WHERE id = ?", TASK_SUBTREE);
+            let within: Option<i32> = sqlx::query_scalar(&query)
+                .bind(task_id)
+                .bind(parent_task_id)
+                .fetch_optional(&self.pool)
+                .await?;
+            if within.is_some() {
+                return Err(TimeboxError::Validation("A task cannot be moved under itself or one of its subtasks".to_string()));
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("UPDATE tasks SET parent_task_id = ? WHERE id = ?")
+            .bind(parent_task_id)
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+        if !task.completed {
+            reopen_parent_tasks(&mut tx, task_id).await?;
+        }
+        tx.commit().await?;
+
+        task.parent_task_id = parent_task_id.map(str::to_string);
+        Ok(task)
+    }
+
+    /// Deletes a task along with its subtasks and checklist. Their sessions are kept without a task.
+    pub async fn delete_task(&self, task_id: &str) -> Result<(), TimeboxError> {
+        let query = format!("{} SELECT DISTINCT goal_id FROM tasks WHERE id IN subtree AND goal_id IS NOT NULL", TASK_SUBTREE);
+        let goal_ids: Vec<String> = sqlx::query_scalar(&query)
+            .bind(task_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+        // Deleting the current instance of a routine ends the routine, so the instance before it
+        // doesn't bring it back
+        let query = format!(
+            "{} UPDATE tasks SET recurrence = NULL WHERE id IN (SELECT recurs_from FROM tasks WHERE id IN subtree)",
+            TASK_SUBTREE
+        );
+        sqlx::query(&query)
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query(
+            r#"
+            DELETE FROM tasks
+            WHERE id = ?
+            "#,
+        )
+        .bind(task_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(TimeboxError::not_found("Task", task_id));
+        }
+        tx.commit().await?;
+
+        // The sessions of the task and its subtasks no longer count towards their goals
+        for goal_id in goal_ids {
+            self.refresh_goal_progress(&goal_id).await?;
+        }
+
+        Ok(())
+    }
+
+    // A task that can take subtasks: it exists and belongs to the same user
+    async fn get_parent_task(&self, user_id: &str, parent_task_id: &str) -> Result<Task, TimeboxError> {
+        let parent = self.get_task(parent_task_id).await?
+            .ok_or_else(|| TimeboxError::not_found("Task", parent_task_id))?;
+        if parent.user_id != user_id {
+            return Err(TimeboxError::not_found("Task", parent_task_id));
+        }
+        Ok(parent)
+    }
+
+    /// Every task of the user with its own focus count and the totals of its subtree.
+    pub async fn get_tasks_with_pomodoro_counts(&self, user_id: &str) -> Result<Vec<(Task, TaskCounts)>, TimeboxError> {
+        // tree pairs each task with itself and every subtask below it
+        let rows = sqlx::query(
+            r#"
+            WITH RECURSIVE tree(root_id, id) AS (
+                SELECT id, id FROM tasks WHERE user_id = ?
+                UNION
+                SELECT tree.root_id, t.id FROM tasks t JOIN tree ON t.parent_task_id = tree.id
+            ),
+            focus AS (
+                SELECT task_id, COUNT(*) AS pomodoros
+                FROM pomodoro_sessions
+                WHERE session_type = 'FOCUS' AND task_id IS NOT NULL
+                GROUP BY task_id
+            ),
+            rollup AS (
+                SELECT tree.root_id,
+                       SUM(d.estimated_pomodoros) AS total_estimated_pomodoros,
+                       SUM(COALESCE(f.pomodoros, 0)) AS total_pomodoros,
+                       COUNT(*) - 1 AS subtasks,
+                       SUM(CASE WHEN d.id != tree.root_id AND d.completed THEN 1 ELSE 0 END) AS completed_subtasks
+                FROM tree
+                JOIN tasks d ON d.id = tree.id
+                LEFT JOIN focus f ON f.task_id = d.id
+                GROUP BY tree.root_id
+            )
+            SELECT
+                t.id, t.user_id, t.title, t.estimated_pomodoros, t.completed, t.completed_at, t.goal_id, t.project_id, t.parent_task_id, t.due_date, t.priority, t.position, t.planned_for, t.recurrence, t.recurs_from, t.created_at,
+                (SELECT json_group_array(tg.name) FROM task_tags tt JOIN tags tg ON tt.tag_id = tg.id WHERE tt.task_id = t.id) AS tags,
+                COALESCE(f.pomodoros, 0) AS pomodoro_count,
+                r.total_estimated_pomodoros, r.total_pomodoros, r.subtasks, r.completed_subtasks,
+                (SELECT COUNT(*) FROM checklist_items ci WHERE ci.task_id = t.id) AS checklist_items,
+                (SELECT COUNT(*) FROM checklist_items ci WHERE ci.task_id = t.id AND ci.completed = 1) AS completed_checklist_items
+            FROM tasks t
+            JOIN rollup r ON r.root_id = t.id
+            LEFT JOIN focus f ON f.task_id = t.id
+            ORDER BY t.position, t.created_at DESC
+            "#
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let tasks_with_counts = rows.into_iter().map(|row| {
+            let task = task_from_row(&row);
+            let counts = TaskCounts {
+                actual_pomodoros: row.get("pomodoro_count"),
+                total_estimated_pomodoros: row.get("total_estimated_pomodoros"),
+                total_pomodoros: row.get("total_pomodoros"),
+                subtasks: row.get("subtasks"),
+                completed_subtasks: row.get("completed_subtasks"),
+                checklist_items: row.get("checklist_items"),
+                completed_checklist_items: row.get("completed_checklist_items"),
+            };
+            (task, counts)
+        }).collect();
+
+        Ok(tasks_with_counts)
+    }
+
+    // Checklist Methods
+
+    pub async fn get_checklist_items(&self, task_id: &str) -> Result<Vec<ChecklistItem>, TimeboxError> {
+        self.get_task(task_id).await?
+            .ok_or_else(|| TimeboxError::not_found("Task", task_id))?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, task_id, title, completed, position, created_at
+            FROM checklist_items
+            WHERE task_id = ?
+            ORDER BY position
+            "#
+        )
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(checklist_item_from_row).collect())
+    }
+
+    /// Appends an item to the end of a task's checklist.
+    pub async fn add_checklist_item(&self, task_id: &str, title: &str) -> Result<ChecklistItem, TimeboxError> {
+        let title = validate_checklist_title(title)?;
+        self.get_task(task_id).await?
+            .ok_or_else(|| TimeboxError::not_found("Task", task_id))?;
+
+        let item_id = Uuid::new_v4().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO checklist_items (id, task_id, title, position)
+            VALUES (?, ?, ?, (SELECT COALESCE(MAX(position) + 1, 0) FROM checklist_items WHERE task_id = ?))
+            "#,
+        )
+        .bind(&item_id)
+        .bind(task_id)
+        .bind(title)
+        .bind(task_id)
+        .execute(&self.pool)
+        .await?;
+
+        self.get_checklist_item(&item_id).await
+    }
+
+    pub async fn update_checklist_item(&self, item_id: &str, changes: ChecklistItemChanges) -> Result<ChecklistItem, TimeboxError> {
+        let current = self.get_checklist_item(item_id).await?;
+        let title = match changes.title.as_deref() {
+            Some(title) => validate_checklist_title(title)?,
+            None => current.title.as_str(),
+        };
+
+        sqlx::query("UPDATE checklist_items SET title = ?, completed = ? WHERE id = ?")
+            .bind(title)
+            .bind(changes.completed.unwrap_or(current.completed))
+            .bind(item_id)
+            .execute(&self.pool)
+            .await?;
+
+        self.get_checklist_item(item_id).await
+    }
+
+    /// Reorders a task's checklist; `item_ids` must list every item of the task exactly once.
+    pub async fn reorder_checklist_items(&self, task_id: &str, item_ids: &[String]) -> Result<Vec<ChecklistItem>, TimeboxError> {
+        let mut current: Vec<String> = self.get_checklist_items(task_id).await?
+            .into_iter()
+            .map(|item| item.id)
+            .collect();
+        let mut requested = item_ids.to_vec();
+        current.sort();
+        requested.sort();
+        if current != requested {
+            return Err(TimeboxError::Validation("The new order must list every checklist item of the task once".to_string()));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for (position, item_id) in item_ids.iter().enumerate() {
+            sqlx::query("UPDATE checklist_items SET position = ? WHERE id = ?")
+                .bind(position as i32)
+                .bind(item_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        self.get_checklist_items(task_id).await
+    }
+
+    pub async fn delete_checklist_item(&self, item_id: &str) -> Result<(), TimeboxError> {
+        let result = sqlx::query("DELETE FROM checklist_items WHERE id = ?")
+            .bind(item_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(TimeboxError::not_found("Checklist item", item_id));
+        }
+
+        Ok(())
+    }
+
+    async fn get_checklist_item(&self, item_id: &str) -> Result<ChecklistItem, TimeboxError> {
+        let row = sqlx::query("SELECT id, task_id, title, completed, position, created_at FROM checklist_items WHERE id = ?")
+            .bind(item_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| TimeboxError::not_found("Checklist item", item_id))?;
+
+        Ok(checklist_item_from_row(&row))
+    }
+
+    // Project Methods
+
+    pub async fn create_project(
+        &self,
+        user_id: &str,
+        name: &str,
+        color: Option<&str>,
+        hourly_rate: Option<f64>,
+    ) -> Result<Project, TimeboxError> {
+        let name = validate_project_name(name)?;
+        validate_hourly_rate(hourly_rate)?;
+        let color = color.map(str::trim).filter(|c| !c.is_empty());
+        self.ensure_project_name_free(user_id, name, None).await?;
+
+        let project = Project {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            name: name.to_string(),
+            color: color.map(str::to_string),
+            archived: false,
+            hourly_rate,
+            created_at: Utc::now(),
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO projects (id, user_id, name, color, archived, hourly_rate, created_at)
+            VALUES (?, ?, ?, ?, 0, ?, ?)
+            "#,
+        )
+        .bind(&project.id)
+        .bind(&project.user_id)
+        .bind(&project.name)
+        .bind(&project.color)
+        .bind(project.hourly_rate)
+        .bind(project.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(project)
+    }
+
+    /// The user's projects by name, archived ones last and only when asked for.
+    pub async fn get_projects(&self, user_id: &str, include_archived: bool) -> Result<Vec<Project>, TimeboxError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, user_id, name, color, archived, hourly_rate, created_at
+            FROM projects
+            WHERE user_id = ? AND (? OR archived = 0)
+            ORDER BY archived, name
+            "#
+        )
+        .bind(user_id)
+        .bind(include_archived)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(project_from_row).collect())
+    }
+
+    pub async fn get_project(&self, project_id: &str) -> Result<Option<Project>, TimeboxError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, user_id, name, color, archived, hourly_rate, created_at
+            FROM projects
+            WHERE id = ?
+            "#
+        )
+        .bind(project_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(project_from_row))
+    }
+
+    pub async fn update_project(&self, project_id: &str, changes: ProjectChanges) -> Result<Project, TimeboxError> {
+        let mut project = self.get_project(project_id).await?
+            .ok_or_else(|| TimeboxError::not_found("Project", project_id))?;
+
+        if let Some(name) = &changes.name {
+            let name = validate_project_name(name)?;
+            self.ensure_project_name_free(&project.user_id, name, Some(project_id)).await?;
+            project.name = name.to_string();
+        }
+        if changes.clear_color {
+            project.color = None;
+        } else if let Some(color) = changes.color.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+            project.color = Some(color.to_string());
+        }
+        if let Some(archived) = changes.archived {
+            project.archived = archived;
+        }
+        if changes.clear_hourly_rate {
+            project.hourly_rate = None;
+        } else if changes.hourly_rate.is_some() {
+            validate_hourly_rate(changes.hourly_rate)?;
+            project.hourly_rate = changes.hourly_rate;
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE projects
+            SET name = ?, color = ?, archived = ?, hourly_rate = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&project.name)
+        .bind(&project.color)
+        .bind(project.archived)
+        .bind(project.hourly_rate)
+        .bind(project_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(project)
+    }
+
+    /// Deletes a project. Its tasks and their sessions are kept, without a project.
+    pub async fn delete_project(&self, project_id: &str) -> Result<(), TimeboxError> {
+        let result = sqlx::query("DELETE FROM projects WHERE id = ?")
+            .bind(project_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(TimeboxError::not_found("Project", project_id));
+        }
+
+        Ok(())
+    }
+
+    // A project of the user that tasks may be added to, i.e. one that isn't archived
+    async fn get_assignable_project(&self, user_id: &str, project_id: &str) -> Result<Project, TimeboxError> {
+        let project = self.get_project(project_id).await?
+            .filter(|project| project.user_id == user_id)
+            .ok_or_else(|| TimeboxError::not_found("Project", project_id))?;
+        if project.archived {
+            return Err(TimeboxError::Validation(format!("Project \"{}\" is archived", project.name)));
+        }
+
+        Ok(project)
+    }
+
+    async fn ensure_project_name_free(&self, user_id: &str, name: &str, project_id: Option<&str>) -> Result<(), TimeboxError> {
+        let taken: Option<i64> = sqlx::query_scalar("SELECT 1 FROM projects WHERE user_id = ? AND name = ? AND id IS NOT ?")
+            .bind(user_id)
+            .bind(name)
+            .bind(project_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match taken {
+            Some(_) => Err(TimeboxError::Conflict(format!("A project named \"{}\" already exists", name))),
+            None => Ok(()),
+        }
+    }
+
+    // Tag Methods
+
+    pub async fn create_tag(&self, user_id: &str, name: &str) -> Result<Tag, TimeboxError> {
+        let name = normalize_tags(&[name.to_string()])?.remove(0);
+        self.ensure_tag_name_free(user_id, &name, None).await?;
+
+        let tag = Tag {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            name,
+            created_at: Utc::now(),
+        };
+
+        sqlx::query("INSERT INTO tags (id, user_id, name, created_at) VALUES (?, ?, ?, ?)")
+            .bind(&tag.id)
+            .bind(&tag.user_id)
+            .bind(&tag.name)
+            .bind(tag.created_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(tag)
+    }
+
+    pub async fn get_tags(&self, user_id: &str) -> Result<Vec<Tag>, TimeboxError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, user_id, name, created_at
+            FROM tags
+            WHERE user_id = ?
+            ORDER BY name
+            "#
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(tag_from_row).collect())
+    }
+
+    /// Renames a tag everywhere it is used.
+    pub async fn rename_tag(&self, tag_id: &str, name: &str) -> Result<Tag, TimeboxError> {
+        let name = normalize_tags(&[name.to_string()])?.remove(0);
+        let row = sqlx::query("SELECT id, user_id, name, created_at FROM tags WHERE id = ?")
+            .bind(tag_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| TimeboxError::not_found("Tag", tag_id))?;
+        let mut tag = tag_from_row(&row);
+        self.ensure_tag_name_free(&tag.user_id, &name, Some(tag_id)).await?;
+
+        sqlx::query("UPDATE tags SET name = ? WHERE id = ?")
+            .bind(&name)
+            .bind(tag_id)
+            .execute(&self.pool)
+            .await?;
+
+        tag.name = name;
+        Ok(tag)
+    }
+
+    /// Deletes a tag and removes it from every task and session.
+    pub async fn delete_tag(&self, tag_id: &str) -> Result<(), TimeboxError> {
+        let result = sqlx::query("DELETE FROM tags WHERE id = ?")
+            .bind(tag_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(TimeboxError::not_found("Tag", tag_id));
+        }
+
+        Ok(())
+    }
+
+    async fn ensure_tag_name_free(&self, user_id: &str, name: &str, tag_id: Option<&str>) -> Result<(), TimeboxError> {
+        let taken: Option<i64> = sqlx::query_scalar("SELECT 1 FROM tags WHERE user_id = ? AND name = ? AND id IS NOT ?")
+            .bind(user_id)
+            .bind(name)
+            .bind(tag_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match taken {
+            Some(_) => Err(TimeboxError::Conflict(format!("A tag named \"{}\" already exists", name))),
+            None => Ok(()),
+        }
+    }
+
+    /// Completed sessions started in [start_date, end_date), newest first.
+    pub async fn get_sessions_by_date_range(
+        &self,
+        user_id: &str,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+        session_type: Option<SessionType>,
+        filter: &ProjectTagFilter,
+    ) -> Result<Vec<PomodoroSession>, TimeboxError> {
+        let query = format!(
+            r#"
+            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time, ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.pause_count, ps.paused_seconds, ps.manual_override, ps.stop_reason, ps.created_at, t.title as task_title,
+                   (SELECT json_group_array(tg.name) FROM session_tags st JOIN tags tg ON st.tag_id = tg.id WHERE st.session_id = ps.id) AS tags
+            FROM pomodoro_sessions ps
+            LEFT JOIN tasks t ON ps.task_id = t.id
+            WHERE ps.user_id = ? AND ps.start_time >= ? AND ps.start_time < ? AND ps.end_time IS NOT NULL
+                AND (? IS NULL OR ps.session_type = ?) AND {}
+            ORDER BY ps.start_time DESC
+            "#,
+            SESSION_FILTER
+        );
+        let session_type = session_type.map(|st| st.to_string());
+
+        let rows = filter.bind(
+            sqlx::query(&query)
+                .bind(user_id)
+                .bind(start_date)
+                .bind(end_date)
+                .bind(&session_type)
+                .bind(&session_type)
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let sessions = rows.iter().map(session_from_row).collect();
+
+        Ok(sessions)
+    }
+
+    /// Aggregates the completed focus sessions started in [start_date, end_date) for the analytics dashboard.
+    /// Periods and heatmap buckets follow the user's timezone, including DST changes within the range,
+    /// which can span at most MAX_STATS_DAYS days.
+    pub async fn get_focus_stats(
+        &self,
+        user_id: &str,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+        granularity: Granularity,
+        filter: &ProjectTagFilter,
+    ) -> Result<FocusStats, TimeboxError> {
+        if end_date <= start_date {
+            return Err(TimeboxError::Validation("The end of the range must be after its start".to_string()));
+        }
+        if end_date - start_date > chrono::Duration::days(MAX_STATS_DAYS) {
+            return Err(TimeboxError::Validation(format!("The range can span at most {} days", MAX_STATS_DAYS)));
+        }
+
+        let tz = self.get_user_timezone(user_id).await?;
+        let segments = offset_segments_json(tz, start_date, end_date);
+        let focus_sessions = local_focus_sessions();
+        let totals_columns = r#"
+            COALESCE(SUM(duration_seconds), 0) AS focus_seconds,
+            COUNT(*) AS sessions,
+            COALESCE(SUM(interrupted = 0), 0) AS completed_sessions,
+            COALESCE(SUM(interrupted != 0), 0) AS interrupted_sessions,
+            COALESCE(SUM(interruption_count), 0) AS interruptions,
+            COALESCE(SUM(pause_count), 0) AS pauses,
+            COALESCE(SUM(paused_seconds), 0) AS paused_seconds,
+            COALESCE(SUM(duration_seconds + paused_seconds), 0) AS wall_clock_seconds,
+            COALESCE(AVG(duration_seconds), 0.0) AS average_session_seconds
+        "#;
+        let period_start = match granularity {
+            Granularity::Day => "date(local_start)",
+            Granularity::Week => "date(local_start, 'weekday 0', '-6 days')",
+            Granularity::Month => "strftime('%Y-%m-01', local_start)",
+        };
+
+        let totals_row = focus_query(&format!("{} SELECT {} FROM focus", focus_sessions, totals_columns), &segments, user_id, filter)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let period_rows = focus_query(
+            &format!(
+                "{} SELECT {} AS period_start, {} FROM focus GROUP BY 1 ORDER BY 1",
+                focus_sessions, period_start, totals_columns
+            ),
+            &segments,
+            user_id,
+            filter,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let heatmap_rows = focus_query(
+            &format!(
+                r#"
+                {}
+                SELECT (CAST(strftime('%w', local_start) AS INTEGER) + 6) % 7 AS weekday,
+                       CAST(strftime('%H', local_start) AS INTEGER) AS hour,
+                       COALESCE(SUM(duration_seconds), 0) AS focus_seconds,
+                       COUNT(*) AS sessions
+                FROM focus
+                GROUP BY 1, 2
+                ORDER BY 1, 2
+                "#,
+                focus_sessions
+            ),
+            &segments,
+            user_id,
+            filter,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let task_rows = focus_query(
+            &format!(
+                r#"
+                {}
+                SELECT f.task_id, t.title,
+                       COALESCE(SUM(f.duration_seconds), 0) AS focus_seconds,
+                       COUNT(*) AS sessions
+                FROM focus f
+                LEFT JOIN tasks t ON f.task_id = t.id
+                GROUP BY f.task_id
+                ORDER BY focus_seconds DESC
+                "#,
+                focus_sessions
+            ),
+            &segments,
+            user_id,
+            filter,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let goal_rows = focus_query(
+            &format!(
+                r#"
+                {}
+                SELECT g.id AS goal_id, g.title,
+                       COALESCE(SUM(f.duration_seconds), 0) AS focus_seconds,
+                       COUNT(*) AS sessions
+                FROM focus f
+                JOIN tasks t ON f.task_id = t.id
+                JOIN goals g ON t.goal_id = g.id
+                GROUP BY g.id
+                ORDER BY focus_seconds DESC
+                "#,
+                focus_sessions
+            ),
+            &segments,
+            user_id,
+            filter,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let project_rows = focus_query(
+            &format!(
+                r#"
+                {}
+                SELECT f.project_id, p.name, p.color, p.hourly_rate,
+                       COALESCE(SUM(f.duration_seconds), 0) AS focus_seconds,
+                       COUNT(*) AS sessions
+                FROM focus f
+                LEFT JOIN projects p ON f.project_id = p.id
+                GROUP BY f.project_id
+                ORDER BY focus_seconds DESC
+                "#,
+                focus_sessions
+            ),
+            &segments,
+            user_id,
+            filter,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(FocusStats {
+            totals: focus_totals_from_row(&totals_row),
+            periods: period_rows.iter().map(|row| FocusPeriodStats {
+                period_start: row.get("period_start"),
+                totals: focus_totals_from_row(row),
+            }).collect(),
+            heatmap: heatmap_rows.iter().map(|row| HeatmapBucket {
+                weekday: row.get("weekday"),
+                hour: row.get("hour"),
+                focus_seconds: row.get("focus_seconds"),
+                sessions: row.get("sessions"),
+            }).collect(),
+            tasks: task_rows.iter().map(|row| TaskFocusStats {
+                task_id: row.get("task_id"),
+                title: row.get("title"),
+                focus_seconds: row.get("focus_seconds"),
+                sessions: row.get("sessions"),
+            }).collect(),
+            goals: goal_rows.iter().map(|row| GoalFocusStats {
+                goal_id: row.get("goal_id"),
+                title: row.get("title"),
+                focus_seconds: row.get("focus_seconds"),
+                sessions: row.get("sessions"),
+            }).collect(),
+            projects: project_rows.iter().map(|row| ProjectFocusStats::new(
+                row.get("project_id"),
+                row.get("name"),
+                row.get("color"),
+                row.get("hourly_rate"),
+                row.get("focus_seconds"),
+                row.get("sessions"),
+            )).collect(),
+        })
+    }
+
+    // Focus seconds and completed Pomodoros per local day of [start_date, end_date)
+    async fn daily_focus(
+        &self,
+        user_id: &str,
+        tz: Tz,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> Result<HashMap<NaiveDate, (i64, i64)>, TimeboxError> {
+        let sql = format!(
+            r#"
+            {}
+            SELECT date(local_start) AS day,
+                   COALESCE(SUM(duration_seconds), 0) AS focus_seconds,
+                   COALESCE(SUM({}), 0) AS pomodoros
+            FROM focus
+            GROUP BY 1
+            "#,
+            local_focus_sessions(),
+            COMPLETED_FOCUS
+        );
+        let rows = focus_query(&sql, &offset_segments_json(tz, start_date, end_date), user_id, &ProjectTagFilter::default())
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter()
+            .filter_map(|row| {
+                let day = NaiveDate::parse_from_str(&row.get::<String, &str>("day"), "%Y-%m-%d").ok()?;
+                Some((day, (row.get("focus_seconds"), row.get("pomodoros"))))
+            })
+            .collect())
+    }
+
+    /// Today's focus measured against the user's daily target.
+    pub async fn get_daily_target_progress(&self, user_id: &str) -> Result<DailyTargetProgress, TimeboxError> {
+        let settings = self.get_or_create_settings(user_id).await?;
+        let tz = self.get_user_timezone(user_id).await?;
+        let today = timezone::today(tz);
+        let (start, end) = timezone::day_bounds(tz, today);
+
+        let (focus_seconds, pomodoros) = self.daily_focus(user_id, tz, start, end).await?
+            .remove(&today)
+            .unwrap_or((0, 0));
+
+        Ok(DailyTargetProgress::new(&settings, today, focus_seconds, pomodoros))
+    }
+
+    /// Current and longest runs of days meeting the daily target, in the user's timezone.
+    /// Rest days only count when the target was met; missing one doesn't break a streak,
+    /// and neither does today while it is still in progress. History older than MAX_STATS_DAYS is not looked at.
+    pub async fn get_streaks(&self, user_id: &str) -> Result<StreakSummary, TimeboxError> {
+        let settings = self.get_or_create_settings(user_id).await?;
+        let tz = self.get_user_timezone(user_id).await?;
+        let today = timezone::today(tz);
+
+        let first_session: Option<DateTime<Utc>> = sqlx::query_scalar(
+            r#"
+            SELECT MIN(start_time)
+            FROM pomodoro_sessions
+            WHERE user_id = ? AND session_type = 'FOCUS' AND end_time IS NOT NULL
+            "#
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+        let first_day = first_session
+            .map_or(today, |start| timezone::local_date(tz, start).min(today))
+            .max(today - chrono::Duration::days(MAX_STATS_DAYS - 1));
+
+        let days = self.daily_focus(
+            user_id,
+            tz,
+            timezone::start_of_day(tz, first_day),
+            timezone::day_bounds(tz, today).1,
+        ).await?;
+        let progress = |day: NaiveDate| {
+            let (focus_seconds, pomodoros) = days.get(&day).copied().unwrap_or((0, 0));
+            DailyTargetProgress::new(&settings, day, focus_seconds, pomodoros)
+        };
+
+        let mut current_streak = 0;
+        let mut longest_streak = 0;
+        let mut day = first_day;
+        while day <= today {
+            let progress = progress(day);
+            if progress.target_met {
+                current_streak += 1;
+                longest_streak = longest_streak.max(current_streak);
+            } else if !progress.rest_day && day != today {
+                current_streak = 0;
+            }
+            day = day.succ_opt().unwrap_or(today + chrono::Duration::days(1));
+        }
+
+        Ok(StreakSummary {
+            today: progress(today),
+            current_streak,
+            longest_streak,
+        })
+    }
+
+    // Daily Reflection Methods
+
+    pub async fn create_or_update_reflection(
+        &self,
+        user_id: &str,
+        reflection_date: DateTime<Utc>,
+        title: Option<String>,
+        duration_reflection: Option<String>,
+        purpose_reflection: Option<String>,
+        general_notes: Option<String>,
+        mood_rating: Option<i32>,
+        productivity_rating: Option<i32>,
+    ) -> Result<DailyReflection, TimeboxError> {
+        let reflection_id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        // Normalize to the user's local date so UNIQUE(user_id, reflection_date) works
+        let tz = self.get_user_timezone(user_id).await?;
+        let date_only = timezone::local_date(tz, reflection_date).format("%Y-%m-%d").to_string();
+
+        sqlx::query(
+            r#"
+            INSERT INTO daily_reflections (
+                id, user_id, reflection_date, title, duration_reflection,
+                purpose_reflection, general_notes, mood_rating, productivity_rating,
+                created_at, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(user_id, reflection_date)
+            DO UPDATE SET
+                title = excluded.title,
+                duration_reflection = excluded.duration_reflection,
+                purpose_reflection = excluded.purpose_reflection,
+                general_notes = excluded.general_notes,
+                mood_rating = excluded.mood_rating,
+                productivity_rating = excluded.productivity_rating,
+                updated_at = ?
+            "#,
+        )
+        .bind(&reflection_id)
+        .bind(user_id)
+        .bind(&date_only)
+        .bind(&title)
+        .bind(&duration_reflection)
+        .bind(&purpose_reflection)
+        .bind(&general_notes)
+        .bind(mood_rating)
+        .bind(productivity_rating)
+        .bind(&now)
+        .bind(&now)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        // Fetch the created/updated reflection
+        self.get_reflection_by_date(user_id, reflection_date).await
+            .map(|opt| opt.expect("Reflection should exist after create/update"))
+    }
+
+    pub async fn get_reflection_by_date(
+        &self,
+        user_id: &str,
+        reflection_date: DateTime<Utc>,
+    ) -> Result<Option<DailyReflection>, TimeboxError> {
+        let tz = self.get_user_timezone(user_id).await?;
+        let date_only = timezone::local_date(tz, reflection_date).format("%Y-%m-%d").to_string();
+        let row = sqlx::query(
+            r#"
+            SELECT id, user_id, reflection_date, title, duration_reflection,
+                   purpose_reflection, general_notes, mood_rating, productivity_rating,
+                   created_at, updated_at
+            FROM daily_reflections
+            WHERE user_id = ? AND reflection_date = ?
+            "#,
+        )
+        .bind(user_id)
+        .bind(&date_only)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(reflection_from_row))
+    }
+
+    pub async fn get_reflections_by_month(
+        &self,
+        user_id: &str,
+        year: i32,
+        month: i32,
+    ) -> Result<Vec<DailyReflection>, TimeboxError> {
+        let start_date = format!("{:04}-{:02}-01", year, month);
+        let end_date = if month == 12 {
+            format!("{:04}-01-01", year + 1)
+        } else {
+            format!("{:04}-{:02}-01", year, month + 1)
+        };
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, user_id, reflection_date, title, duration_reflection,
+                   purpose_reflection, general_notes, mood_rating, productivity_rating,
+                   created_at, updated_at
+            FROM daily_reflections
+            WHERE user_id = ? AND reflection_date >= ? AND reflection_date < ?
+            ORDER BY reflection_date DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(&start_date)
+        .bind(&end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let reflections = rows.iter().map(reflection_from_row).collect();
+
+        Ok(reflections)
+    }
+
+    /// Every user's data, for exports.
+    pub async fn get_snapshot(&self) -> Result<DataSnapshot, TimeboxError> {
+        let users = sqlx::query("SELECT id, name, timezone, created_at FROM users ORDER BY created_at")
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(user_from_row)
+            .collect();
+
+        let settings = sqlx::query(
+            r#"
+            SELECT user_id, focus_minutes, short_break_minutes, long_break_minutes, cycles_before_long_break, strict_mode, auto_start_breaks, sound_enabled, sound_volume,
+                   daily_target_unit, daily_target, rest_days
+            FROM pomodoro_settings
+            ORDER BY user_id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(settings_from_row)
+        .collect();
+
+        let tasks = sqlx::query(
+            r#"
+            SELECT id, user_id, title, estimated_pomodoros, completed, completed_at, goal_id, project_id, parent_task_id, due_date, priority, position, planned_for, recurrence, recurs_from, created_at,
+                   (SELECT json_group_array(tg.name) FROM task_tags tt JOIN tags tg ON tt.tag_id = tg.id WHERE tt.task_id = tasks.id) AS tags
+            FROM tasks
+            ORDER BY created_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(task_from_row)
+        .collect();
+
+        let sessions = sqlx::query(
+            r#"
+            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time,
+                   ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.pause_count, ps.paused_seconds, ps.manual_override, ps.stop_reason, ps.created_at, t.title as task_title,
+                   (SELECT json_group_array(tg.name) FROM session_tags st JOIN tags tg ON st.tag_id = tg.id WHERE st.session_id = ps.id) AS tags
+            FROM pomodoro_sessions ps
+            LEFT JOIN tasks t ON ps.task_id = t.id
+            ORDER BY ps.start_time
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(session_from_row)
+        .collect();
+
+        let session_pauses = sqlx::query(
+            r#"
+            SELECT id, session_id, paused_at, resumed_at, reason
+            FROM session_pauses
+            ORDER BY paused_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(pause_from_row)
+        .collect();
+
+        let interruptions = sqlx::query(
+            r#"
+            SELECT i.id, i.session_id, i.user_id, i.occurred_at, i.kind, i.reason, i.category, t.title as task_title
+            FROM session_interruptions i
+            JOIN pomodoro_sessions ps ON i.session_id = ps.id
+            LEFT JOIN tasks t ON ps.task_id = t.id
+            ORDER BY i.occurred_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(interruption_from_row)
+        .collect();
+
+        let session_audit = sqlx::query(
+            r#"
+            SELECT id, session_id, user_id, action, before_data, after_data, created_at
+            FROM session_audit
+            ORDER BY created_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(audit_from_row)
+        .collect();
+
+        let goals = sqlx::query(
+            r#"
+            SELECT id, user_id, title, target_pomodoros, completed_pomodoros, completed,
+                   category, motivation, target_date, description, created_at
+            FROM goals
+            ORDER BY created_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(goal_from_row)
+        .collect();
+
+        let reflections = sqlx::query(
+            r#"
+            SELECT id, user_id, reflection_date, title, duration_reflection,
+                   purpose_reflection, general_notes, mood_rating, productivity_rating,
+                   created_at, updated_at
+            FROM daily_reflections
+            ORDER BY reflection_date
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(reflection_from_row)
+        .collect();
+
+        let projects = sqlx::query("SELECT id, user_id, name, color, archived, hourly_rate, created_at FROM projects ORDER BY created_at")
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(project_from_row)
+            .collect();
+
+        let tags = sqlx::query("SELECT id, user_id, name, created_at FROM tags ORDER BY created_at")
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(tag_from_row)
+            .collect();
+
+        let checklist_items = sqlx::query("SELECT id, task_id, title, completed, position, created_at FROM checklist_items ORDER BY task_id, position")
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(checklist_item_from_row)
+            .collect();
+
+        Ok(DataSnapshot {
+            users,
+            settings,
+            tasks,
+            sessions,
+            session_pauses,
+            interruptions,
+            session_audit,
+            goals,
+            reflections,
+            projects,
+            tags,
+            checklist_items,
+        })
+    }
+
+    /// Loads an exported snapshot in one transaction. Rows whose id already exists are skipped;
+    /// in replace mode all existing data is deleted first.
+    pub async fn import_snapshot(&self, data: &DataSnapshot, mut report: ImportReport) -> Result<ImportReport, TimeboxError> {
+        let mut tx = self.pool.begin().await?;
+
+        if report.mode == ImportMode::Replace {
+            // Children before parents, so foreign keys never point at deleted rows
+            for table in ["active_timer", "session_audit", "session_interruptions", "session_pauses", "session_tags", "pomodoro_sessions", "daily_reflections", "checklist_items", "task_tags", "tasks", "tags", "projects", "goals", "pomodoro_settings", "users"] {
+                sqlx::query(&format!("DELETE FROM {}", table)).execute(&mut *tx).await?;
+            }
+        }
+
+        let mut users = ImportTableReport::new("users");
+        for user in &data.users {
+            if row_exists(&mut tx, "SELECT 1 FROM users WHERE id = ?", &user.id).await? {
+                users.skipped += 1;
+                continue;
+            }
+            let result = sqlx::query(
+                r#"
+                INSERT INTO users (id, name, timezone, timezone_source, created_at)
+                VALUES (?, ?, ?, 'user', ?)
+                "#
+            )
+            .bind(&user.id)
+            .bind(&user.name)
+            .bind(&user.timezone)
+            .bind(user.created_at)
+            .execute(&mut *tx)
+            .await;
+            report.record(&mut users, &user.id, result)?;
+        }
+
+        let mut settings = ImportTableReport::new("pomodoro_settings");
+        for s in &data.settings {
+            if row_exists(&mut tx, "SELECT 1 FROM pomodoro_settings WHERE user_id = ?", &s.user_id).await? {
+                settings.skipped += 1;
+                continue;
+            }
+            // Archives are checked like settings changed in the app
+            let s = match s.clone().validated() {
+                Ok(s) => s,
+                Err(TimeboxError::Validation(message)) => {
+                    settings.conflicting += 1;
+                    report.messages.push(format!("{} {}: {}", settings.table, s.user_id, message));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let result = sqlx::query(
+                r#"
+                INSERT INTO pomodoro_settings (user_id, focus_minutes, short_break_minutes, long_break_minutes, cycles_before_long_break, strict_mode, auto_start_breaks, sound_enabled, sound_volume, daily_target_unit, daily_target, rest_days)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&s.user_id)
+            .bind(s.focus_minutes)
+            .bind(s.short_break_minutes)
+            .bind(s.long_break_minutes)
+            .bind(s.cycles_before_long_break)
+            .bind(s.strict_mode)
+            .bind(s.auto_start_breaks)
+            .bind(s.sound_enabled)
+            .bind(s.sound_volume)
+            .bind(s.daily_target_unit.as_str())
+            .bind(s.daily_target)
+            .bind(rest_days_to_db(&s.rest_days))
+            .execute(&mut *tx)
+            .await;
+            report.record(&mut settings, &s.user_id, result)?;
+        }
+
+        let mut goals = ImportTableReport::new("goals");
+        for goal in &data.goals {
+            if row_exists(&mut tx, "SELECT 1 FROM goals WHERE id = ?", &goal.id).await? {
+                goals.skipped += 1;
+                continue;
+            }
+            let result = sqlx::query(
+                r#"
+                INSERT INTO goals (id, user_id, title, target_pomodoros, completed_pomodoros, completed, category, motivation, target_date, description, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&goal.id)
+            .bind(&goal.user_id)
+            .bind(&goal.title)
+            .bind(goal.target_pomodoros)
+            .bind(goal.completed_pomodoros)
+            .bind(goal.completed)
+            .bind(&goal.category)
+            .bind(&goal.motivation)
+            .bind(goal.target_date)
+            .bind(&goal.description)
+            .bind(goal.created_at)
+            .execute(&mut *tx)
+            .await;
+            report.record(&mut goals, &goal.id, result)?;
+        }
+
+        let mut projects = ImportTableReport::new("projects");
+        for project in &data.projects {
+            if row_exists(&mut tx, "SELECT 1 FROM projects WHERE id = ?", &project.id).await? {
+                projects.skipped += 1;
+                continue;
+            }
+            let result = sqlx::query(
+                r#"
+                INSERT INTO projects (id, user_id, name, color, archived, hourly_rate, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&project.id)
+            .bind(&project.user_id)
+            .bind(&project.name)
+            .bind(&project.color)
+            .bind(project.archived)
+            .bind(project.hourly_rate)
+            .bind(project.created_at)
+            .execute(&mut *tx)
+            .await;
+            report.record(&mut projects, &project.id, result)?;
+        }
+
+        let mut tags = ImportTableReport::new("tags");
+        for tag in &data.tags {
+            if row_exists(&mut tx, "SELECT 1 FROM tags WHERE id = ?", &tag.id).await? {
+                tags.skipped += 1;
+                continue;
+            }
+            let result = sqlx::query("INSERT INTO tags (id, user_id, name, created_at) VALUES (?, ?, ?, ?)")
+                .bind(&tag.id)
+                .bind(&tag.user_id)
+                .bind(&tag.name)
+                .bind(tag.created_at)
+                .execute(&mut *tx)
+                .await;
+            report.record(&mut tags, &tag.id, result)?;
+        }
+
+        // Tag links are stored by name on tasks and sessions, and only restored for rows inserted here
+        let mut tasks = ImportTableReport::new("tasks");
+        let mut linked = Vec::new();
+        for task in &data.tasks {
+            if row_exists(&mut tx, "SELECT 1 FROM tasks WHERE id = ?", &task.id).await? {
+                tasks.skipped += 1;
+                continue;
+            }
+            let result = sqlx::query(
+                r#"
+                INSERT INTO tasks (id, user_id, title, estimated_pomodoros, completed, completed_at, goal_id, project_id, due_date, priority, position, planned_for, recurrence, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&task.id)
+            .bind(&task.user_id)
+            .bind(&task.title)
+            .bind(task.estimated_pomodoros)
+            .bind(task.completed)
+            // Older archives only say that a task was completed, not when
+            .bind(task.completed.then(|| task.completed_at.unwrap_or(task.created_at)))
+            .bind(&task.goal_id)
+            .bind(&task.project_id)
+            .bind(task.due_date)
+            .bind(task.priority.as_db())
+            .bind(task.position)
+            .bind(task.planned_for)
+            .bind(&task.recurrence)
+            .bind(task.created_at)
+            .execute(&mut *tx)
+            .await;
+            let inserted = result.is_ok();
+            report.record(&mut tasks, &task.id, result)?;
+            if inserted {
+                replace_tag_links(&mut tx, "task_tags", "task_id", &task.id, &task.user_id, &task.tags).await?;
+                if task.parent_task_id.is_some() || task.recurs_from.is_some() {
+                    linked.push(task);
+                }
+            }
+        }
+
+        // Parents and previous instances of routines are linked once every task is in, since either
+        // may be listed after the task. Subtasks whose parent is not in the database end up as top-level tasks.
+        for task in linked {
+            sqlx::query(
+                r#"
+                UPDATE tasks
+                SET parent_task_id = (SELECT id FROM tasks WHERE id = ?), recurs_from = (SELECT id FROM tasks WHERE id = ?)
+                WHERE id = ?
+                "#,
+            )
+            .bind(&task.parent_task_id)
+            .bind(&task.recurs_from)
+            .bind(&task.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let mut checklist = ImportTableReport::new("checklist_items");
+        for item in &data.checklist_items {
+            if row_exists(&mut tx, "SELECT 1 FROM checklist_items WHERE id = ?", &item.id).await? {
+                checklist.skipped += 1;
+                continue;
+            }
+            let result = sqlx::query(
+                r#"
+                INSERT INTO checklist_items (id, task_id, title, completed, position, created_at)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&item.id)
+            .bind(&item.task_id)
+            .bind(&item.title)
+            .bind(item.completed)
+            .bind(item.position)
+            .bind(item.created_at)
+            .execute(&mut *tx)
+            .await;
+            report.record(&mut checklist, &item.id, result)?;
+        }
+
+        let mut sessions = ImportTableReport::new("pomodoro_sessions");
+        for session in &data.sessions {
+            if row_exists(&mut tx, "SELECT 1 FROM pomodoro_sessions WHERE id = ?", &session.id).await? {
+                sessions.skipped += 1;
+                continue;
+            }
+            let result = sqlx::query(
+                r#"
+                INSERT INTO pomodoro_sessions (id, user_id, task_id, session_type, start_time, end_time, duration_seconds, interrupted, interruption_count, pause_count, paused_seconds, manual_override, stop_reason, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&session.id)
+            .bind(&session.user_id)
+            .bind(&session.task_id)
+            .bind(session.session_type.to_string())
+            .bind(session.start_time)
+            .bind(session.end_time)
+            .bind(session.duration_seconds)
+            .bind(session.interrupted)
+            .bind(session.interruption_count)
+            .bind(session.pause_count)
+            .bind(session.paused_seconds)
+            .bind(session.manual_override)
+            .bind(&session.stop_reason)
+            .bind(session.created_at)
+            .execute(&mut *tx)
+            .await;
+            let inserted = result.is_ok();
+            report.record(&mut sessions, &session.id, result)?;
+            if inserted {
+                replace_tag_links(&mut tx, "session_tags", "session_id", &session.id, &session.user_id, &session.tags).await?;
+            }
+        }
+
+        let mut pauses = ImportTableReport::new("session_pauses");
+        for pause in &data.session_pauses {
+            if row_exists(&mut tx, "SELECT 1 FROM session_pauses WHERE id = ?", &pause.id).await? {
+                pauses.skipped += 1;
+                continue;
+            }
+            let result = sqlx::query(
+                r#"
+                INSERT INTO session_pauses (id, session_id, paused_at, resumed_at, reason)
+                VALUES (?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&pause.id)
+            .bind(&pause.session_id)
+            .bind(pause.paused_at)
+            .bind(pause.resumed_at)
+            .bind(&pause.reason)
+            .execute(&mut *tx)
+            .await;
+            report.record(&mut pauses, &pause.id, result)?;
+        }
+
+        let mut interruptions = ImportTableReport::new("session_interruptions");
+        for interruption in &data.interruptions {
+            if row_exists(&mut tx, "SELECT 1 FROM session_interruptions WHERE id = ?", &interruption.id).await? {
+                interruptions.skipped += 1;
+                continue;
+            }
+            let result = sqlx::query(
+                r#"
+                INSERT INTO session_interruptions (id, session_id, user_id, occurred_at, kind, reason, category)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&interruption.id)
+            .bind(&interruption.session_id)
+            .bind(&interruption.user_id)
+            .bind(interruption.occurred_at)
+            .bind(interruption.kind.as_str())
+            .bind(&interruption.reason)
+            .bind(&interruption.category)
+            .execute(&mut *tx)
+            .await;
+            report.record(&mut interruptions, &interruption.id, result)?;
+        }
+
+        let mut audit = ImportTableReport::new("session_audit");
+        for entry in &data.session_audit {
+            if row_exists(&mut tx, "SELECT 1 FROM session_audit WHERE id = ?", &entry.id).await? {
+                audit.skipped += 1;
+                continue;
+            }
+            let result = sqlx::query(
+                r#"
+                INSERT INTO session_audit (id, session_id, user_id, action, before_data, after_data, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&entry.id)
+            .bind(&entry.session_id)
+            .bind(&entry.user_id)
+            .bind(entry.action.as_str())
+            .bind(serde_json::to_string(&entry.before).map_err(std::io::Error::from)?)
+            .bind(serde_json::to_string(&entry.after).map_err(std::io::Error::from)?)
+            .bind(entry.created_at)
+            .execute(&mut *tx)
+            .await;
+            report.record(&mut audit, &entry.id, result)?;
+        }
+
+        let mut reflections = ImportTableReport::new("daily_reflections");
+        for reflection in &data.reflections {
+            if row_exists(&mut tx, "SELECT 1 FROM daily_reflections WHERE id = ?", &reflection.id).await? {
+                reflections.skipped += 1;
+                continue;
+            }
+            let result = sqlx::query(
+                r#"
+                INSERT INTO daily_reflections (
+                    id, user_id, reflection_date, title, duration_reflection,
+                    purpose_reflection, general_notes, mood_rating, productivity_rating,
+                    created_at, updated_at
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&reflection.id)
+            .bind(&reflection.user_id)
+            .bind(&reflection.reflection_date)
+            .bind(&reflection.title)
+            .bind(&reflection.duration_reflection)
+            .bind(&reflection.purpose_reflection)
+            .bind(&reflection.general_notes)
+            .bind(reflection.mood_rating)
+            .bind(reflection.productivity_rating)
+            .bind(reflection.created_at)
+            .bind(reflection.updated_at)
+            .execute(&mut *tx)
+            .await;
+            report.record(&mut reflections, &reflection.id, result)?;
+        }
+
+        // A replaced database still needs the default user the app starts with
+        sqlx::query("INSERT OR IGNORE INTO users (id, name) VALUES ('default_user', 'Default User')")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("INSERT OR IGNORE INTO pomodoro_settings (user_id) VALUES ('default_user')")
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        report.tables = vec![users, settings, goals, projects, tags, tasks, checklist, sessions, pauses, interruptions, audit, reflections];
+        self.refresh_all_goal_progress().await?;
+
+        Ok(report)
+    }
+
+    /// Stores sessions read from another tracker as manual sessions, in one transaction.
+    /// Tasks are matched by title or created; sessions already logged with the same start and end are skipped,
+    /// and ones overlapping other logged sessions are reported as conflicting.
+    pub async fn import_manual_sessions(
+        &self,
+        user_id: &str,
+        sessions: &[ImportedSession],
+        mut report: ImportReport,
+    ) -> Result<ImportReport, TimeboxError> {
+        self.get_user(user_id).await?
+            .ok_or_else(|| TimeboxError::not_found("User", user_id))?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut tasks = ImportTableReport::new("tasks");
+        let mut imported = ImportTableReport::new("pomodoro_sessions");
+        let mut task_ids: HashMap<String, String> = HashMap::new();
+
+        for session in sessions {
+            let line = format!("line {}", session.line);
+
+            let duplicate: Option<i64> = sqlx::query_scalar(
+                r#"
+                SELECT 1
+                FROM pomodoro_sessions
+                WHERE user_id = ? AND datetime(start_time) = datetime(?) AND datetime(end_time) = datetime(?)
+                "#
+            )
+            .bind(user_id)
+            .bind(session.start_time)
+            .bind(session.end_time)
+            .fetch_optional(&mut *tx)
+            .await?;
+            if duplicate.is_some() {
+                imported.skipped += 1;
+                continue;
+            }
+
+            let mut row = PomodoroSession {
+                id: Uuid::new_v4().to_string(),
+                user_id: user_id.to_string(),
+                task_id: None,
+                session_type: session.session_type.clone(),
+                start_time: session.start_time,
+                end_time: Some(session.end_time),
+                duration_seconds: Some((session.end_time - session.start_time).num_seconds() as i32),
+                interrupted: false,
+                interruption_count: 0,
+                pause_count: 0,
+                paused_seconds: 0,
+                manual_override: true,
+                stop_reason: None,
+                created_at: Utc::now(),
+                task_title: None,
+                tags: Vec::new(),
+            };
+            match ensure_no_overlap(&mut tx, &row, &row.id).await {
+                Ok(()) => {}
+                Err(TimeboxError::Conflict(message)) => {
+                    imported.conflicting += 1;
+                    report.messages.push(format!("{} {}: {}", imported.table, line, message));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            }
+
+            row.task_id = match &session.task_title {
+                Some(title) => match task_ids.get(title) {
+                    Some(task_id) => Some(task_id.clone()),
+                    None => {
+                        let existing: Option<String> = sqlx::query_scalar(
+                            "SELECT id FROM tasks WHERE user_id = ? AND title = ? ORDER BY created_at LIMIT 1"
+                        )
+                        .bind(user_id)
+                        .bind(title)
+                        .fetch_optional(&mut *tx)
+                        .await?;
+
+                        let task_id = match existing {
+                            Some(task_id) => task_id,
+                            None => {
+                                let task_id = Uuid::new_v4().to_string();
+                                let result = sqlx::query("INSERT INTO tasks (id, user_id, title) VALUES (?, ?, ?)")
+                                    .bind(&task_id)
+                                    .bind(user_id)
+                                    .bind(title)
+                                    .execute(&mut *tx)
+                                    .await;
+                                report.record(&mut tasks, &line, result)?;
+                                task_id
+                            }
+                        };
+                        task_ids.insert(title.clone(), task_id.clone());
+                        Some(task_id)
+                    }
+                },
+                None => None,
+            };
+
+            let result = insert_session_row(&mut tx, &row).await;
+            report.record(&mut imported, &line, result)?;
+        }
+
+        tx.commit().await?;
+
+        report.tables = vec![tasks, imported];
+        self.refresh_all_goal_progress().await?;
+
+        Ok(report)
+    }
+
+    /// Tasks completed in [start_date, end_date), most recently completed first.
+    pub async fn get_completed_tasks(
+        &self,
+        user_id: &str,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+        filter: &ProjectTagFilter,
+    ) -> Result<Vec<Task>, TimeboxError> {
+        // datetime() normalizes CURRENT_TIMESTAMP values and bound RFC 3339 values to one format
+        let query = format!(
+            r#"
+            SELECT t.id, t.user_id, t.title, t.estimated_pomodoros, t.completed, t.completed_at, t.goal_id, t.project_id, t.parent_task_id, t.due_date, t.priority, t.position, t.planned_for, t.recurrence, t.recurs_from, t.created_at,
+                   (SELECT json_group_array(tg.name) FROM task_tags tt JOIN tags tg ON tt.tag_id = tg.id WHERE tt.task_id = t.id) AS tags
+            FROM tasks t
+            WHERE t.user_id = ? AND t.completed = 1
+            AND datetime(t.completed_at) >= datetime(?) AND datetime(t.completed_at) < datetime(?)
+            AND {}
+            ORDER BY t.completed_at DESC
+            "#,
+            TASK_FILTER
+        );
+
+        let rows = filter.bind(sqlx::query(&query).bind(user_id).bind(start_date).bind(end_date))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(task_from_row).collect())
+    }
+
+    pub async fn get_day_activities(
+        &self,
+        user_id: &str,
+        date: DateTime<Utc>,
+    ) -> Result<DayActivities, TimeboxError> {
+        let tz = self.get_user_timezone(user_id).await?;
+        let (day_start, day_end) = timezone::day_bounds(tz, timezone::local_date(tz, date));
+
+        let completed_tasks = self.get_completed_tasks(user_id, day_start, day_end, &ProjectTagFilter::default()).await?;
+
+        // Get pomodoro sessions for the day
+        let pomodoro_rows = sqlx::query(
+            r#"
+            SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time,
+                   ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.pause_count, ps.paused_seconds, ps.manual_override, ps.stop_reason, ps.created_at, t.title as task_title,
+                   (SELECT json_group_array(tg.name) FROM session_tags st JOIN tags tg ON st.tag_id = tg.id WHERE st.session_id = ps.id) AS tags
+            FROM pomodoro_sessions ps
+            LEFT JOIN tasks t ON ps.task_id = t.id
+            WHERE ps.user_id = ? AND ps.start_time >= ? AND ps.start_time < ? AND ps.session_type = 'FOCUS'
+            ORDER BY ps.start_time ASC
+            "#
+        )
+        .bind(user_id)
+        .bind(&day_start)
+        .bind(&day_end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let pomodoro_sessions = pomodoro_rows.iter().map(session_from_row).collect();
+
+        Ok(DayActivities {
+            pomodoro_sessions,
+            completed_tasks,
+        })
+    }
+}
+
+// Focus sessions of a range with their start shifted to local wall-clock time.
+// Binds the JSON from `offset_segments_json`, the user id, then a `ProjectTagFilter`.
+fn local_focus_sessions() -> String {
+    format!(
+        r#"
+        WITH segments AS (
+            SELECT json_extract(value, '$.start') AS range_start,
+                   json_extract(value, '$.end') AS range_end,
+                   json_extract(value, '$.offset') AS utc_offset
+            FROM json_each(?)
+        ),
+        focus AS (
+            SELECT ps.task_id, t.project_id, ps.interrupted, ps.stop_reason, ps.interruption_count, ps.pause_count, ps.paused_seconds,
+                   COALESCE(ps.duration_seconds, 0) AS duration_seconds,
+                   datetime(ps.start_time, printf('%+d seconds', s.utc_offset)) AS local_start
+            FROM segments s
+            JOIN pomodoro_sessions ps
+                ON ps.user_id = ? AND ps.start_time >= s.range_start AND ps.start_time < s.range_end
+            LEFT JOIN tasks t ON ps.task_id = t.id
+            WHERE ps.session_type = 'FOCUS' AND ps.end_time IS NOT NULL AND {}
+        )
+        "#,
+        SESSION_FILTER
+    )
+}
+
+// A query over `local_focus_sessions()` with all of its parameters bound
+fn focus_query<'q>(
+    sql: &'q str,
+    segments: &'q str,
+    user_id: &'q str,
+    filter: &'q ProjectTagFilter,
+) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    filter.bind(sqlx::query(sql).bind(segments).bind(user_id))
+}
+
+// Conditions bound by `ProjectTagFilter::bind`, for tasks `t`
+const TASK_FILTER: &str = r#"
+    (? IS NULL OR t.project_id = ?)
+    AND (? IS NULL OR EXISTS (
+        SELECT 1 FROM task_tags tt JOIN tags tg ON tt.tag_id = tg.id
+        WHERE tt.task_id = t.id AND tg.name = ?
+    ))
+"#;
+
+// Same for sessions `ps` joined to their task `t`; a session has its own tags and those of its task
+const SESSION_FILTER: &str = r#"
+    (? IS NULL OR t.project_id = ?)
+    AND (? IS NULL OR EXISTS (
+        SELECT 1 FROM tags tg
+        WHERE tg.name = ? AND tg.id IN (
+            SELECT tag_id FROM session_tags WHERE session_id = ps.id
+            UNION SELECT tag_id FROM task_tags WHERE task_id = ps.task_id
+        )
+    ))
+"#;
+
+// Focus sessions that count as completed Pomodoros: ones that ran to the end rather than being
+// interrupted or stopped early
+const COMPLETED_FOCUS: &str = "duration_seconds > 0 AND interrupted = 0 AND stop_reason IS NULL";
+
+// `subtree` holds the bound task and every subtask below it; UNION stops at repeated rows
+const TASK_SUBTREE: &str = r#"
+    WITH RECURSIVE subtree(id) AS (
+        SELECT ?
+        UNION SELECT t.id FROM tasks t JOIN subtree s ON t.parent_task_id = s.id
+    )
+"#;
+
+// Bounds are formatted exactly like bound DateTime values so they compare correctly as text
+fn offset_segments_json(tz: Tz, start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    let segments: Vec<serde_json::Value> = timezone::offset_segments(tz, start, end)
+        .into_iter()
+        .map(|(start, end, offset)| serde_json::json!({
+            "start": start.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, false),
+            "end": end.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, false),
+            "offset": offset,
+        }))
+        .collect();
+    serde_json::Value::Array(segments).to_string()
+}
+
+async fn row_exists(tx: &mut Transaction<'_, Sqlite>, sql: &str, id: &str) -> Result<bool, sqlx::Error> {
+    let found: Option<i64> = sqlx::query_scalar(sql)
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    Ok(found.is_some())
+}
+
+// Untracked stretches shorter than this are not worth a session of their own
+const MIN_MANUAL_GAP: chrono::Duration = chrono::Duration::minutes(1);
+
+// Gaps of at least MIN_MANUAL_GAP between `start` and `end` not covered by the tracked ranges, which are sorted by start
+fn untracked_gaps(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    tracked: impl Iterator<Item = (DateTime<Utc>, DateTime<Utc>)>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut gaps = Vec::new();
+    let mut cursor = start;
+    for (tracked_start, tracked_end) in tracked {
+        if tracked_start > cursor {
+            gaps.push((cursor, tracked_start.min(end)));
+        }
+        cursor = cursor.max(tracked_end);
+    }
+    if cursor < end {
+        gaps.push((cursor, end));
+    }
+
+    gaps.retain(|(gap_start, gap_end)| *gap_end - *gap_start >= MIN_MANUAL_GAP);
+    gaps
+}
+
+// Gives each part of a range of `span` seconds the share of `duration` its length makes up of the whole range.
+// Parts whose share rounds down to nothing are left out rather than logged as empty sessions
+fn share_duration(
+    duration: i32,
+    span: i64,
+    parts: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Vec<(DateTime<Utc>, DateTime<Utc>, i32)> {
+    parts.iter()
+        .map(|&(start, end)| (start, end, ((duration as i64 * (end - start).num_seconds() + span / 2) / span) as i32))
+        .filter(|&(_, _, part_duration)| part_duration > 0)
+        .collect()
+}
+
+// Loads a session for a history change; running sessions belong to the timer and can't be changed
+async fn fetch_completed_session(tx: &mut Transaction<'_, Sqlite>, session_id: &str) -> Result<PomodoroSession, TimeboxError> {
+    let session = sqlx::query(
+        r#"
+        SELECT ps.id, ps.user_id, ps.task_id, ps.session_type, ps.start_time, ps.end_time, ps.duration_seconds, ps.interrupted, ps.interruption_count, ps.pause_count, ps.paused_seconds, ps.manual_override, ps.stop_reason, ps.created_at, t.title as task_title,
+               (SELECT json_group_array(tg.name) FROM session_tags st JOIN tags tg ON st.tag_id = tg.id WHERE st.session_id = ps.id) AS tags
+        FROM pomodoro_sessions ps
+        LEFT JOIN tasks t ON ps.task_id = t.id
+        WHERE ps.id = ?
+        "#
+    )
+    .bind(session_id)
+    .fetch_optional(&mut **tx)
+    .await?
+    .map(|row| session_from_row(&row))
+    .ok_or_else(|| TimeboxError::not_found("Session", session_id))?;
+
+    if session.end_time.is_none() {
+        return Err(TimeboxError::Conflict(format!("Session {} is still running", session_id)));
+    }
+
+    Ok(session)
+}
+
+// Seconds between start and end, which must be in order
+fn validate_session_span(session: &PomodoroSession) -> Result<i64, TimeboxError> {
+    match session.end_time {
+        Some(end_time) if end_time > session.start_time => Ok((end_time - session.start_time).num_seconds()),
+        _ => Err(TimeboxError::Validation("A session must end after it starts".to_string())),
+    }
+}
+
+// Sessions of a user never overlap; `replaced_id` is the session the new times are for
+async fn ensure_no_overlap(tx: &mut Transaction<'_, Sqlite>, session: &PomodoroSession, replaced_id: &str) -> Result<(), TimeboxError> {
+    let overlapping: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT id
+        FROM pomodoro_sessions
+        WHERE user_id = ? AND id != ? AND start_time < ? AND (end_time IS NULL OR end_time > ?)
+        LIMIT 1
+        "#
+    )
+    .bind(&session.user_id)
+    .bind(replaced_id)
+    .bind(session.end_time)
+    .bind(session.start_time)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    match overlapping {
+        Some(id) => Err(TimeboxError::Conflict(format!("The session would overlap session {}", id))),
+        None => Ok(()),
+    }
+}
+
+async fn insert_session_row(tx: &mut Transaction<'_, Sqlite>, session: &PomodoroSession) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO pomodoro_sessions (id, user_id, task_id, session_type, start_time, end_time, duration_seconds, interrupted, interruption_count, pause_count, paused_seconds, manual_override, stop_reason, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#
+    )
+    .bind(&session.id)
+    .bind(&session.user_id)
+    .bind(&session.task_id)
+    .bind(session.session_type.to_string())
+    .bind(session.start_time)
+    .bind(session.end_time)
+    .bind(session.duration_seconds)
+    .bind(session.interrupted)
+    .bind(session.interruption_count)
+    .bind(session.pause_count)
+    .bind(session.paused_seconds)
+    .bind(session.manual_override)
+    .bind(&session.stop_reason)
+    .bind(session.created_at)
+    .execute(&mut **tx)
+    .await?;
+
+    replace_tag_links(tx, "session_tags", "session_id", &session.id, &session.user_id, &session.tags).await
+}
+
+fn validate_project_name(name: &str) -> Result<&str, TimeboxError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(TimeboxError::Validation("Project name cannot be empty".to_string()));
+    }
+    Ok(name)
+}
+
+fn validate_hourly_rate(hourly_rate: Option<f64>) -> Result<(), TimeboxError> {
+    match hourly_rate {
+        Some(rate) if !rate.is_finite() || rate < 0.0 => {
+            Err(TimeboxError::Validation("Hourly rate must be zero or more".to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_checklist_title(title: &str) -> Result<&str, TimeboxError> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(TimeboxError::Validation("Checklist item title cannot be empty".to_string()));
+    }
+    Ok(title)
+}
+
+async fn write_active_timer(tx: &mut Transaction<'_, Sqlite>, timer: &ActiveTimerState) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO active_timer (id, session_id, remaining_seconds, total_seconds, is_paused, anchor_time, updated_at)
+        VALUES (1, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(id)
+        DO UPDATE SET
+            session_id = excluded.session_id,
+            remaining_seconds = excluded.remaining_seconds,
+            total_seconds = excluded.total_seconds,
+            is_paused = excluded.is_paused,
+            anchor_time = excluded.anchor_time,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&timer.session_id)
+    .bind(timer.remaining_seconds)
+    .bind(timer.total_seconds)
+    .bind(timer.is_paused)
+    .bind(timer.anchor_time)
+    .bind(timer.updated_at)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+// Open subtasks are stamped with the completion time of `task_id`
+async fn complete_subtasks(tx: &mut Transaction<'_, Sqlite>, task_id: &str) -> Result<(), sqlx::Error> {
+    let query = format!(
+        "{} UPDATE tasks SET completed = 1, completed_at = (SELECT completed_at FROM tasks WHERE id = ?) WHERE id IN subtree AND completed = 0",
+        TASK_SUBTREE
+    );
+    sqlx::query(&query)
+        .bind(task_id)
+        .bind(task_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+// Creates the instance of a routine that follows `task`, due on the rule's next occurrence from today on.
+// Does nothing when the task does not repeat, is already followed, the rule has run out, or it sits under a completed task.
+async fn create_next_instance(tx: &mut Transaction<'_, Sqlite>, task: &Task, today: NaiveDate) -> Result<Option<Task>, TimeboxError> {
+    // A rule that no longer parses ends the routine rather than failing the update
+    let Some(recurrence) = task.recurrence.as_deref().and_then(|rule| Recurrence::parse(rule).ok()) else {
+        return Ok(None);
+    };
+    let followed = sqlx::query("SELECT 1 FROM tasks WHERE recurs_from = ?")
+        .bind(&task.id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .is_some();
+    if followed {
+        return Ok(None);
+    }
+    if let Some(parent_task_id) = &task.parent_task_id {
+        let parent_completed: Option<bool> = sqlx::query_scalar("SELECT completed FROM tasks WHERE id = ?")
+            .bind(parent_task_id)
+            .fetch_optional(&mut **tx)
+            .await?;
+        if parent_completed == Some(true) {
+            return Ok(None);
+        }
+    }
+
+    let due = task.due_date.unwrap_or(today);
+    let from = if task.completed { (due + chrono::Duration::days(1)).max(today) } else { today };
+    let Some(next_due) = recurrence.first_on_or_after(due, from) else {
+        return Ok(None);
+    };
+
+    let position: i32 = sqlx::query_scalar("SELECT COALESCE(MIN(position) - 1, 0) FROM tasks WHERE user_id = ?")
+        .bind(&task.user_id)
+        .fetch_one(&mut **tx)
+        .await?;
+    let next = Task {
+        id: Uuid::new_v4().to_string(),
+        completed: false,
+        completed_at: None,
+        due_date: Some(next_due),
+        position,
+        // Stays in the daily plan, on the day it is now due
+        planned_for: task.planned_for.map(|_| next_due),
+        recurs_from: Some(task.id.clone()),
+        created_at: Utc::now(),
+        ..task.clone()
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO tasks (id, user_id, title, estimated_pomodoros, goal_id, project_id, parent_task_id, due_date, priority, position, planned_for, recurrence, recurs_from, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&next.id)
+    .bind(&next.user_id)
+    .bind(&next.title)
+    .bind(next.estimated_pomodoros)
+    .bind(&next.goal_id)
+    .bind(&next.project_id)
+    .bind(&next.parent_task_id)
+    .bind(next.due_date)
+    .bind(next.priority.as_db())
+    .bind(next.position)
+    .bind(next.planned_for)
+    .bind(&next.recurrence)
+    .bind(&next.recurs_from)
+    .bind(next.created_at)
+    .execute(&mut **tx)
+    .await?;
+    replace_tag_links(tx, "task_tags", "task_id", &next.id, &next.user_id, &next.tags).await?;
+
+    // The checklist starts over, all items open
+    let items: Vec<(String, i32)> = sqlx::query_as("SELECT title, position FROM checklist_items WHERE task_id = ? ORDER BY position")
+        .bind(&task.id)
+        .fetch_all(&mut **tx)
+        .await?;
+    for (title, item_position) in items {
+        sqlx::query("INSERT INTO checklist_items (id, task_id, title, position) VALUES (?, ?, ?, ?)")
+            .bind(Uuid::new_v4().to_string())
+            .bind(&next.id)
+            .bind(title)
+            .bind(item_position)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(Some(next))
+}
+
+// Reopens every completed task above `task_id`, so no completed task has open subtasks
+async fn reopen_parent_tasks(tx: &mut Transaction<'_, Sqlite>, task_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        WITH RECURSIVE ancestors(id) AS (
+            SELECT parent_task_id FROM tasks WHERE id = ?
+            UNION SELECT t.parent_task_id FROM tasks t JOIN ancestors a ON t.id = a.id
+        )
+        UPDATE tasks SET completed = 0, completed_at = NULL WHERE id IN (SELECT id FROM ancestors) AND completed = 1
+        "#,
+    )
+    .bind(task_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+// Trims tag names and drops repeats that only differ in case, matching how the tags table compares names
+fn normalize_tags(names: &[String]) -> Result<Vec<String>, TimeboxError> {
+    let mut tags: Vec<String> = Vec::new();
+    for name in names {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(TimeboxError::Validation("Tag names cannot be empty".to_string()));
+        }
+        if !tags.iter().any(|tag| tag.eq_ignore_ascii_case(name)) {
+            tags.push(name.to_string());
+        }
+    }
+    tags.sort_by_key(|tag| tag.to_lowercase());
+    Ok(tags)
+}
+
+// Links a task or session to exactly the tags named, creating the ones the user doesn't have yet.
+// `link_table` is task_tags or session_tags, and `owner_column` its task or session column.
+async fn replace_tag_links(
+    tx: &mut Transaction<'_, Sqlite>,
+    link_table: &str,
+    owner_column: &str,
+    owner_id: &str,
+    user_id: &str,
+    names: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query(&format!("DELETE FROM {} WHERE {} = ?", link_table, owner_column))
+        .bind(owner_id)
+        .execute(&mut **tx)
+        .await?;
+
+    for name in names {
+        sqlx::query("INSERT OR IGNORE INTO tags (id, user_id, name) VALUES (?, ?, ?)")
+            .bind(Uuid::new_v4().to_string())
+            .bind(user_id)
+            .bind(name)
+            .execute(&mut **tx)
+            .await?;
+        sqlx::query(&format!(
+            "INSERT OR IGNORE INTO {} ({}, tag_id) SELECT ?, id FROM tags WHERE user_id = ? AND name = ?",
+            link_table, owner_column
+        ))
+        .bind(owner_id)
+        .bind(user_id)
+        .bind(name)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn insert_session_audit(
+    tx: &mut Transaction<'_, Sqlite>,
+    session_id: &str,
+    user_id: &str,
+    action: SessionAuditAction,
+    before: &[&PomodoroSession],
+    after: &[&PomodoroSession],
+) -> Result<(), TimeboxError> {
+    let to_json = |sessions: &[&PomodoroSession]| serde_json::to_string(sessions).map_err(std::io::Error::from);
+
+    sqlx::query(
+        r#"
+        INSERT INTO session_audit (id, session_id, user_id, action, before_data, after_data, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(session_id)
+    .bind(user_id)
+    .bind(action.as_str())
+    .bind(to_json(before)?)
+    .bind(to_json(after)?)
+    .bind(Utc::now())
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+// Two v4 UUIDs give 244 random bits from the OS generator
+fn generate_api_token() -> String {
+    format!("tbx_{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple())
+}
+
+fn user_from_row(row: &SqliteRow) -> User {
+    User {
+        id: row.get("id"),
+        name: row.get("name"),
+        timezone: row.get("timezone"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn settings_from_row(row: &SqliteRow) -> PomodoroSettings {
+    PomodoroSettings {
+        user_id: row.get("user_id"),
+        focus_minutes: row.get("focus_minutes"),
+        short_break_minutes: row.get("short_break_minutes"),
+        long_break_minutes: row.get("long_break_minutes"),
+        cycles_before_long_break: row.get("cycles_before_long_break"),
+        strict_mode: row.get::<i32, &str>("strict_mode") != 0,
+        auto_start_breaks: row.get::<i32, &str>("auto_start_breaks") != 0,
+        sound_enabled: row.get::<i32, &str>("sound_enabled") != 0,
+        sound_volume: row.get("sound_volume"),
+        daily_target_unit: DailyTargetUnit::from_db_str(&row.get::<Option<String>, &str>("daily_target_unit").unwrap_or_default()),
+        daily_target: row.get::<Option<i32>, &str>("daily_target").unwrap_or(0),
+        rest_days: rest_days_from_db(&row.get::<Option<String>, &str>("rest_days").unwrap_or_default()),
+    }
+}
+
+fn task_from_row(row: &SqliteRow) -> Task {
+    Task {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        title: row.get("title"),
+        estimated_pomodoros: row.get("estimated_pomodoros"),
+        completed: row.get::<i32, &str>("completed") != 0,
+        completed_at: row.get("completed_at"),
+        goal_id: row.get("goal_id"),
+        project_id: row.get("project_id"),
+        tags: tags_from_json(row.get("tags")),
+        parent_task_id: row.get("parent_task_id"),
+        due_date: row.get("due_date"),
+        priority: TaskPriority::from_db(row.get("priority")),
+        position: row.get("position"),
+        planned_for: row.get("planned_for"),
+        recurrence: row.get("recurrence"),
+        recurs_from: row.get("recurs_from"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn checklist_item_from_row(row: &SqliteRow) -> ChecklistItem {
+    ChecklistItem {
+        id: row.get("id"),
+        task_id: row.get("task_id"),
+        title: row.get("title"),
+        completed: row.get::<i32, &str>("completed") != 0,
+        position: row.get("position"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn project_from_row(row: &SqliteRow) -> Project {
+    Project {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        name: row.get("name"),
+        color: row.get("color"),
+        archived: row.get::<i32, &str>("archived") != 0,
+        hourly_rate: row.get("hourly_rate"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn tag_from_row(row: &SqliteRow) -> Tag {
+    Tag {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        name: row.get("name"),
+        created_at: row.get("created_at"),
+    }
+}
+
+// Tag names as selected with json_group_array
+fn tags_from_json(value: Option<String>) -> Vec<String> {
+    let mut tags: Vec<String> = value
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default();
+    tags.sort_by_key(|tag| tag.to_lowercase());
+    tags
+}
+
+fn goal_from_row(row: &SqliteRow) -> Goal {
+    Goal {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        title: row.get("title"),
+        target_pomodoros: row.get("target_pomodoros"),
+        completed_pomodoros: row.get("completed_pomodoros"),
+        completed: row.get::<i32, &str>("completed") != 0,
+        category: row.get("category"),
+        motivation: row.get("motivation"),
+        target_date: row.get("target_date"),
+        description: row.get("description"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn reflection_from_row(row: &SqliteRow) -> DailyReflection {
+    DailyReflection {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        reflection_date: row.get("reflection_date"),
+        title: row.get("title"),
+        duration_reflection: row.get("duration_reflection"),
+        purpose_reflection: row.get("purpose_reflection"),
+        general_notes: row.get("general_notes"),
+        mood_rating: row.get("mood_rating"),
+        productivity_rating: row.get("productivity_rating"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn focus_totals_from_row(row: &SqliteRow) -> FocusTotals {
+    FocusTotals {
+        focus_seconds: row.get("focus_seconds"),
+        sessions: row.get("sessions"),
+        completed_sessions: row.get("completed_sessions"),
+        interrupted_sessions: row.get("interrupted_sessions"),
+        interruptions: row.get("interruptions"),
+        pauses: row.get("pauses"),
+        paused_seconds: row.get("paused_seconds"),
+        wall_clock_seconds: row.get("wall_clock_seconds"),
+        average_session_seconds: row.get("average_session_seconds"),
+    }
+}
+
+fn audit_from_row(row: &SqliteRow) -> SessionAuditEntry {
+    let action: String = row.get("action");
+    // Rows are only ever written from serialized sessions
+    let sessions = |column: &str| serde_json::from_str(&row.get::<String, &str>(column)).unwrap_or_default();
+
+    SessionAuditEntry {
+        id: row.get("id"),
+        session_id: row.get("session_id"),
+        user_id: row.get("user_id"),
+        action: SessionAuditAction::from_db_str(&action),
+        before: sessions("before_data"),
+        after: sessions("after_data"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn interruption_from_row(row: &SqliteRow) -> Interruption {
+    let kind: String = row.get("kind");
+
+    Interruption {
+        id: row.get("id"),
+        session_id: row.get("session_id"),
+        user_id: row.get("user_id"),
+        occurred_at: row.get("occurred_at"),
+        kind: InterruptionKind::from_db_str(&kind),
+        reason: row.get("reason"),
+        category: row.get("category"),
+        task_title: row.get("task_title"),
+    }
+}
+
+fn pause_from_row(row: &SqliteRow) -> SessionPause {
+    SessionPause {
+        id: row.get("id"),
+        session_id: row.get("session_id"),
+        paused_at: row.get("paused_at"),
+        resumed_at: row.get("resumed_at"),
+        reason: row.get("reason"),
+    }
+}
+
+fn session_from_row(row: &SqliteRow) -> PomodoroSession {
+    let session_type_str: String = row.get("session_type");
+
+    PomodoroSession {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        task_id: row.get("task_id"),
+        session_type: SessionType::from_db_str(&session_type_str),
+        start_time: row.get("start_time"),
+        end_time: row.get("end_time"),
+        duration_seconds: row.get("duration_seconds"),
+        interrupted: row.get::<i32, &str>("interrupted") != 0,
+        interruption_count: row.get("interruption_count"),
+        pause_count: row.get("pause_count"),
+        paused_seconds: row.get("paused_seconds"),
+        manual_override: row.get::<i32, &str>("manual_override") != 0,
+        stop_reason: row.get("stop_reason"),
+        created_at: row.get("created_at"),
+        task_title: row.get("task_title"),
+        tags: tags_from_json(row.get("tags")),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DayActivities {
+    pub pomodoro_sessions: Vec<PomodoroSession>,
+    pub completed_tasks: Vec<Task>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DataSnapshot {
+    pub users: Vec<User>,
+    pub settings: Vec<PomodoroSettings>,
+    pub tasks: Vec<Task>,
+    pub sessions: Vec<PomodoroSession>,
+    // Missing from archives written before pauses were recorded
+    #[serde(default)]
+    pub session_pauses: Vec<SessionPause>,
+    #[serde(default)]
+    pub interruptions: Vec<Interruption>,
+    #[serde(default)]
+    pub session_audit: Vec<SessionAuditEntry>,
+    pub goals: Vec<Goal>,
+    pub reflections: Vec<DailyReflection>,
+    #[serde(default)]
+    pub projects: Vec<Project>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub checklist_items: Vec<ChecklistItem>,
+}
+
+// A session logged by hand rather than timed
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ManualSession {
+    pub task_id: Option<String>,
+    // Defaults to FOCUS
+    pub session_type: Option<SessionType>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    // Defaults to the time between start and end
+    pub duration_seconds: Option<i32>,
+    // Log only the parts of the range no other session covers
+    #[serde(default)]
+    pub fill_gaps: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+// Fields of a task to change; omitted ones keep their current value
+#[derive(Serialize, Deserialize, Default)]
+pub struct TaskChanges {
+    pub title: Option<String>,
+    pub estimated_pomodoros: Option<i32>,
+    pub completed: Option<bool>,
+    pub due_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub clear_due_date: bool,
+    pub priority: Option<TaskPriority>,
+    pub planned_for: Option<NaiveDate>,
+    // Takes the task out of its daily plan
+    #[serde(default)]
+    pub clear_planned_for: bool,
+    // Repeat rule, see `Recurrence::parse`
+    pub recurrence: Option<String>,
+    // Stops the routine; instances already created stay
+    #[serde(default)]
+    pub clear_recurrence: bool,
+}
+
+// A day a routine comes up on; `task_id` is set when its instance for that day exists
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UpcomingOccurrence {
+    pub date: NaiveDate,
+    pub task_id: Option<String>,
+    pub title: String,
+    pub recurrence: String,
+}
+
+// Tasks queued for one day, measured against how many Pomodoros fit into it
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DailyPlan {
+    pub date: String,
+    // In queue order; completed tasks stay listed
+    pub tasks: Vec<Task>,
+    // Estimates of the open tasks
+    pub planned_pomodoros: i64,
+    pub available_minutes: i32,
+    // Focus sessions that fit into the available minutes with the user's session and break lengths
+    pub capacity_pomodoros: i64,
+    pub overcommitted: bool,
+    pub warning: Option<String>,
+}
+
+// Focus counts of a task, with totals over the task and its subtasks at any depth
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TaskCounts {
+    // Focus sessions on the task itself
+    pub actual_pomodoros: i64,
+    pub total_estimated_pomodoros: i64,
+    pub total_pomodoros: i64,
+    pub subtasks: i64,
+    pub completed_subtasks: i64,
+    pub checklist_items: i64,
+    pub completed_checklist_items: i64,
+}
+
+// Fields of a checklist item to change; omitted ones keep their current value
+#[derive(Serialize, Deserialize, Default)]
+pub struct ChecklistItemChanges {
+    pub title: Option<String>,
+    pub completed: Option<bool>,
+}
+
+// Fields of a project to change; omitted ones keep their current value
+#[derive(Serialize, Deserialize, Default)]
+pub struct ProjectChanges {
+    pub name: Option<String>,
+    pub color: Option<String>,
+    #[serde(default)]
+    pub clear_color: bool,
+    pub archived: Option<bool>,
+    pub hourly_rate: Option<f64>,
+    #[serde(default)]
+    pub clear_hourly_rate: bool,
+}
+
+// Narrows tasks, sessions or focus stats down to a project and/or a tag; empty fields match everything
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ProjectTagFilter {
+    pub project_id: Option<String>,
+    // Tag name, compared regardless of case
+    pub tag: Option<String>,
+}
+
+impl ProjectTagFilter {
+    // Binds the project id and the tag twice each, once for every `? IS NULL OR` check
+    fn bind<'q>(&'q self, query: Query<'q, Sqlite, SqliteArguments<'q>>) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        let tag = self.tag.as_deref().map(str::trim);
+        query
+            .bind(self.project_id.as_deref())
+            .bind(self.project_id.as_deref())
+            .bind(tag)
+            .bind(tag)
+    }
+}
+
+// Fields of a session to change; omitted ones keep their current value
+#[derive(Serialize, Deserialize, Default)]
+pub struct SessionChanges {
+    pub task_id: Option<String>,
+    // Detaches the session from its task; takes precedence over task_id
+    #[serde(default)]
+    pub clear_task: bool,
+    pub session_type: Option<SessionType>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub duration_seconds: Option<i32>,
+    // Replaces the session's own tags
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionAuditAction {
+    Edit,
+    Delete,
+    Split,
+    Merge,
+}
+
+impl SessionAuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionAuditAction::Edit => "edit",
+            SessionAuditAction::Delete => "delete",
+            SessionAuditAction::Split => "split",
+            SessionAuditAction::Merge => "merge",
+        }
+    }
+
+    pub fn from_db_str(value: &str) -> SessionAuditAction {
+        match value {
+            "delete" => SessionAuditAction::Delete,
+            "split" => SessionAuditAction::Split,
+            "merge" => SessionAuditAction::Merge,
+            _ => SessionAuditAction::Edit,
+        }
+    }
+}
+
+// One change to the session history, with the affected sessions as they were before and after it
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionAuditEntry {
+    pub id: String,
+    pub session_id: String,
+    pub user_id: String,
+    pub action: SessionAuditAction,
+    pub before: Vec<PomodoroSession>,
+    pub after: Vec<PomodoroSession>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InterruptionSource {
+    pub category: Option<String>,
+    pub interruptions: i64,
+    pub internal: i64,
+    pub external: i64,
+    pub last_occurred_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GoalDayProgress {
+    pub date: String,
+    pub completed_pomodoros: i32,
+    pub focus_seconds: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GoalProgress {
+    pub goal: Goal,
+    pub days: Vec<GoalDayProgress>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    Day,
+    // Weeks start on Monday
+    Week,
+    Month,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FocusTotals {
+    pub focus_seconds: i64,
+    pub sessions: i64,
+    pub completed_sessions: i64,
+    pub interrupted_sessions: i64,
+    pub interruptions: i64,
+    pub pauses: i64,
+    pub paused_seconds: i64,
+    // Focus plus paused time, i.e. how long the sessions took on the clock
+    pub wall_clock_seconds: i64,
+    pub average_session_seconds: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FocusPeriodStats {
+    // Local date the day, week or month starts on, as YYYY-MM-DD
+    pub period_start: String,
+    #[serde(flatten)]
+    pub totals: FocusTotals,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HeatmapBucket {
+    // 0 = Monday ... 6 = Sunday, in the user's timezone
+    pub weekday: i32,
+    pub hour: i32,
+    pub focus_seconds: i64,
+    pub sessions: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TaskFocusStats {
+    // None collects focus sessions without a task
+    pub task_id: Option<String>,
+    pub title: Option<String>,
+    pub focus_seconds: i64,
+    pub sessions: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GoalFocusStats {
+    pub goal_id: String,
+    pub title: String,
+    pub focus_seconds: i64,
+    pub sessions: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProjectFocusStats {
+    // None collects focus sessions outside of any project
+    pub project_id: Option<String>,
+    pub name: Option<String>,
+    pub color: Option<String>,
+    pub focus_seconds: i64,
+    pub sessions: i64,
+    pub hourly_rate: Option<f64>,
+    // Focus time at the project's hourly rate, rounded to cents
+    pub amount: Option<f64>,
+}
+
+impl ProjectFocusStats {
+    fn new(
+        project_id: Option<String>,
+        name: Option<String>,
+        color: Option<String>,
+        hourly_rate: Option<f64>,
+        focus_seconds: i64,
+        sessions: i64,
+    ) -> Self {
+        let amount = hourly_rate.map(|rate| (rate * focus_seconds as f64 / 36.0).round() / 100.0);
+
+        ProjectFocusStats {
+            project_id,
+            name,
+            color,
+            focus_seconds,
+            sessions,
+            hourly_rate,
+            amount,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FocusStats {
+    pub totals: FocusTotals,
+    pub periods: Vec<FocusPeriodStats>,
+    // Only buckets with focus time are listed
+    pub heatmap: Vec<HeatmapBucket>,
+    pub tasks: Vec<TaskFocusStats>,
+    pub goals: Vec<GoalFocusStats>,
+    pub projects: Vec<ProjectFocusStats>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DailyTargetProgress {
+    pub date: String,
+    pub focus_seconds: i64,
+    pub pomodoros: i64,
+    pub target_unit: DailyTargetUnit,
+    pub target: i32,
+    // Focus so far in the target's unit
+    pub progress: i64,
+    pub target_met: bool,
+    pub rest_day: bool,
+}
+
+impl DailyTargetProgress {
+    fn new(settings: &PomodoroSettings, day: NaiveDate, focus_seconds: i64, pomodoros: i64) -> Self {
+        let progress = match settings.daily_target_unit {
+            DailyTargetUnit::Minutes => focus_seconds / 60,
+            DailyTargetUnit::Pomodoros => pomodoros,
+        };
+
+        DailyTargetProgress {
+            date: day.format("%Y-%m-%d").to_string(),
+            focus_seconds,
+            pomodoros,
+            target_unit: settings.daily_target_unit,
+            target: settings.daily_target,
+            progress,
+            target_met: settings.daily_target > 0 && progress >= settings.daily_target as i64,
+            rest_day: settings.rest_days.contains(&(day.weekday().num_days_from_monday() as u8)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StreakSummary {
+    pub today: DailyTargetProgress,
+    pub current_streak: i32,
+    pub longest_streak: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 10, hour, minute, 0).unwrap()
+    }
+
+    async fn log_session(db: &Database, start_time: DateTime<Utc>, end_time: DateTime<Utc>, duration_seconds: i32) -> PomodoroSession {
+        let entry = ManualSession {
+            task_id: None,
+            session_type: None,
+            start_time,
+            end_time,
+            duration_seconds: Some(duration_seconds),
+            fill_gaps: false,
+            tags: Vec::new(),
+        };
+        db.create_manual_session("default_user", entry).await.unwrap().remove(0)
+    }
+
+    #[tokio::test]
+    async fn split_parts_add_up_to_the_original() {
+        let db = Database::new("sqlite::memory:").await.unwrap();
+        let session = log_session(&db, at(9, 0), at(10, 0), 2999).await;
+
+        let (first, second) = db.split_session(&session.id, at(9, 17)).await.unwrap();
+        assert_eq!((first.start_time, first.end_time), (at(9, 0), Some(at(9, 17))));
+        assert_eq!((second.start_time, second.end_time), (at(9, 17), Some(at(10, 0))));
+        assert_eq!(first.duration_seconds.unwrap() + second.duration_seconds.unwrap(), 2999);
+        assert_eq!(first.duration_seconds, Some(850));
+
+        for split_at in [at(9, 0), at(10, 0), at(10, 30)] {
+            assert!(matches!(db.split_session(&first.id, split_at).await, Err(TimeboxError::Validation(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn split_moves_later_pauses_to_the_second_part() {
+        let db = Database::new("sqlite::memory:").await.unwrap();
+        let session = log_session(&db, at(9, 0), at(10, 0), 3000).await;
+        for (paused_at, resumed_at) in [(at(9, 5), at(9, 7)), (at(9, 40), at(9, 45))] {
+            sqlx::query("INSERT INTO session_pauses (id, session_id, paused_at, resumed_at) VALUES (?, ?, ?, ?)")
+                .bind(Uuid::new_v4().to_string())
+                .bind(&session.id)
+                .bind(paused_at)
+                .bind(resumed_at)
+                .execute(&db.pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("UPDATE pomodoro_sessions SET pause_count = 2, paused_seconds = 420 WHERE id = ?")
+            .bind(&session.id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let (first, second) = db.split_session(&session.id, at(9, 30)).await.unwrap();
+        assert_eq!((first.pause_count, first.paused_seconds), (1, 120));
+        assert_eq!((second.pause_count, second.paused_seconds), (1, 300));
+
+        let first_pauses = db.get_session_pauses(&first.id).await.unwrap();
+        let second_pauses = db.get_session_pauses(&second.id).await.unwrap();
+        assert_eq!(first_pauses.iter().map(|pause| pause.paused_at).collect::<Vec<_>>(), [at(9, 5)]);
+        assert_eq!(second_pauses.iter().map(|pause| pause.paused_at).collect::<Vec<_>>(), [at(9, 40)]);
+    }
+
+    #[tokio::test]
+    async fn merge_requires_adjacent_sessions() {
+        let db = Database::new("sqlite::memory:").await.unwrap();
+        let first = log_session(&db, at(9, 0), at(9, 25), 1500).await;
+        let second = log_session(&db, at(9, 30), at(9, 55), 1500).await;
+        let third = log_session(&db, at(10, 0), at(10, 25), 1500).await;
+
+        assert!(matches!(db.merge_sessions(&first.id, &third.id).await, Err(TimeboxError::Validation(_))));
+        assert!(matches!(db.merge_sessions(&third.id, &first.id).await, Err(TimeboxError::Validation(_))));
+
+        let merged = db.merge_sessions(&second.id, &first.id).await.unwrap();
+        assert_eq!(merged.id, first.id);
+        assert_eq!((merged.start_time, merged.end_time), (at(9, 0), Some(at(9, 55))));
+        assert_eq!(merged.duration_seconds, Some(3000));
+        assert!(db.get_session(&second.id).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn untracked_gaps_skip_tracked_and_short_parts() {
+        // A tracked session straddling the start of the range, another running past its end
+        let tracked = [(at(8, 50), at(9, 10)), (at(9, 40), at(10, 30))];
+        assert_eq!(untracked_gaps(at(9, 0), at(10, 0), tracked.into_iter()), [(at(9, 10), at(9, 40))]);
+
+        // A gap of exactly MIN_MANUAL_GAP is kept, a shorter one isn't
+        let half_minute = chrono::Duration::seconds(30);
+        let tracked = [(at(9, 0), at(9, 20)), (at(9, 21), at(9, 40)), (at(9, 40) + half_minute, at(10, 0))];
+        assert_eq!(untracked_gaps(at(9, 0), at(10, 0), tracked.into_iter()), [(at(9, 20), at(9, 21))]);
+
+        assert_eq!(untracked_gaps(at(9, 0), at(10, 0), std::iter::empty()), [(at(9, 0), at(10, 0))]);
+        assert!(untracked_gaps(at(9, 0), at(10, 0), [(at(8, 0), at(11, 0))].into_iter()).is_empty());
+    }
+
+    #[test]
+    fn share_duration_leaves_out_empty_parts() {
+        let parts = [(at(9, 0), at(9, 1)), (at(9, 30), at(10, 0))];
+        assert_eq!(share_duration(1200, 3600, &parts), [(at(9, 0), at(9, 1), 20), (at(9, 30), at(10, 0), 600)]);
+        // One minute's share of a single second rounds to nothing
+        assert_eq!(share_duration(1, 3600, &parts), [(at(9, 30), at(10, 0), 1)]);
+        assert!(share_duration(1, 3600, &parts[..1]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn filling_gaps_treats_the_running_session_as_tracked_until_now() {
+        let db = Database::new("sqlite::memory:").await.unwrap();
+        let now = Utc::now();
+        sqlx::query("INSERT INTO pomodoro_sessions (id, user_id, session_type, start_time) VALUES ('running', 'default_user', 'FOCUS', ?)")
+            .bind(now - chrono::Duration::minutes(30))
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let mut entry = ManualSession {
+            task_id: None,
+            session_type: None,
+            start_time: now - chrono::Duration::hours(2),
+            end_time: now - chrono::Duration::minutes(10),
+            duration_seconds: None,
+            fill_gaps: true,
+            tags: Vec::new(),
+        };
+        let sessions = db.create_manual_session("default_user", entry.clone()).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].end_time.map(|end| end.timestamp()), Some((now - chrono::Duration::minutes(30)).timestamp()));
+
+        // Nothing is left between the logged part and the running session
+        assert!(matches!(db.create_manual_session("default_user", entry.clone()).await, Err(TimeboxError::Conflict(_))));
+
+        entry.start_time = now - chrono::Duration::hours(3);
+        entry.duration_seconds = Some(1);
+        assert!(matches!(db.create_manual_session("default_user", entry).await, Err(TimeboxError::Validation(_))));
+    }
+}
@@ -1,10 +1,19 @@
 -- TimeBox Pomodoro System Database Schema
+-- Reference only: the schema is created and upgraded by the numbered migrations in migrations.rs
+
+-- Applied migrations
+CREATE TABLE IF NOT EXISTS schema_version (
+    version INTEGER PRIMARY KEY,
+    description TEXT NOT NULL,
+    applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
 
 -- Users table
 CREATE TABLE IF NOT EXISTS users (
     id TEXT PRIMARY KEY,
     name TEXT NOT NULL,
     timezone TEXT DEFAULT 'UTC',
+    timezone_source TEXT DEFAULT 'default', -- 'default', 'detected' or 'user'
     created_at DATETIME DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -19,9 +28,25 @@ CREATE TABLE IF NOT EXISTS pomodoro_settings (
     auto_start_breaks BOOLEAN DEFAULT 0,
     sound_enabled BOOLEAN DEFAULT 1,
     sound_volume INTEGER DEFAULT 70,
+    daily_target_unit TEXT DEFAULT 'pomodoros', -- 'pomodoros' or 'minutes'
+    daily_target INTEGER DEFAULT 0,
+    rest_days TEXT DEFAULT '', -- comma-separated weekdays, 0 = Monday
     FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
 );
 
+-- Projects table
+CREATE TABLE IF NOT EXISTS projects (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL COLLATE NOCASE,
+    color TEXT,
+    archived BOOLEAN DEFAULT 0,
+    hourly_rate REAL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE(user_id, name)
+);
+
 -- Tasks table
 CREATE TABLE IF NOT EXISTS tasks (
     id TEXT PRIMARY KEY,
@@ -29,10 +54,44 @@ CREATE TABLE IF NOT EXISTS tasks (
     title TEXT NOT NULL,
     estimated_pomodoros INTEGER DEFAULT 1,
     completed BOOLEAN DEFAULT 0,
+    completed_at DATETIME,
+    goal_id TEXT,
+    project_id TEXT,
+    parent_task_id TEXT, -- subtasks are deleted with their parent
+    due_date TEXT, -- local YYYY-MM-DD
+    priority INTEGER NOT NULL DEFAULT 0, -- 0 = none, 1 = low, 2 = medium, 3 = high
+    position INTEGER NOT NULL DEFAULT 0,
+    planned_for TEXT, -- local YYYY-MM-DD
+    recurrence TEXT, -- normalized RRULE
+    recurs_from TEXT, -- previous instance of the routine
     created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (goal_id) REFERENCES goals(id) ON DELETE SET NULL,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE SET NULL,
+    FOREIGN KEY (parent_task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (recurs_from) REFERENCES tasks(id) ON DELETE SET NULL
 );
 
+CREATE INDEX IF NOT EXISTS idx_tasks_goal ON tasks(goal_id);
+CREATE INDEX IF NOT EXISTS idx_tasks_project ON tasks(project_id);
+CREATE INDEX IF NOT EXISTS idx_tasks_parent ON tasks(parent_task_id);
+CREATE INDEX IF NOT EXISTS idx_tasks_planned ON tasks(user_id, planned_for);
+CREATE INDEX IF NOT EXISTS idx_tasks_completed_at ON tasks(user_id, completed_at);
+CREATE INDEX IF NOT EXISTS idx_tasks_recurs_from ON tasks(recurs_from);
+
+-- Checklist items of a task, in order
+CREATE TABLE IF NOT EXISTS checklist_items (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    completed BOOLEAN DEFAULT 0,
+    position INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_checklist_items_task ON checklist_items(task_id, position);
+
 -- Pomodoro Sessions table
 CREATE TABLE IF NOT EXISTS pomodoro_sessions (
     id TEXT PRIMARY KEY,
@@ -44,12 +103,89 @@ CREATE TABLE IF NOT EXISTS pomodoro_sessions (
     duration_seconds INTEGER,
     interrupted BOOLEAN DEFAULT 0,
     interruption_count INTEGER DEFAULT 0,
+    pause_count INTEGER DEFAULT 0,
+    paused_seconds INTEGER DEFAULT 0,
     manual_override BOOLEAN DEFAULT 0,
+    stop_reason TEXT,
     created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
     FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
     FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE SET NULL
 );
 
+CREATE INDEX IF NOT EXISTS idx_sessions_user_start ON pomodoro_sessions(user_id, start_time);
+CREATE INDEX IF NOT EXISTS idx_sessions_task ON pomodoro_sessions(task_id);
+
+-- Pause intervals of a session; resumed_at is NULL while paused
+CREATE TABLE IF NOT EXISTS session_pauses (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    paused_at DATETIME NOT NULL,
+    resumed_at DATETIME,
+    reason TEXT,
+    FOREIGN KEY (session_id) REFERENCES pomodoro_sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_pauses_session ON session_pauses(session_id);
+
+-- Interruptions logged during a session
+CREATE TABLE IF NOT EXISTS session_interruptions (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    occurred_at DATETIME NOT NULL,
+    kind TEXT CHECK(kind IN ('internal', 'external')) NOT NULL,
+    reason TEXT,
+    category TEXT,
+    FOREIGN KEY (session_id) REFERENCES pomodoro_sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_interruptions_user_time ON session_interruptions(user_id, occurred_at);
+CREATE INDEX IF NOT EXISTS idx_interruptions_session ON session_interruptions(session_id);
+
+-- Edits to session history; no foreign key on session_id, so entries outlive the sessions they describe
+CREATE TABLE IF NOT EXISTS session_audit (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    action TEXT CHECK(action IN ('edit', 'delete', 'split', 'merge')) NOT NULL,
+    before_data TEXT NOT NULL,
+    after_data TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_audit_user ON session_audit(user_id, created_at);
+
+-- Tags, attached to tasks and to individual sessions
+CREATE TABLE IF NOT EXISTS tags (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL COLLATE NOCASE,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE(user_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS task_tags (
+    task_id TEXT NOT NULL,
+    tag_id TEXT NOT NULL,
+    PRIMARY KEY (task_id, tag_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS session_tags (
+    session_id TEXT NOT NULL,
+    tag_id TEXT NOT NULL,
+    PRIMARY KEY (session_id, tag_id),
+    FOREIGN KEY (session_id) REFERENCES pomodoro_sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_tags_tag ON task_tags(tag_id);
+CREATE INDEX IF NOT EXISTS idx_session_tags_tag ON session_tags(tag_id);
+
 -- Goals table (for long-term objectives)
 CREATE TABLE IF NOT EXISTS goals (
     id TEXT PRIMARY KEY,
@@ -58,7 +194,7 @@ CREATE TABLE IF NOT EXISTS goals (
     target_pomodoros INTEGER NOT NULL,
     completed_pomodoros INTEGER DEFAULT 0,
     completed BOOLEAN DEFAULT 0,
-    
+
     category TEXT,
     motivation TEXT,
     target_date DATETIME,
@@ -85,8 +221,29 @@ CREATE TABLE IF NOT EXISTS daily_reflections (
     UNIQUE(user_id, reflection_date)
 );
 
+-- Active timer table (single row, restored on startup after a crash or restart)
+CREATE TABLE IF NOT EXISTS active_timer (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    session_id TEXT NOT NULL,
+    remaining_seconds INTEGER NOT NULL,
+    total_seconds INTEGER NOT NULL,
+    is_paused BOOLEAN DEFAULT 0,
+    anchor_time DATETIME NOT NULL,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (session_id) REFERENCES pomodoro_sessions(id) ON DELETE CASCADE
+);
+
+-- Local HTTP API settings (single row)
+CREATE TABLE IF NOT EXISTS api_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    enabled BOOLEAN DEFAULT 0,
+    port INTEGER NOT NULL,
+    token TEXT NOT NULL,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
 -- Insert default user if none exists
 INSERT OR IGNORE INTO users (id, name) VALUES ('default_user', 'Default User');
 
 -- Insert default settings for the default user
-INSERT OR IGNORE INTO pomodoro_settings (user_id) VALUES ('default_user');
\ No newline at end of file
+INSERT OR IGNORE INTO pomodoro_settings (user_id) VALUES ('default_user');
@@ -0,0 +1,128 @@
+use std::fmt;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::{json, Value};
+
+// Strict mode rules a command can refuse to break
+#[derive(Debug, Clone, Copy)]
+pub enum StrictModeRule {
+    PauseDuringFocus,
+    StopReasonRequired,
+    SessionAlreadyRunning,
+}
+
+impl StrictModeRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StrictModeRule::PauseDuringFocus => "pause_during_focus",
+            StrictModeRule::StopReasonRequired => "stop_reason_required",
+            StrictModeRule::SessionAlreadyRunning => "session_already_running",
+        }
+    }
+
+    fn message(&self) -> &'static str {
+        match self {
+            StrictModeRule::PauseDuringFocus => "Strict mode does not allow pausing a focus session",
+            StrictModeRule::StopReasonRequired => "Strict mode requires a reason to stop a focus session early",
+            StrictModeRule::SessionAlreadyRunning => "Strict mode does not allow starting a session while another is running",
+        }
+    }
+}
+
+/// Error shared by `Database` and the Tauri commands.
+/// It reaches the frontend as `{ code, message, details }` so the UI can react to the kind of failure.
+#[derive(Debug)]
+pub enum TimeboxError {
+    NotFound(String),
+    Validation(String),
+    Conflict(String),
+    NoActiveSession,
+    StrictModeViolation(StrictModeRule),
+    DatabaseLocked,
+    Database(sqlx::Error),
+    Io(std::io::Error),
+}
+
+impl TimeboxError {
+    pub fn not_found(entity: &str, id: &str) -> Self {
+        TimeboxError::NotFound(format!("{} {} not found", entity, id))
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            TimeboxError::NotFound(_) => "NOT_FOUND",
+            TimeboxError::Validation(_) => "VALIDATION",
+            TimeboxError::Conflict(_) => "CONFLICT",
+            TimeboxError::NoActiveSession => "NO_ACTIVE_SESSION",
+            TimeboxError::StrictModeViolation(_) => "STRICT_MODE_VIOLATION",
+            TimeboxError::DatabaseLocked => "DATABASE_LOCKED",
+            TimeboxError::Database(_) => "DATABASE",
+            TimeboxError::Io(_) => "IO",
+        }
+    }
+
+    fn details(&self) -> Option<Value> {
+        match self {
+            TimeboxError::StrictModeViolation(rule) => Some(json!({ "rule": rule.as_str() })),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TimeboxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeboxError::NotFound(message)
+            | TimeboxError::Validation(message)
+            | TimeboxError::Conflict(message) => write!(f, "{}", message),
+            TimeboxError::NoActiveSession => write!(f, "No active session"),
+            TimeboxError::StrictModeViolation(rule) => write!(f, "{}", rule.message()),
+            TimeboxError::DatabaseLocked => write!(f, "The database is busy, please try again"),
+            TimeboxError::Database(e) => write!(f, "{}", e),
+            TimeboxError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for TimeboxError {}
+
+impl From<sqlx::Error> for TimeboxError {
+    fn from(e: sqlx::Error) -> Self {
+        match &e {
+            sqlx::Error::RowNotFound => TimeboxError::NotFound("Record not found".to_string()),
+            sqlx::Error::Database(db_err) => {
+                // SQLite reports extended result codes; the low byte is the primary code
+                let primary_code = db_err.code()
+                    .and_then(|code| code.parse::<i32>().ok())
+                    .map(|code| code & 0xff);
+
+                if matches!(primary_code, Some(5) | Some(6)) {
+                    // SQLITE_BUSY / SQLITE_LOCKED
+                    TimeboxError::DatabaseLocked
+                } else if db_err.is_unique_violation() {
+                    TimeboxError::Conflict(db_err.message().to_string())
+                } else if db_err.is_foreign_key_violation() || db_err.is_check_violation() {
+                    TimeboxError::Validation(db_err.message().to_string())
+                } else {
+                    TimeboxError::Database(e)
+                }
+            }
+            _ => TimeboxError::Database(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for TimeboxError {
+    fn from(e: std::io::Error) -> Self {
+        TimeboxError::Io(e)
+    }
+}
+
+impl Serialize for TimeboxError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("TimeboxError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
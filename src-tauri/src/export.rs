@@ -0,0 +1,366 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::database::{DataSnapshot, Database, SessionType};
+use crate::error::TimeboxError;
+use crate::migrations;
+
+pub const ARCHIVE_FORMAT: &str = "timebox-export";
+// Bump when the archive layout changes in a way importers need to know about
+pub const ARCHIVE_VERSION: u32 = 1;
+
+const JSON_FILE: &str = "timebox-export.json";
+const ICS_FILE: &str = "sessions.ics";
+
+/// Versioned JSON archive holding every table of the database.
+#[derive(Serialize, Deserialize)]
+pub struct ExportArchive {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub schema_version: i64,
+    pub exported_at: DateTime<Utc>,
+    pub data: DataSnapshot,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExportSummary {
+    pub directory: String,
+    pub files: Vec<String>,
+    pub exported_at: DateTime<Utc>,
+}
+
+// Directory name used when the caller does not pick one, e.g. timebox-export-20250101-093000
+pub fn default_directory_name(exported_at: DateTime<Utc>) -> String {
+    format!("timebox-export-{}", exported_at.format("%Y%m%d-%H%M%S"))
+}
+
+/// Writes the JSON archive, one CSV file per table and an iCalendar file of completed sessions into `directory`.
+pub async fn export_to_directory(db: &Database, directory: &Path) -> Result<ExportSummary, TimeboxError> {
+    let archive = ExportArchive {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: migrations::latest_version(),
+        exported_at: Utc::now(),
+        data: db.get_snapshot().await?,
+    };
+
+    fs::create_dir_all(directory)?;
+
+    let mut files = Vec::new();
+
+    let json_path = directory.join(JSON_FILE);
+    fs::write(&json_path, serde_json::to_vec_pretty(&archive).map_err(std::io::Error::from)?)?;
+    files.push(json_path);
+
+    files.extend(write_csv_files(directory, &archive.data)?);
+
+    let ics_path = directory.join(ICS_FILE);
+    fs::write(&ics_path, sessions_to_ics(&archive.data, archive.exported_at))?;
+    files.push(ics_path);
+
+    Ok(ExportSummary {
+        directory: directory.display().to_string(),
+        files: files.iter().map(|path| path.display().to_string()).collect(),
+        exported_at: archive.exported_at,
+    })
+}
+
+fn write_csv_files(directory: &Path, data: &DataSnapshot) -> Result<Vec<PathBuf>, TimeboxError> {
+    Ok(vec![
+        write_csv(
+            directory.join("users.csv"),
+            &["id", "name", "timezone", "created_at"],
+            data.users.iter().map(|user| vec![
+                user.id.clone(),
+                user.name.clone(),
+                user.timezone.clone(),
+                user.created_at.to_rfc3339(),
+            ]),
+        )?,
+        write_csv(
+            directory.join("settings.csv"),
+            &[
+                "user_id", "focus_minutes", "short_break_minutes", "long_break_minutes", "cycles_before_long_break",
+                "strict_mode", "auto_start_breaks", "sound_enabled", "sound_volume",
+                "daily_target_unit", "daily_target", "rest_days",
+            ],
+            data.settings.iter().map(|settings| vec![
+                settings.user_id.clone(),
+                settings.focus_minutes.to_string(),
+                settings.short_break_minutes.to_string(),
+                settings.long_break_minutes.to_string(),
+                settings.cycles_before_long_break.to_string(),
+                settings.strict_mode.to_string(),
+                settings.auto_start_breaks.to_string(),
+                settings.sound_enabled.to_string(),
+                settings.sound_volume.to_string(),
+                settings.daily_target_unit.as_str().to_string(),
+                settings.daily_target.to_string(),
+                settings.rest_days.iter().map(|day| day.to_string()).collect::<Vec<_>>().join(","),
+            ]),
+        )?,
+        write_csv(
+            directory.join("tasks.csv"),
+            &[
+                "id", "user_id", "title", "estimated_pomodoros", "completed", "completed_at", "goal_id", "project_id", "tags", "parent_task_id",
+                "due_date", "priority", "position", "planned_for", "recurrence", "recurs_from", "created_at",
+            ],
+            data.tasks.iter().map(|task| vec![
+                task.id.clone(),
+                task.user_id.clone(),
+                task.title.clone(),
+                task.estimated_pomodoros.to_string(),
+                task.completed.to_string(),
+                task.completed_at.map(|at| at.to_rfc3339()).unwrap_or_default(),
+                task.goal_id.clone().unwrap_or_default(),
+                task.project_id.clone().unwrap_or_default(),
+                task.tags.join(","),
+                task.parent_task_id.clone().unwrap_or_default(),
+                task.due_date.map(|date| date.to_string()).unwrap_or_default(),
+                task.priority.as_str().to_string(),
+                task.position.to_string(),
+                task.planned_for.map(|date| date.to_string()).unwrap_or_default(),
+                task.recurrence.clone().unwrap_or_default(),
+                task.recurs_from.clone().unwrap_or_default(),
+                task.created_at.to_rfc3339(),
+            ]),
+        )?,
+        write_csv(
+            directory.join("checklist_items.csv"),
+            &["id", "task_id", "title", "completed", "position", "created_at"],
+            data.checklist_items.iter().map(|item| vec![
+                item.id.clone(),
+                item.task_id.clone(),
+                item.title.clone(),
+                item.completed.to_string(),
+                item.position.to_string(),
+                item.created_at.to_rfc3339(),
+            ]),
+        )?,
+        write_csv(
+            directory.join("sessions.csv"),
+            &[
+                "id", "user_id", "task_id", "task_title", "session_type", "start_time", "end_time", "duration_seconds",
+                "interrupted", "interruption_count", "pause_count", "paused_seconds", "manual_override", "stop_reason", "tags", "created_at",
+            ],
+            data.sessions.iter().map(|session| vec![
+                session.id.clone(),
+                session.user_id.clone(),
+                session.task_id.clone().unwrap_or_default(),
+                session.task_title.clone().unwrap_or_default(),
+                session.session_type.to_string(),
+                session.start_time.to_rfc3339(),
+                session.end_time.map(|end| end.to_rfc3339()).unwrap_or_default(),
+                session.duration_seconds.map(|d| d.to_string()).unwrap_or_default(),
+                session.interrupted.to_string(),
+                session.interruption_count.to_string(),
+                session.pause_count.to_string(),
+                session.paused_seconds.to_string(),
+                session.manual_override.to_string(),
+                session.stop_reason.clone().unwrap_or_default(),
+                session.tags.join(","),
+                session.created_at.to_rfc3339(),
+            ]),
+        )?,
+        write_csv(
+            directory.join("session_pauses.csv"),
+            &["id", "session_id", "paused_at", "resumed_at", "reason"],
+            data.session_pauses.iter().map(|pause| vec![
+                pause.id.clone(),
+                pause.session_id.clone(),
+                pause.paused_at.to_rfc3339(),
+                pause.resumed_at.map(|resumed| resumed.to_rfc3339()).unwrap_or_default(),
+                pause.reason.clone().unwrap_or_default(),
+            ]),
+        )?,
+        write_csv(
+            directory.join("interruptions.csv"),
+            &["id", "session_id", "user_id", "occurred_at", "kind", "reason", "category"],
+            data.interruptions.iter().map(|interruption| vec![
+                interruption.id.clone(),
+                interruption.session_id.clone(),
+                interruption.user_id.clone(),
+                interruption.occurred_at.to_rfc3339(),
+                interruption.kind.as_str().to_string(),
+                interruption.reason.clone().unwrap_or_default(),
+                interruption.category.clone().unwrap_or_default(),
+            ]),
+        )?,
+        write_csv(
+            directory.join("session_audit.csv"),
+            &["id", "session_id", "user_id", "action", "before", "after", "created_at"],
+            data.session_audit.iter().map(|entry| vec![
+                entry.id.clone(),
+                entry.session_id.clone(),
+                entry.user_id.clone(),
+                entry.action.as_str().to_string(),
+                serde_json::to_string(&entry.before).unwrap_or_default(),
+                serde_json::to_string(&entry.after).unwrap_or_default(),
+                entry.created_at.to_rfc3339(),
+            ]),
+        )?,
+        write_csv(
+            directory.join("projects.csv"),
+            &["id", "user_id", "name", "color", "archived", "hourly_rate", "created_at"],
+            data.projects.iter().map(|project| vec![
+                project.id.clone(),
+                project.user_id.clone(),
+                project.name.clone(),
+                project.color.clone().unwrap_or_default(),
+                project.archived.to_string(),
+                project.hourly_rate.map(|rate| rate.to_string()).unwrap_or_default(),
+                project.created_at.to_rfc3339(),
+            ]),
+        )?,
+        write_csv(
+            directory.join("tags.csv"),
+            &["id", "user_id", "name", "created_at"],
+            data.tags.iter().map(|tag| vec![
+                tag.id.clone(),
+                tag.user_id.clone(),
+                tag.name.clone(),
+                tag.created_at.to_rfc3339(),
+            ]),
+        )?,
+        write_csv(
+            directory.join("goals.csv"),
+            &[
+                "id", "user_id", "title", "target_pomodoros", "completed_pomodoros", "completed",
+                "category", "motivation", "target_date", "description", "created_at",
+            ],
+            data.goals.iter().map(|goal| vec![
+                goal.id.clone(),
+                goal.user_id.clone(),
+                goal.title.clone(),
+                goal.target_pomodoros.to_string(),
+                goal.completed_pomodoros.to_string(),
+                goal.completed.to_string(),
+                goal.category.clone().unwrap_or_default(),
+                goal.motivation.clone().unwrap_or_default(),
+                goal.target_date.map(|date| date.to_rfc3339()).unwrap_or_default(),
+                goal.description.clone().unwrap_or_default(),
+                goal.created_at.to_rfc3339(),
+            ]),
+        )?,
+        write_csv(
+            directory.join("reflections.csv"),
+            &[
+                "id", "user_id", "reflection_date", "title", "duration_reflection", "purpose_reflection",
+                "general_notes", "mood_rating", "productivity_rating", "created_at", "updated_at",
+            ],
+            data.reflections.iter().map(|reflection| vec![
+                reflection.id.clone(),
+                reflection.user_id.clone(),
+                reflection.reflection_date.clone(),
+                reflection.title.clone().unwrap_or_default(),
+                reflection.duration_reflection.clone().unwrap_or_default(),
+                reflection.purpose_reflection.clone().unwrap_or_default(),
+                reflection.general_notes.clone().unwrap_or_default(),
+                reflection.mood_rating.map(|r| r.to_string()).unwrap_or_default(),
+                reflection.productivity_rating.map(|r| r.to_string()).unwrap_or_default(),
+                reflection.created_at.to_rfc3339(),
+                reflection.updated_at.to_rfc3339(),
+            ]),
+        )?,
+    ])
+}
+
+fn write_csv(
+    path: PathBuf,
+    headers: &[&str],
+    records: impl Iterator<Item = Vec<String>>,
+) -> Result<PathBuf, TimeboxError> {
+    let mut writer = csv::Writer::from_path(&path).map_err(std::io::Error::from)?;
+    writer.write_record(headers).map_err(std::io::Error::from)?;
+    for record in records {
+        writer.write_record(&record).map_err(std::io::Error::from)?;
+    }
+    writer.flush()?;
+
+    Ok(path)
+}
+
+/// Renders every completed session as a VEVENT, titled after its task so calendars and timesheet tools can pick it up.
+pub fn sessions_to_ics(data: &DataSnapshot, exported_at: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//Timebox//Timebox {}//EN", env!("CARGO_PKG_VERSION")),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    let completed = data.sessions.iter().filter(|session| session.duration_seconds.unwrap_or(0) > 0);
+    for session in completed {
+        let Some(end_time) = session.end_time else { continue };
+
+        let phase = match session.session_type {
+            SessionType::Focus => "Focus session",
+            SessionType::ShortBreak => "Short break",
+            SessionType::LongBreak => "Long break",
+        };
+        let summary = session.task_title.as_deref().unwrap_or(phase);
+
+        let mut description = format!(
+            "{}, {} min",
+            phase,
+            session.duration_seconds.unwrap_or(0) / 60
+        );
+        if session.interruption_count > 0 {
+            description.push_str(&format!(", {} interruptions", session.interruption_count));
+        }
+        if let Some(reason) = &session.stop_reason {
+            description.push_str(&format!(", stopped early: {}", reason));
+        }
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@timebox", session.id));
+        lines.push(format!("DTSTAMP:{}", ics_timestamp(exported_at)));
+        lines.push(format!("DTSTART:{}", ics_timestamp(session.start_time)));
+        lines.push(format!("DTEND:{}", ics_timestamp(end_time)));
+        lines.push(format!("SUMMARY:{}", ics_escape(summary)));
+        lines.push(format!("DESCRIPTION:{}", ics_escape(&description)));
+        let categories: Vec<String> = std::iter::once(session.session_type.to_string())
+            .chain(session.tags.iter().map(|tag| ics_escape(tag)))
+            .collect();
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_ics_line(line)).collect()
+}
+
+fn ics_timestamp(instant: DateTime<Utc>) -> String {
+    instant.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// Escapes TEXT values as required by RFC 5545
+fn ics_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// Lines longer than 75 octets are folded onto continuation lines starting with a space
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
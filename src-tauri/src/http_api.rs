@@ -0,0 +1,739 @@
+use std::convert::Infallible;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, patch, post, put};
+use axum::{Json, Router};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::{broadcast, watch, Mutex};
+use tokio_stream::wrappers::{BroadcastStream, WatchStream};
+use tokio_stream::{Stream, StreamExt};
+use tower_http::cors::{Any, CorsLayer};
+
+use crate::commands::{
+    AppState, GetReflectionRequest, GetReflectionsByMonthRequest, LogManualSessionRequest, MergeSessionsRequest,
+    RecordInterruptionRequest, SaveReflectionRequest, SplitSessionRequest, StartSessionRequest, UpdateSessionRequest, TimerStatusResponse, UpdateChecklistItemRequest, UpdateGoalRequest, UpdateProjectRequest, UpdateTaskRequest, begin_session, build_timer_status,
+    pause_active_session, record_active_interruption, resume_active_session, stop_active_session, sync_with_database,
+};
+use crate::cycle::{self, CycleState};
+use crate::database::{
+    ApiSettings, ChecklistItem, DailyPlan, DailyReflection, FocusStats, Goal, GoalProgress, Granularity, Interruption, InterruptionSource, PomodoroSession, Project,
+    ProjectTagFilter, SessionType, Tag, Task, UpcomingOccurrence, DEFAULT_UPCOMING_DAYS,
+};
+use crate::error::TimeboxError;
+use crate::timer_engine::{TimerEvent, TICK_EVENT};
+
+const DEFAULT_USER: &str = "default_user";
+// Comments keep idle SSE connections from being dropped by proxies and browser extensions
+const KEEP_ALIVE_INTERVAL: StdDuration = StdDuration::from_secs(15);
+
+/// Owns the embedded HTTP server so it can be started, restarted and stopped as the settings change.
+pub struct HttpApi {
+    events: broadcast::Sender<TimerEvent>,
+    // Signals the running server, and the event streams it serves, to shut down
+    server: Mutex<Option<watch::Sender<()>>>,
+}
+
+#[derive(Clone)]
+struct ApiContext {
+    state: Arc<AppState>,
+    events: broadcast::Sender<TimerEvent>,
+    token: Arc<str>,
+    shutdown: watch::Receiver<()>,
+}
+
+impl HttpApi {
+    pub fn new(events: broadcast::Sender<TimerEvent>) -> Self {
+        HttpApi {
+            events,
+            server: Mutex::new(None),
+        }
+    }
+
+    /// Stops the running server, if any, and starts a new one on 127.0.0.1 when the API is enabled.
+    pub async fn apply(&self, state: Arc<AppState>, settings: &ApiSettings) -> Result<(), TimeboxError> {
+        let mut server = self.server.lock().await;
+        if let Some(shutdown) = server.take() {
+            let _ = shutdown.send(());
+        }
+
+        if !settings.enabled {
+            return Ok(());
+        }
+
+        let listener = bind(settings.port).await?;
+        let (shutdown, mut shutdown_signal) = watch::channel(());
+        let router = router(ApiContext {
+            state,
+            events: self.events.clone(),
+            token: settings.token.as_str().into(),
+            shutdown: shutdown_signal.clone(),
+        });
+
+        tauri::async_runtime::spawn(async move {
+            let served = axum::serve(listener, router)
+                .with_graceful_shutdown(async move {
+                    let _ = shutdown_signal.changed().await;
+                })
+                .await;
+            if let Err(e) = served {
+                eprintln!("HTTP API server failed: {}", e);
+            }
+        });
+        *server = Some(shutdown);
+
+        Ok(())
+    }
+}
+
+// The previous server releases its port asynchronously, so a restart on the same port retries briefly
+async fn bind(port: u16) -> Result<tokio::net::TcpListener, TimeboxError> {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut attempts = 0;
+    loop {
+        match tokio::net::TcpListener::bind(addr).await {
+            Ok(listener) => return Ok(listener),
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse && attempts < 10 => {
+                attempts += 1;
+                tokio::time::sleep(StdDuration::from_millis(50)).await;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+                return Err(TimeboxError::Conflict(format!("Port {} is already in use", port)));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn router(context: ApiContext) -> Router {
+    Router::new()
+        .route("/api/timer", get(timer_status))
+        .route("/api/timer/start", post(start_timer))
+        .route("/api/timer/pause", post(pause_timer))
+        .route("/api/timer/resume", post(resume_timer))
+        .route("/api/timer/stop", post(stop_timer))
+        .route("/api/timer/interruptions", post(record_interruption))
+        .route("/api/timer/events", get(timer_events))
+        .route("/api/cycle", get(cycle_state))
+        .route("/api/tasks", get(list_tasks).post(create_task))
+        .route("/api/tasks/{id}", patch(update_task).delete(delete_task))
+        .route("/api/tasks/{id}/goal", put(assign_task_to_goal))
+        .route("/api/tasks/{id}/project", put(assign_task_to_project))
+        .route("/api/tasks/{id}/tags", put(set_task_tags))
+        .route("/api/tasks/order", put(reorder_tasks))
+        .route("/api/tasks/upcoming", get(upcoming_occurrences))
+        .route("/api/tasks/{id}/parent", put(set_task_parent))
+        .route("/api/tasks/{id}/checklist", get(list_checklist_items).post(add_checklist_item))
+        .route("/api/tasks/{id}/checklist/order", put(reorder_checklist_items))
+        .route("/api/checklist/{id}", patch(update_checklist_item).delete(delete_checklist_item))
+        .route("/api/projects", get(list_projects).post(create_project))
+        .route("/api/projects/{id}", patch(update_project).delete(delete_project))
+        .route("/api/tags", get(list_tags).post(create_tag))
+        .route("/api/tags/{id}", patch(rename_tag).delete(delete_tag))
+        .route("/api/sessions", get(list_sessions).post(log_manual_session))
+        .route("/api/sessions/today", get(today_sessions))
+        .route("/api/sessions/merge", post(merge_sessions))
+        .route("/api/sessions/{id}", patch(update_session).delete(delete_session))
+        .route("/api/sessions/{id}/split", post(split_session))
+        .route("/api/interruptions", get(list_interruptions))
+        .route("/api/interruptions/sources", get(interruption_sources))
+        .route("/api/stats", get(focus_stats))
+        .route("/api/plan", get(daily_plan))
+        .route("/api/goals", get(list_goals).post(create_goal))
+        .route("/api/goals/{id}", patch(update_goal).delete(delete_goal))
+        .route("/api/goals/{id}/progress", get(goal_progress))
+        .route("/api/reflections", get(get_reflection).put(save_reflection))
+        .route("/api/reflections/month", get(reflections_by_month))
+        .layer(middleware::from_fn_with_state(context.clone(), authenticate))
+        // Any origin may call the API; the bearer token is what grants access
+        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any))
+        .with_state(context)
+}
+
+/// Requires the install's token as `Authorization: Bearer <token>`.
+/// `?token=` is accepted too, because browsers can't set headers on an `EventSource`.
+async fn authenticate(State(context): State<ApiContext>, headers: HeaderMap, request: Request, next: Next) -> Response {
+    let from_header = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+    let from_query = request.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("token="))
+            .map(str::to_string)
+    });
+
+    match from_header.or(from_query) {
+        Some(token) if constant_time_eq(token.as_bytes(), context.token.as_bytes()) => next.run(request).await,
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            Json(serde_json::json!({ "code": "UNAUTHORIZED", "message": "Missing or invalid API token", "details": null })),
+        )
+            .into_response(),
+    }
+}
+
+// Compares every byte so response timing doesn't reveal how much of a guessed token was right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+// TimeboxError as an HTTP response with the same `{ code, message, details }` body the frontend gets
+struct ApiError(TimeboxError);
+
+impl From<TimeboxError> for ApiError {
+    fn from(e: TimeboxError) -> Self {
+        ApiError(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self.0 {
+            TimeboxError::NotFound(_) => StatusCode::NOT_FOUND,
+            TimeboxError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            TimeboxError::Conflict(_) | TimeboxError::NoActiveSession | TimeboxError::StrictModeViolation(_) => StatusCode::CONFLICT,
+            TimeboxError::DatabaseLocked => StatusCode::SERVICE_UNAVAILABLE,
+            TimeboxError::Database(_) | TimeboxError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(self.0)).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+// Fills the id from the URL into a request struct that also carries it in the body
+fn with_path_id<T: for<'de> Deserialize<'de>>(mut body: Value, field: &str, id: String) -> Result<T, ApiError> {
+    match body.as_object_mut() {
+        Some(object) => {
+            object.insert(field.to_string(), Value::String(id));
+        }
+        None => return Err(TimeboxError::Validation("Request body must be a JSON object".to_string()).into()),
+    }
+    serde_json::from_value(body).map_err(|e| TimeboxError::Validation(e.to_string()).into())
+}
+
+#[derive(Deserialize)]
+struct UserQuery {
+    user_id: Option<String>,
+}
+
+impl UserQuery {
+    fn user_id(&self) -> &str {
+        self.user_id.as_deref().unwrap_or(DEFAULT_USER)
+    }
+}
+
+#[derive(Deserialize)]
+struct ReasonRequest {
+    reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CreateTaskRequest {
+    user_id: Option<String>,
+    title: String,
+    estimated_pomodoros: Option<i32>,
+    goal_id: Option<String>,
+    project_id: Option<String>,
+    parent_task_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AssignGoalRequest {
+    goal_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AssignProjectRequest {
+    project_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TagsRequest {
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ReorderTasksRequest {
+    user_id: Option<String>,
+    task_ids: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct PlanQuery {
+    user_id: Option<String>,
+    date: Option<NaiveDate>,
+    available_minutes: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct UpcomingQuery {
+    user_id: Option<String>,
+    days: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct SetParentRequest {
+    parent_task_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChecklistItemRequest {
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct ChecklistOrderRequest {
+    item_ids: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct TasksQuery {
+    user_id: Option<String>,
+    project_id: Option<String>,
+    tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProjectsQuery {
+    user_id: Option<String>,
+    #[serde(default)]
+    include_archived: bool,
+}
+
+#[derive(Deserialize)]
+struct CreateProjectRequest {
+    user_id: Option<String>,
+    name: String,
+    color: Option<String>,
+    hourly_rate: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct CreateTagRequest {
+    user_id: Option<String>,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct RenameTagRequest {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct StatsQuery {
+    user_id: Option<String>,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+    granularity: Option<Granularity>,
+    project_id: Option<String>,
+    tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CreateGoalRequest {
+    user_id: Option<String>,
+    title: String,
+    target_pomodoros: i32,
+    category: Option<String>,
+    motivation: Option<String>,
+    target_date: Option<DateTime<Utc>>,
+    description: Option<String>,
+}
+
+// Without a range every session of the user is returned
+#[derive(Deserialize)]
+struct SessionsQuery {
+    user_id: Option<String>,
+    start_date: Option<DateTime<Utc>>,
+    end_date: Option<DateTime<Utc>>,
+    session_type: Option<SessionType>,
+    // Only applied together with a range
+    project_id: Option<String>,
+    tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct InterruptionsQuery {
+    user_id: Option<String>,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+    // Only used for sources, 10 when omitted
+    limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct InterruptionResponse {
+    interruption_count: i32,
+}
+
+async fn current_status(state: &AppState) -> Result<TimerStatusResponse, TimeboxError> {
+    let mut active_session = state.active_session.write().await;
+    sync_with_database(&state.db, &mut active_session).await?;
+    Ok(build_timer_status(&state.db, active_session.as_ref()).await)
+}
+
+async fn timer_status(State(context): State<ApiContext>) -> ApiResult<TimerStatusResponse> {
+    Ok(Json(current_status(&context.state).await?))
+}
+
+async fn start_timer(State(context): State<ApiContext>, Json(req): Json<StartSessionRequest>) -> ApiResult<PomodoroSession> {
+    Ok(Json(begin_session(&context.state, req).await?))
+}
+
+async fn pause_timer(State(context): State<ApiContext>, req: Option<Json<ReasonRequest>>) -> ApiResult<TimerStatusResponse> {
+    let reason = req.and_then(|Json(req)| req.reason);
+    pause_active_session(&context.state, reason).await?;
+    timer_status(State(context)).await
+}
+
+async fn resume_timer(State(context): State<ApiContext>) -> ApiResult<TimerStatusResponse> {
+    resume_active_session(&context.state).await?;
+    timer_status(State(context)).await
+}
+
+async fn stop_timer(State(context): State<ApiContext>, req: Option<Json<ReasonRequest>>) -> ApiResult<TimerStatusResponse> {
+    let reason = req.and_then(|Json(req)| req.reason);
+    stop_active_session(&context.state, reason).await?;
+    timer_status(State(context)).await
+}
+
+async fn record_interruption(
+    State(context): State<ApiContext>,
+    req: Option<Json<RecordInterruptionRequest>>,
+) -> ApiResult<InterruptionResponse> {
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    let interruption_count = record_active_interruption(&context.state, req).await?;
+    Ok(Json(InterruptionResponse { interruption_count }))
+}
+
+/// Streams timer events as server-sent events, starting with the current status.
+async fn timer_events(State(context): State<ApiContext>) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let initial = current_status(&context.state).await?;
+    let initial = Event::default().event(TICK_EVENT).json_data(initial).ok();
+
+    // Slow clients skip the events they missed; the next tick carries the full status anyway
+    let updates = BroadcastStream::new(context.events.subscribe()).filter_map(|event| {
+        let event = event.ok()?;
+        Event::default().event(event.name).json_data(event.payload).ok()
+    });
+
+    // Event streams never finish on their own, so they end when the server shuts down,
+    // which also cuts off clients still holding a token that was just replaced
+    let shutdown = WatchStream::from_changes(context.shutdown.clone()).map(|_| None);
+
+    let stream = tokio_stream::iter(initial)
+        .chain(updates)
+        .map(Some)
+        .merge(shutdown)
+        .map_while(|event| event.map(Ok));
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(KEEP_ALIVE_INTERVAL)))
+}
+
+async fn cycle_state(State(context): State<ApiContext>, Query(query): Query<UserQuery>) -> ApiResult<CycleState> {
+    Ok(Json(cycle::current_cycle_state(&context.state.db, query.user_id()).await?))
+}
+
+async fn list_tasks(State(context): State<ApiContext>, Query(query): Query<TasksQuery>) -> ApiResult<Vec<Task>> {
+    let user_id = query.user_id.as_deref().unwrap_or(DEFAULT_USER);
+    let filter = ProjectTagFilter { project_id: query.project_id, tag: query.tag };
+    Ok(Json(context.state.db.get_tasks(user_id, &filter).await?))
+}
+
+async fn create_task(State(context): State<ApiContext>, Json(req): Json<CreateTaskRequest>) -> Result<(StatusCode, Json<Task>), ApiError> {
+    let task = context.state.db.create_task(
+        req.user_id.as_deref().unwrap_or(DEFAULT_USER),
+        &req.title,
+        req.estimated_pomodoros,
+        req.goal_id.as_deref(),
+        req.project_id.as_deref(),
+        req.parent_task_id.as_deref(),
+    ).await?;
+    Ok((StatusCode::CREATED, Json(task)))
+}
+
+async fn update_task(State(context): State<ApiContext>, Path(id): Path<String>, Json(body): Json<Value>) -> ApiResult<Task> {
+    let req: UpdateTaskRequest = with_path_id(body, "task_id", id)?;
+    Ok(Json(context.state.db.update_task(&req.task_id, req.changes).await?))
+}
+
+async fn reorder_tasks(State(context): State<ApiContext>, Json(req): Json<ReorderTasksRequest>) -> ApiResult<Vec<Task>> {
+    let user_id = req.user_id.as_deref().unwrap_or(DEFAULT_USER);
+    Ok(Json(context.state.db.reorder_tasks(user_id, &req.task_ids).await?))
+}
+
+async fn daily_plan(State(context): State<ApiContext>, Query(query): Query<PlanQuery>) -> ApiResult<DailyPlan> {
+    let user_id = query.user_id.as_deref().unwrap_or(DEFAULT_USER);
+    Ok(Json(context.state.db.get_daily_plan(user_id, query.date, query.available_minutes).await?))
+}
+
+async fn upcoming_occurrences(State(context): State<ApiContext>, Query(query): Query<UpcomingQuery>) -> ApiResult<Vec<UpcomingOccurrence>> {
+    let user_id = query.user_id.as_deref().unwrap_or(DEFAULT_USER);
+    let days = query.days.unwrap_or(DEFAULT_UPCOMING_DAYS);
+    Ok(Json(context.state.db.get_upcoming_occurrences(user_id, days).await?))
+}
+
+async fn delete_task(State(context): State<ApiContext>, Path(id): Path<String>) -> Result<StatusCode, ApiError> {
+    context.state.db.delete_task(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn assign_task_to_goal(
+    State(context): State<ApiContext>,
+    Path(id): Path<String>,
+    Json(req): Json<AssignGoalRequest>,
+) -> ApiResult<Task> {
+    Ok(Json(context.state.db.set_task_goal(&id, req.goal_id.as_deref()).await?))
+}
+
+async fn assign_task_to_project(
+    State(context): State<ApiContext>,
+    Path(id): Path<String>,
+    Json(req): Json<AssignProjectRequest>,
+) -> ApiResult<Task> {
+    Ok(Json(context.state.db.set_task_project(&id, req.project_id.as_deref()).await?))
+}
+
+async fn set_task_tags(State(context): State<ApiContext>, Path(id): Path<String>, Json(req): Json<TagsRequest>) -> ApiResult<Task> {
+    Ok(Json(context.state.db.set_task_tags(&id, &req.tags).await?))
+}
+
+async fn set_task_parent(State(context): State<ApiContext>, Path(id): Path<String>, Json(req): Json<SetParentRequest>) -> ApiResult<Task> {
+    Ok(Json(context.state.db.set_task_parent(&id, req.parent_task_id.as_deref()).await?))
+}
+
+async fn list_checklist_items(State(context): State<ApiContext>, Path(id): Path<String>) -> ApiResult<Vec<ChecklistItem>> {
+    Ok(Json(context.state.db.get_checklist_items(&id).await?))
+}
+
+async fn add_checklist_item(
+    State(context): State<ApiContext>,
+    Path(id): Path<String>,
+    Json(req): Json<ChecklistItemRequest>,
+) -> Result<(StatusCode, Json<ChecklistItem>), ApiError> {
+    let item = context.state.db.add_checklist_item(&id, &req.title).await?;
+    Ok((StatusCode::CREATED, Json(item)))
+}
+
+async fn reorder_checklist_items(
+    State(context): State<ApiContext>,
+    Path(id): Path<String>,
+    Json(req): Json<ChecklistOrderRequest>,
+) -> ApiResult<Vec<ChecklistItem>> {
+    Ok(Json(context.state.db.reorder_checklist_items(&id, &req.item_ids).await?))
+}
+
+async fn update_checklist_item(State(context): State<ApiContext>, Path(id): Path<String>, Json(body): Json<Value>) -> ApiResult<ChecklistItem> {
+    let req: UpdateChecklistItemRequest = with_path_id(body, "item_id", id)?;
+    Ok(Json(context.state.db.update_checklist_item(&req.item_id, req.changes).await?))
+}
+
+async fn delete_checklist_item(State(context): State<ApiContext>, Path(id): Path<String>) -> Result<StatusCode, ApiError> {
+    context.state.db.delete_checklist_item(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_projects(State(context): State<ApiContext>, Query(query): Query<ProjectsQuery>) -> ApiResult<Vec<Project>> {
+    let user_id = query.user_id.as_deref().unwrap_or(DEFAULT_USER);
+    Ok(Json(context.state.db.get_projects(user_id, query.include_archived).await?))
+}
+
+async fn create_project(
+    State(context): State<ApiContext>,
+    Json(req): Json<CreateProjectRequest>,
+) -> Result<(StatusCode, Json<Project>), ApiError> {
+    let project = context.state.db.create_project(
+        req.user_id.as_deref().unwrap_or(DEFAULT_USER),
+        &req.name,
+        req.color.as_deref(),
+        req.hourly_rate,
+    ).await?;
+    Ok((StatusCode::CREATED, Json(project)))
+}
+
+async fn update_project(State(context): State<ApiContext>, Path(id): Path<String>, Json(body): Json<Value>) -> ApiResult<Project> {
+    let req: UpdateProjectRequest = with_path_id(body, "project_id", id)?;
+    Ok(Json(context.state.db.update_project(&req.project_id, req.changes).await?))
+}
+
+async fn delete_project(State(context): State<ApiContext>, Path(id): Path<String>) -> Result<StatusCode, ApiError> {
+    context.state.db.delete_project(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_tags(State(context): State<ApiContext>, Query(query): Query<UserQuery>) -> ApiResult<Vec<Tag>> {
+    Ok(Json(context.state.db.get_tags(query.user_id()).await?))
+}
+
+async fn create_tag(State(context): State<ApiContext>, Json(req): Json<CreateTagRequest>) -> Result<(StatusCode, Json<Tag>), ApiError> {
+    let tag = context.state.db.create_tag(req.user_id.as_deref().unwrap_or(DEFAULT_USER), &req.name).await?;
+    Ok((StatusCode::CREATED, Json(tag)))
+}
+
+async fn rename_tag(State(context): State<ApiContext>, Path(id): Path<String>, Json(req): Json<RenameTagRequest>) -> ApiResult<Tag> {
+    Ok(Json(context.state.db.rename_tag(&id, &req.name).await?))
+}
+
+async fn delete_tag(State(context): State<ApiContext>, Path(id): Path<String>) -> Result<StatusCode, ApiError> {
+    context.state.db.delete_tag(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_sessions(State(context): State<ApiContext>, Query(query): Query<SessionsQuery>) -> ApiResult<Vec<PomodoroSession>> {
+    let user_id = query.user_id.as_deref().unwrap_or(DEFAULT_USER);
+    let sessions = match (query.start_date, query.end_date) {
+        (Some(start_date), Some(end_date)) => {
+            let filter = ProjectTagFilter { project_id: query.project_id, tag: query.tag };
+            context.state.db.get_sessions_by_date_range(user_id, start_date, end_date, query.session_type, &filter).await?
+        }
+        (None, None) => context.state.db.get_sessions(user_id, None).await?,
+        _ => return Err(TimeboxError::Validation("start_date and end_date must be given together".to_string()).into()),
+    };
+    Ok(Json(sessions))
+}
+
+async fn today_sessions(State(context): State<ApiContext>, Query(query): Query<UserQuery>) -> ApiResult<Vec<PomodoroSession>> {
+    Ok(Json(context.state.db.get_today_sessions(query.user_id()).await?))
+}
+
+async fn update_session(State(context): State<ApiContext>, Path(id): Path<String>, Json(body): Json<Value>) -> ApiResult<PomodoroSession> {
+    let req: UpdateSessionRequest = with_path_id(body, "session_id", id)?;
+    Ok(Json(context.state.db.edit_session(&req.session_id, req.changes).await?))
+}
+
+async fn delete_session(State(context): State<ApiContext>, Path(id): Path<String>) -> Result<StatusCode, ApiError> {
+    context.state.db.delete_session(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn split_session(
+    State(context): State<ApiContext>,
+    Path(id): Path<String>,
+    Json(body): Json<Value>,
+) -> ApiResult<Vec<PomodoroSession>> {
+    let req: SplitSessionRequest = with_path_id(body, "session_id", id)?;
+    let (first, second) = context.state.db.split_session(&req.session_id, req.split_at).await?;
+    Ok(Json(vec![first, second]))
+}
+
+async fn merge_sessions(State(context): State<ApiContext>, Json(req): Json<MergeSessionsRequest>) -> ApiResult<PomodoroSession> {
+    Ok(Json(context.state.db.merge_sessions(&req.first_session_id, &req.second_session_id).await?))
+}
+
+async fn list_interruptions(
+    State(context): State<ApiContext>,
+    Query(query): Query<InterruptionsQuery>,
+) -> ApiResult<Vec<Interruption>> {
+    let user_id = query.user_id.as_deref().unwrap_or(DEFAULT_USER);
+    Ok(Json(context.state.db.get_interruptions(user_id, query.start_date, query.end_date).await?))
+}
+
+async fn interruption_sources(
+    State(context): State<ApiContext>,
+    Query(query): Query<InterruptionsQuery>,
+) -> ApiResult<Vec<InterruptionSource>> {
+    let user_id = query.user_id.as_deref().unwrap_or(DEFAULT_USER);
+    let limit = query.limit.unwrap_or(10).max(1);
+    Ok(Json(context.state.db.get_interruption_sources(user_id, query.start_date, query.end_date, limit).await?))
+}
+
+async fn focus_stats(State(context): State<ApiContext>, Query(query): Query<StatsQuery>) -> ApiResult<FocusStats> {
+    let user_id = query.user_id.as_deref().unwrap_or(DEFAULT_USER);
+    let filter = ProjectTagFilter { project_id: query.project_id, tag: query.tag };
+    Ok(Json(context.state.db.get_focus_stats(
+        user_id,
+        query.start_date,
+        query.end_date,
+        query.granularity.unwrap_or(Granularity::Day),
+        &filter,
+    ).await?))
+}
+
+async fn log_manual_session(
+    State(context): State<ApiContext>,
+    Json(req): Json<LogManualSessionRequest>,
+) -> Result<(StatusCode, Json<Vec<PomodoroSession>>), ApiError> {
+    let sessions = context.state.db.create_manual_session(&req.user_id, req.session).await?;
+    Ok((StatusCode::CREATED, Json(sessions)))
+}
+
+async fn list_goals(State(context): State<ApiContext>, Query(query): Query<UserQuery>) -> ApiResult<Vec<Goal>> {
+    Ok(Json(context.state.db.get_goals(query.user_id()).await?))
+}
+
+async fn create_goal(State(context): State<ApiContext>, Json(req): Json<CreateGoalRequest>) -> Result<(StatusCode, Json<Goal>), ApiError> {
+    let goal = context.state.db.create_goal(
+        req.user_id.as_deref().unwrap_or(DEFAULT_USER),
+        &req.title,
+        req.target_pomodoros,
+        req.category,
+        req.motivation,
+        req.target_date,
+        req.description,
+    ).await?;
+    Ok((StatusCode::CREATED, Json(goal)))
+}
+
+async fn update_goal(State(context): State<ApiContext>, Path(id): Path<String>, Json(body): Json<Value>) -> ApiResult<Goal> {
+    let req: UpdateGoalRequest = with_path_id(body, "goal_id", id)?;
+    context.state.db.update_goal(
+        &req.goal_id,
+        req.title.as_deref(),
+        req.target_pomodoros,
+        req.completed,
+        req.category,
+        req.motivation,
+        req.target_date,
+        req.description,
+    ).await?;
+    let goal = context.state.db.get_goal(&req.goal_id).await?
+        .ok_or_else(|| TimeboxError::not_found("Goal", &req.goal_id))?;
+    Ok(Json(goal))
+}
+
+async fn delete_goal(State(context): State<ApiContext>, Path(id): Path<String>) -> Result<StatusCode, ApiError> {
+    context.state.db.delete_goal(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn goal_progress(State(context): State<ApiContext>, Path(id): Path<String>) -> ApiResult<GoalProgress> {
+    Ok(Json(context.state.db.get_goal_progress(&id).await?))
+}
+
+async fn get_reflection(
+    State(context): State<ApiContext>,
+    Query(req): Query<GetReflectionRequest>,
+) -> ApiResult<Option<DailyReflection>> {
+    Ok(Json(context.state.db.get_reflection_by_date(&req.user_id, req.reflection_date).await?))
+}
+
+async fn reflections_by_month(
+    State(context): State<ApiContext>,
+    Query(req): Query<GetReflectionsByMonthRequest>,
+) -> ApiResult<Vec<DailyReflection>> {
+    Ok(Json(context.state.db.get_reflections_by_month(&req.user_id, req.year, req.month).await?))
+}
+
+async fn save_reflection(State(context): State<ApiContext>, Json(req): Json<SaveReflectionRequest>) -> ApiResult<DailyReflection> {
+    Ok(Json(context.state.db.create_or_update_reflection(
+        &req.user_id,
+        req.reflection_date,
+        req.title,
+        req.duration_reflection,
+        req.purpose_reflection,
+        req.general_notes,
+        req.mood_rating,
+        req.productivity_rating,
+    ).await?))
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::database::{Database, SessionType};
+use crate::error::TimeboxError;
+use crate::export::{ExportArchive, ARCHIVE_FORMAT, ARCHIVE_VERSION};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    // JSON archive written by export_data
+    Archive,
+    // Toggl-style time entries: Start date, Start time, End date, End time, Description
+    TogglCsv,
+    // Generic Pomodoro log with start/end or start/duration columns
+    PomodoroCsv,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    // Keep existing data and add rows whose ids are not known yet
+    Merge,
+    // Wipe all data first; only supported for archives
+    Replace,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ImportRequest {
+    pub path: String,
+    pub format: Option<ImportFormat>,
+    pub mode: Option<ImportMode>,
+    // Owner of sessions read from CSV files
+    pub user_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ImportTableReport {
+    pub table: String,
+    pub inserted: u32,
+    pub skipped: u32,
+    pub conflicting: u32,
+}
+
+impl ImportTableReport {
+    pub fn new(table: &str) -> Self {
+        ImportTableReport {
+            table: table.to_string(),
+            inserted: 0,
+            skipped: 0,
+            conflicting: 0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ImportReport {
+    pub format: ImportFormat,
+    pub mode: ImportMode,
+    pub tables: Vec<ImportTableReport>,
+    // Why individual rows were skipped or conflicted
+    pub messages: Vec<String>,
+}
+
+impl ImportReport {
+    pub fn new(format: ImportFormat, mode: ImportMode) -> Self {
+        ImportReport {
+            format,
+            mode,
+            tables: Vec::new(),
+            messages: Vec::new(),
+        }
+    }
+
+    /// Counts the outcome of inserting one row.
+    /// Constraint failures (unique, foreign key, check) mark the row as conflicting; other errors abort the import.
+    pub fn record<T>(&mut self, table: &mut ImportTableReport, id: &str, result: Result<T, sqlx::Error>) -> Result<(), TimeboxError> {
+        match result {
+            Ok(_) => table.inserted += 1,
+            Err(e) => match TimeboxError::from(e) {
+                TimeboxError::Conflict(message) | TimeboxError::Validation(message) => {
+                    table.conflicting += 1;
+                    self.messages.push(format!("{} {}: {}", table.table, id, message));
+                }
+                e => return Err(e),
+            },
+        }
+        Ok(())
+    }
+}
+
+// A session read from a third-party CSV file, not yet stored
+pub struct ImportedSession {
+    pub line: usize,
+    pub task_title: Option<String>,
+    pub session_type: SessionType,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+}
+
+/// Reads the file named in `req` and imports it in a single transaction.
+pub async fn import_file(db: &Database, req: &ImportRequest) -> Result<ImportReport, TimeboxError> {
+    let path = Path::new(&req.path);
+    let format = match req.format {
+        Some(format) => format,
+        None => detect_format(path)?,
+    };
+    let mode = req.mode.unwrap_or(ImportMode::Merge);
+
+    if format == ImportFormat::Archive {
+        let archive: ExportArchive = serde_json::from_slice(&fs::read(path)?)
+            .map_err(|e| TimeboxError::Validation(format!("Not a TimeBox archive: {}", e)))?;
+
+        if archive.format != ARCHIVE_FORMAT {
+            return Err(TimeboxError::Validation(format!("Not a TimeBox archive: format is {}", archive.format)));
+        }
+        if archive.version > ARCHIVE_VERSION {
+            return Err(TimeboxError::Validation(format!(
+                "Archive version {} is newer than this app supports ({})",
+                archive.version, ARCHIVE_VERSION
+            )));
+        }
+
+        return db.import_snapshot(&archive.data, ImportReport::new(format, mode)).await;
+    }
+
+    if mode == ImportMode::Replace {
+        return Err(TimeboxError::Validation("Replace mode is only supported for TimeBox archives".to_string()));
+    }
+
+    let user_id = req.user_id.clone().unwrap_or_else(|| "default_user".to_string());
+    let tz = db.get_user_timezone(&user_id).await?;
+
+    let (sessions, unreadable) = read_sessions_csv(path, format, tz)?;
+    let mut report = db.import_manual_sessions(&user_id, &sessions, ImportReport::new(format, mode)).await?;
+
+    if let Some(table) = report.tables.iter_mut().find(|t| t.table == "pomodoro_sessions") {
+        table.skipped += unreadable.len() as u32;
+    }
+    report.messages.extend(unreadable);
+
+    Ok(report)
+}
+
+fn detect_format(path: &Path) -> Result<ImportFormat, TimeboxError> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    if extension == "json" {
+        return Ok(ImportFormat::Archive);
+    }
+
+    let mut reader = csv::Reader::from_path(path).map_err(std::io::Error::from)?;
+    let headers = normalized_headers(reader.headers().map_err(std::io::Error::from)?);
+    if headers.contains_key("start_date") && headers.contains_key("start_time") {
+        Ok(ImportFormat::TogglCsv)
+    } else {
+        Ok(ImportFormat::PomodoroCsv)
+    }
+}
+
+// Lowercased, underscore-separated header names mapped to their column index
+fn normalized_headers(headers: &csv::StringRecord) -> HashMap<String, usize> {
+    headers
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (name.trim().to_lowercase().replace([' ', '-'], "_"), index))
+        .collect()
+}
+
+/// Parses the rows of a CSV file into sessions. Rows that can't be read are reported rather than failing the import.
+fn read_sessions_csv(path: &Path, format: ImportFormat, tz: Tz) -> Result<(Vec<ImportedSession>, Vec<String>), TimeboxError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(std::io::Error::from)?;
+    let headers = normalized_headers(reader.headers().map_err(std::io::Error::from)?);
+
+    let mut sessions = Vec::new();
+    let mut unreadable = Vec::new();
+
+    for (index, record) in reader.records().enumerate() {
+        // Line 1 is the header
+        let line = index + 2;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                unreadable.push(format!("line {}: {}", line, e));
+                continue;
+            }
+        };
+
+        let column = |names: &[&str]| {
+            names.iter()
+                .filter_map(|name| headers.get(*name))
+                .filter_map(|index| record.get(*index))
+                .map(str::trim)
+                .find(|value| !value.is_empty())
+        };
+
+        let parsed = match format {
+            ImportFormat::TogglCsv => parse_toggl_row(&column, tz),
+            _ => parse_pomodoro_row(&column, tz),
+        };
+
+        match parsed {
+            Ok((task_title, session_type, start_time, end_time)) if end_time > start_time => sessions.push(ImportedSession {
+                line,
+                task_title,
+                session_type,
+                start_time,
+                end_time,
+            }),
+            Ok(_) => unreadable.push(format!("line {}: end time is not after start time", line)),
+            Err(message) => unreadable.push(format!("line {}: {}", line, message)),
+        }
+    }
+
+    Ok((sessions, unreadable))
+}
+
+type ParsedRow = (Option<String>, SessionType, DateTime<Utc>, DateTime<Utc>);
+
+fn parse_toggl_row<'a>(column: &impl Fn(&[&str]) -> Option<&'a str>, tz: Tz) -> Result<ParsedRow, String> {
+    let start_date = column(&["start_date"]).ok_or("missing start date")?;
+    let start_time = column(&["start_time"]).ok_or("missing start time")?;
+    let start = parse_local(tz, start_date, start_time)?;
+
+    let end = match (column(&["end_date"]), column(&["end_time"])) {
+        (Some(end_date), Some(end_time)) => parse_local(tz, end_date, end_time)?,
+        _ => add_duration(start, column(&["duration"]).ok_or("missing end time and duration")?, 60)?,
+    };
+
+    let title = column(&["description", "task", "project"]).map(str::to_string);
+
+    Ok((title, SessionType::Focus, start, end))
+}
+
+fn parse_pomodoro_row<'a>(column: &impl Fn(&[&str]) -> Option<&'a str>, tz: Tz) -> Result<ParsedRow, String> {
+    let start = parse_datetime(
+        column(&["start", "start_time", "started_at", "start_date", "date"]).ok_or("missing start")?,
+        tz,
+    )?;
+
+    let end = match column(&["end", "end_time", "ended_at", "finished_at", "completed_at"]) {
+        Some(end) => parse_datetime(end, tz)?,
+        None => match column(&["duration_seconds", "seconds"]) {
+            Some(seconds) => add_duration(start, seconds, 1)?,
+            None => add_duration(
+                start,
+                column(&["duration_minutes", "minutes", "duration"]).ok_or("missing end and duration")?,
+                60,
+            )?,
+        },
+    };
+
+    let title = column(&["description", "task", "task_title", "title", "name", "label"]).map(str::to_string);
+
+    let session_type = match column(&["session_type", "type", "kind", "phase"]).map(str::to_lowercase) {
+        Some(kind) if kind.contains("long") => SessionType::LongBreak,
+        Some(kind) if kind.contains("break") || kind.contains("short") || kind.contains("rest") => SessionType::ShortBreak,
+        _ => SessionType::Focus,
+    };
+
+    Ok((title, session_type, start, end))
+}
+
+// RFC 3339 timestamps, Unix seconds, or a local "YYYY-MM-DD HH:MM[:SS]" in the user's timezone
+pub fn parse_datetime(value: &str, tz: Tz) -> Result<DateTime<Utc>, String> {
+    if let Ok(instant) = DateTime::parse_from_rfc3339(value) {
+        return Ok(instant.with_timezone(&Utc));
+    }
+    if let Ok(seconds) = value.parse::<i64>() {
+        return DateTime::from_timestamp(seconds, 0).ok_or_else(|| format!("invalid timestamp {}", value));
+    }
+
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|pattern| NaiveDateTime::parse_from_str(value, pattern).ok())
+        .ok_or_else(|| format!("unrecognized date and time {}", value))
+        .and_then(|naive| to_utc(tz, naive))
+}
+
+fn parse_local(tz: Tz, date: &str, time: &str) -> Result<DateTime<Utc>, String> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("unrecognized date {}", date))?;
+    let time = NaiveTime::parse_from_str(time, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+        .map_err(|_| format!("unrecognized time {}", time))?;
+
+    to_utc(tz, date.and_time(time))
+}
+
+fn to_utc(tz: Tz, naive: NaiveDateTime) -> Result<DateTime<Utc>, String> {
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+        .ok_or_else(|| format!("{} does not exist in {}", naive, tz))
+}
+
+fn add_duration(start: DateTime<Utc>, value: &str, unit_seconds: i64) -> Result<DateTime<Utc>, String> {
+    start.checked_add_signed(parse_duration(value, unit_seconds)?)
+        .ok_or_else(|| format!("duration {} ends out of range", value))
+}
+
+// "HH:MM:SS", "MM:SS", or a plain number of `unit_seconds`
+fn parse_duration(value: &str, unit_seconds: i64) -> Result<Duration, String> {
+    let too_long = || format!("duration {} is too long", value);
+    let parts: Vec<&str> = value.split(':').collect();
+    let seconds = match parts.as_slice() {
+        [hours, minutes, seconds] => sum_parts(value, &[(hours, 3600), (minutes, 60), (seconds, 1)])?,
+        [minutes, seconds] => sum_parts(value, &[(minutes, 60), (seconds, 1)])?,
+        _ => {
+            let seconds = value.parse::<f64>()
+                .ok()
+                .filter(|amount| amount.is_finite())
+                .ok_or_else(|| format!("unrecognized duration {}", value))?
+                * unit_seconds as f64;
+            // `as` would saturate out-of-range values instead of failing
+            if seconds.abs() >= i64::MAX as f64 {
+                return Err(too_long());
+            }
+            seconds as i64
+        }
+    };
+
+    Duration::try_seconds(seconds).ok_or_else(too_long)
+}
+
+// Total seconds of the (part, seconds per unit) pairs of a clock-style duration
+fn sum_parts(value: &str, parts: &[(&&str, i64)]) -> Result<i64, String> {
+    parts.iter().try_fold(0i64, |total, (part, unit)| {
+        parse_number(part)?
+            .checked_mul(*unit)
+            .and_then(|seconds| seconds.checked_add(total))
+            .ok_or_else(|| format!("duration {} is too long", value))
+    })
+}
+
+fn parse_number(value: &str) -> Result<i64, String> {
+    value.trim().parse().map_err(|_| format!("unrecognized duration part {}", value))
+}
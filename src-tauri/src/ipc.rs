@@ -0,0 +1,298 @@
+use std::fs;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast;
+
+use crate::commands::{
+    AppState, RecordInterruptionRequest, StartSessionRequest, begin_session, build_timer_status, pause_active_session, record_active_interruption,
+    resume_active_session, stop_active_session, sync_with_database,
+};
+use crate::cycle;
+use crate::database::ProjectTagFilter;
+use crate::error::TimeboxError;
+use crate::timer_engine::TimerEvent;
+
+pub const SOCKET_FILE: &str = "timebox.sock";
+
+// JSON-RPC 2.0 error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+// Failures of the command itself; `data` carries the TimeboxError as `{ code, message, details }`
+const COMMAND_ERROR: i64 = -32000;
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    jsonrpc: Option<String>,
+    // Requests without an id are notifications and get no response
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError { code, message: message.into(), data: None }
+    }
+}
+
+impl From<TimeboxError> for RpcError {
+    fn from(e: TimeboxError) -> Self {
+        RpcError {
+            code: COMMAND_ERROR,
+            message: e.to_string(),
+            data: serde_json::to_value(&e).ok(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct UserParams {
+    user_id: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct TaskParams {
+    user_id: Option<String>,
+    #[serde(flatten)]
+    filter: ProjectTagFilter,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ReasonParams {
+    reason: Option<String>,
+}
+
+pub fn socket_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SOCKET_FILE)
+}
+
+/// Listens on the control socket in the data directory so editors and status bars can drive the timer.
+/// Each line on the socket is a JSON-RPC 2.0 request; `subscribe` additionally streams timer events.
+pub async fn spawn(
+    state: Arc<AppState>,
+    events: broadcast::Sender<TimerEvent>,
+    data_dir: PathBuf,
+) -> Result<(), TimeboxError> {
+    let path = socket_path(&data_dir);
+    let listener = bind(&path).await?;
+    // The socket is created by this process, so its owner is the user the app runs as
+    let owner_uid = fs::metadata(&path)?.uid();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    eprintln!("Control socket accept failed: {}", e);
+                    continue;
+                }
+            };
+
+            match stream.peer_cred() {
+                Ok(cred) if cred.uid() == owner_uid => {}
+                Ok(cred) => {
+                    eprintln!("Rejected control socket connection from uid {}", cred.uid());
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("Could not identify control socket peer: {}", e);
+                    continue;
+                }
+            }
+
+            tauri::async_runtime::spawn(handle_connection(stream, state.clone(), events.clone()));
+        }
+    });
+
+    Ok(())
+}
+
+/// Removes the socket file when the app exits.
+pub fn remove_socket(data_dir: &Path) {
+    let _ = fs::remove_file(socket_path(data_dir));
+}
+
+async fn bind(path: &Path) -> Result<UnixListener, TimeboxError> {
+    if path.exists() {
+        // A socket that still accepts connections belongs to another running instance
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(TimeboxError::Conflict(format!("{} is in use by another TimeBox instance", path.display())));
+        }
+        // Otherwise it was left behind by a crash
+        fs::remove_file(path)?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    // Only the owner may connect; peer credentials are checked again on accept
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+
+    Ok(listener)
+}
+
+async fn handle_connection(stream: UnixStream, state: Arc<AppState>, events: broadcast::Sender<TimerEvent>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    // Set by `subscribe`, so clients only receive events from that point on
+    let mut subscription: Option<broadcast::Receiver<TimerEvent>> = None;
+
+    loop {
+        let message = tokio::select! {
+            line = lines.next_line() => match line {
+                Ok(Some(line)) if line.trim().is_empty() => continue,
+                Ok(Some(line)) => match handle_line(&state, &line, &events, &mut subscription).await {
+                    Some(response) => response,
+                    None => continue,
+                },
+                // Disconnected or unreadable
+                _ => break,
+            },
+            event = recv_event(&mut subscription), if subscription.is_some() => match event {
+                Ok(event) => json!({ "jsonrpc": "2.0", "method": event.name, "params": event.payload }),
+                // Slow readers skip the ticks they missed; the next one carries the full status anyway
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+
+        let mut bytes = message.to_string().into_bytes();
+        bytes.push(b'\n');
+        if writer.write_all(&bytes).await.is_err() {
+            break;
+        }
+    }
+}
+
+// Parses and runs one request line; returns the response to write, if any
+async fn handle_line(
+    state: &AppState,
+    line: &str,
+    events: &broadcast::Sender<TimerEvent>,
+    subscription: &mut Option<broadcast::Receiver<TimerEvent>>,
+) -> Option<Value> {
+    let request: RpcRequest = match serde_json::from_str::<Value>(line) {
+        Err(e) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string()))),
+        Ok(value) => {
+            let id = value.get("id").cloned().unwrap_or(Value::Null);
+            match serde_json::from_value(value) {
+                Ok(request) => request,
+                Err(e) => return Some(error_response(id, RpcError::new(INVALID_REQUEST, e.to_string()))),
+            }
+        }
+    };
+
+    let id = request.id.clone();
+    if request.jsonrpc.as_deref() != Some("2.0") {
+        return Some(error_response(id.unwrap_or(Value::Null), RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\"")));
+    }
+
+    let result = match request.method.as_str() {
+        "subscribe" => {
+            if subscription.is_none() {
+                *subscription = Some(events.subscribe());
+            }
+            Ok(Value::Bool(true))
+        }
+        "unsubscribe" => {
+            *subscription = None;
+            Ok(Value::Bool(true))
+        }
+        method => dispatch(state, method, request.params).await,
+    };
+
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => error_response(id, error),
+    })
+}
+
+async fn recv_event(subscription: &mut Option<broadcast::Receiver<TimerEvent>>) -> Result<TimerEvent, broadcast::error::RecvError> {
+    match subscription {
+        Some(events) => events.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": error })
+}
+
+// Maps a method onto the command of the same name
+async fn dispatch(state: &AppState, method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        "get_timer_status" => {
+            let mut active_session = state.active_session.write().await;
+            sync_with_database(&state.db, &mut active_session).await?;
+            to_result(build_timer_status(&state.db, active_session.as_ref()).await)
+        }
+        "has_active_session" => {
+            let mut active_session = state.active_session.write().await;
+            sync_with_database(&state.db, &mut active_session).await?;
+            to_result(active_session.is_some())
+        }
+        "start_session" => {
+            let req: StartSessionRequest = parse_params(params)?;
+            to_result(begin_session(state, req).await?)
+        }
+        "pause_session" => {
+            let params: ReasonParams = parse_params(params)?;
+            to_result(pause_active_session(state, params.reason).await?)
+        }
+        "resume_session" => to_result(resume_active_session(state).await?),
+        "stop_session" => {
+            let params: ReasonParams = parse_params(params)?;
+            to_result(stop_active_session(state, params.reason).await?)
+        }
+        "record_interruption" => {
+            let req: RecordInterruptionRequest = parse_params(params)?;
+            to_result(record_active_interruption(state, req).await?)
+        }
+        "get_cycle_state" => {
+            let user_id = parse_params::<UserParams>(params)?.user_id.unwrap_or_else(|| "default_user".to_string());
+            to_result(cycle::current_cycle_state(&state.db, &user_id).await?)
+        }
+        "get_settings" => {
+            let user_id = parse_params::<UserParams>(params)?.user_id.unwrap_or_else(|| "default_user".to_string());
+            to_result(state.db.get_or_create_settings(&user_id).await?)
+        }
+        "get_tasks" => {
+            let params: TaskParams = parse_params(params)?;
+            let user_id = params.user_id.unwrap_or_else(|| "default_user".to_string());
+            to_result(state.db.get_tasks(&user_id, &params.filter).await?)
+        }
+        "get_today_sessions" => {
+            let user_id = parse_params::<UserParams>(params)?.user_id.unwrap_or_else(|| "default_user".to_string());
+            to_result(state.db.get_today_sessions(&user_id).await?)
+        }
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method {}", method))),
+    }
+}
+
+// Omitted params are treated as an empty object so every field falls back to its default
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn to_result<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::new(COMMAND_ERROR, e.to_string()))
+}
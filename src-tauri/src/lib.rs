@@ -1,14 +1,28 @@
+mod backup;
+pub mod cli;
 mod database;
 mod commands;
+mod cycle;
+mod error;
+mod export;
+mod http_api;
+mod importer;
+#[cfg(unix)]
+mod ipc;
+mod migrations;
+mod recurrence;
+mod timer_engine;
+mod timezone;
 
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 
 use tauri::Manager;
 use database::Database;
-use commands::{AppState, initialize_app, start_session, pause_session, resume_session, stop_session, has_active_session, save_active_session, get_timer_status, get_settings, update_settings, create_task, get_tasks, get_sessions, get_today_sessions, create_goal, get_goals, record_interruption, update_task, delete_task, get_tasks_with_pomodoro_counts, update_goal, delete_goal, get_sessions_by_date_range, log_manual_session, save_daily_reflection, get_daily_reflection, get_reflections_by_month, get_day_activities};
+use http_api::HttpApi;
+use commands::{AppState, restore_active_session, initialize_app, start_session, pause_session, resume_session, stop_session, has_active_session, save_active_session, get_timer_status, get_cycle_state, get_settings, update_settings, set_user_timezone, create_task, get_tasks, reorder_tasks, get_daily_plan, get_upcoming_occurrences, get_sessions, get_today_sessions, create_goal, get_goals, record_interruption, update_task, delete_task, assign_task_to_goal, assign_task_to_project, set_task_tags, set_task_parent, get_checklist_items, add_checklist_item, update_checklist_item, reorder_checklist_items, delete_checklist_item, get_tasks_with_pomodoro_counts, update_goal, delete_goal, get_goal_progress, get_sessions_by_date_range, get_session_pauses, get_focus_stats, get_streaks, get_interruptions, get_interruption_sources, create_project, get_projects, update_project, delete_project, create_tag, get_tags, rename_tag, delete_tag, log_manual_session, update_session, delete_session, split_session, merge_sessions, get_session_audit, save_daily_reflection, get_daily_reflection, get_reflections_by_month, get_day_activities, export_data, import_data, create_backup, list_backups, restore_backup, get_api_settings, update_api_settings, regenerate_api_token};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -21,35 +35,62 @@ pub fn run() {
             tauri::async_runtime::block_on(async {
                 let app_dir = app.path().app_data_dir()
                     .expect("Failed to get app data directory");
-                
-                // Create the directory if it doesn't exist
-                if !app_dir.exists() {
-                    std::fs::create_dir_all(&app_dir)
-                        .expect("Failed to create app data directory");
-                }
-                
-                let db_path = app_dir.join("timebox.db");
-                let db_path_str = db_path.to_str().expect("Invalid path");
-                
-                // Create the database file if it doesn't exist
-                // SQLx requires the file to exist before connecting
-                if !db_path.exists() {
-                    std::fs::File::create(&db_path)
-                        .expect("Failed to create database file");
+
+                // Swap in a backup chosen with restore_backup before anything opens the database
+                match backup::apply_pending_restore(&app_dir) {
+                    Ok(true) => println!("Restored database from backup"),
+                    Ok(false) => {}
+                    Err(e) => eprintln!("Failed to restore backup: {}", e),
                 }
-                
-                // Use absolute path with sqlite:// protocol (3 slashes for absolute paths)
-                let db_url = format!("sqlite://{}", db_path_str);
-                
-                let db = Database::new(&db_url).await
+
+                let db = Database::open(&app_dir.join(backup::DATABASE_FILE)).await
                     .expect("Failed to initialize database");
 
+                // Use the system timezone for users that have not picked one yet
+                if let Err(e) = db.apply_detected_timezone(&timezone::detect_system_timezone()).await {
+                    eprintln!("Failed to apply detected timezone: {}", e);
+                }
+
+                // Resume a timer left running by a previous run and close out orphaned sessions
+                let active_session = restore_active_session(&db).await
+                    .expect("Failed to restore active session");
+
                 let app_state = Arc::new(AppState {
                     db: Arc::new(db),
-                    active_session: RwLock::new(None),
+                    active_session: RwLock::new(active_session),
+                    timer_notify: Notify::new(),
                 });
 
-                app.manage(app_state);
+                app.manage(app_state.clone());
+
+                // Back up the database periodically
+                backup::spawn_scheduler(app_state.db.clone(), app_dir.clone());
+
+                // Create the next instances of routines as their days start
+                recurrence::spawn_scheduler(app_state.db.clone());
+
+                let timer_events = timer_engine::forward_events(app.handle());
+
+                // Let editors and status bars drive the timer over a local socket
+                #[cfg(unix)]
+                if let Err(e) = ipc::spawn(app_state.clone(), timer_events.clone(), app_dir.clone()).await {
+                    eprintln!("Failed to start control socket: {}", e);
+                }
+
+                // Serve the local HTTP API when the user has turned it on
+                let http_api = HttpApi::new(timer_events);
+                match app_state.db.get_api_settings().await {
+                    Ok(settings) => {
+                        if let Err(e) = http_api.apply(app_state.clone(), &settings).await {
+                            eprintln!("Failed to start HTTP API: {}", e);
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to load HTTP API settings: {}", e),
+                }
+                app.manage(http_api);
+
+                // Drive countdowns, completion and timer events from the backend
+                timer_engine::spawn(app.handle().clone(), app_state);
             });
 
             // Get window references
@@ -79,10 +120,15 @@ pub fn run() {
             has_active_session,
             save_active_session,
             get_timer_status,
+            get_cycle_state,
             get_settings,
             update_settings,
+            set_user_timezone,
             create_task,
             get_tasks,
+            reorder_tasks,
+            get_daily_plan,
+            get_upcoming_occurrences,
             get_sessions,
             get_today_sessions,
             create_goal,
@@ -90,16 +136,66 @@ pub fn run() {
             record_interruption,
             update_task,
             delete_task,
+            assign_task_to_goal,
+            assign_task_to_project,
+            set_task_tags,
+            set_task_parent,
+            get_checklist_items,
+            add_checklist_item,
+            update_checklist_item,
+            reorder_checklist_items,
+            delete_checklist_item,
             get_tasks_with_pomodoro_counts,
             update_goal,
             delete_goal,
+            get_goal_progress,
             get_sessions_by_date_range,
+            get_session_pauses,
+            get_focus_stats,
+            get_streaks,
+            get_interruptions,
+            get_interruption_sources,
+            create_project,
+            get_projects,
+            update_project,
+            delete_project,
+            create_tag,
+            get_tags,
+            rename_tag,
+            delete_tag,
             log_manual_session,
+            update_session,
+            delete_session,
+            split_session,
+            merge_sessions,
+            get_session_audit,
             save_daily_reflection,
             get_daily_reflection,
             get_reflections_by_month,
-            get_day_activities
+            get_day_activities,
+            export_data,
+            import_data,
+            create_backup,
+            list_backups,
+            restore_backup,
+            get_api_settings,
+            update_api_settings,
+            regenerate_api_token
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app_handle, event| {
+            // Remove the control socket and take a last backup on the way out
+            if let tauri::RunEvent::Exit = event {
+                let state = app_handle.state::<Arc<AppState>>();
+                if let Ok(app_dir) = app_handle.path().app_data_dir() {
+                    #[cfg(unix)]
+                    ipc::remove_socket(&app_dir);
+
+                    if let Err(e) = tauri::async_runtime::block_on(backup::create_backup(&state.db, &app_dir, backup::BackupKind::Exit)) {
+                        eprintln!("Backup on exit failed: {}", e);
+                    }
+                }
+            }
+        });
 }
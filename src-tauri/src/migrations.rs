@@ -0,0 +1,646 @@
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+// A single schema change. `AddColumn` is skipped when the column already exists, which lets
+// databases created by the old ad hoc `ALTER TABLE` scheme be brought up to date safely.
+enum Step {
+    Sql(&'static str),
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+}
+
+struct Migration {
+    version: i64,
+    description: &'static str,
+    steps: &'static [Step],
+}
+
+// Append new migrations to the end; never edit one that has already shipped.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema (v0.1.3)",
+        steps: &[
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS users (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    timezone TEXT DEFAULT 'UTC',
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )
+                "#,
+            ),
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS pomodoro_settings (
+                    user_id TEXT PRIMARY KEY,
+                    focus_minutes INTEGER DEFAULT 25,
+                    short_break_minutes INTEGER DEFAULT 5,
+                    long_break_minutes INTEGER DEFAULT 15,
+                    cycles_before_long_break INTEGER DEFAULT 4,
+                    strict_mode BOOLEAN DEFAULT 0,
+                    auto_start_breaks BOOLEAN DEFAULT 0,
+                    sound_enabled BOOLEAN DEFAULT 1,
+                    sound_volume INTEGER DEFAULT 70,
+                    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+                )
+                "#,
+            ),
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS tasks (
+                    id TEXT PRIMARY KEY,
+                    user_id TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    estimated_pomodoros INTEGER DEFAULT 1,
+                    completed BOOLEAN DEFAULT 0,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+                )
+                "#,
+            ),
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS pomodoro_sessions (
+                    id TEXT PRIMARY KEY,
+                    user_id TEXT NOT NULL,
+                    task_id TEXT,
+                    session_type TEXT CHECK(session_type IN ('FOCUS', 'SHORT_BREAK', 'LONG_BREAK')) NOT NULL,
+                    start_time DATETIME NOT NULL,
+                    end_time DATETIME,
+                    duration_seconds INTEGER,
+                    interrupted BOOLEAN DEFAULT 0,
+                    interruption_count INTEGER DEFAULT 0,
+                    manual_override BOOLEAN DEFAULT 0,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+                    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE SET NULL
+                )
+                "#,
+            ),
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS goals (
+                    id TEXT PRIMARY KEY,
+                    user_id TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    target_pomodoros INTEGER NOT NULL,
+                    completed_pomodoros INTEGER DEFAULT 0,
+                    completed BOOLEAN DEFAULT 0,
+                    category TEXT,
+                    motivation TEXT,
+                    target_date DATETIME,
+                    description TEXT,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+                )
+                "#,
+            ),
+            // Columns added after the first releases, through ad hoc ALTER TABLE statements
+            Step::AddColumn { table: "goals", column: "category", definition: "TEXT" },
+            Step::AddColumn { table: "goals", column: "motivation", definition: "TEXT" },
+            Step::AddColumn { table: "goals", column: "target_date", definition: "DATETIME" },
+            Step::AddColumn { table: "goals", column: "description", definition: "TEXT" },
+            Step::AddColumn { table: "pomodoro_settings", column: "sound_enabled", definition: "BOOLEAN DEFAULT 1" },
+            Step::AddColumn { table: "pomodoro_settings", column: "sound_volume", definition: "INTEGER DEFAULT 70" },
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS daily_reflections (
+                    id TEXT PRIMARY KEY,
+                    user_id TEXT NOT NULL,
+                    reflection_date DATE NOT NULL,
+                    title TEXT,
+                    duration_reflection TEXT,
+                    purpose_reflection TEXT,
+                    general_notes TEXT,
+                    mood_rating INTEGER,
+                    productivity_rating INTEGER,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+                    UNIQUE(user_id, reflection_date)
+                )
+                "#,
+            ),
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "persisted active timer and strict mode stop reasons",
+        steps: &[
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS active_timer (
+                    id INTEGER PRIMARY KEY CHECK (id = 1),
+                    session_id TEXT NOT NULL,
+                    remaining_seconds INTEGER NOT NULL,
+                    total_seconds INTEGER NOT NULL,
+                    is_paused BOOLEAN DEFAULT 0,
+                    anchor_time DATETIME NOT NULL,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (session_id) REFERENCES pomodoro_sessions(id) ON DELETE CASCADE
+                )
+                "#,
+            ),
+            Step::AddColumn { table: "pomodoro_sessions", column: "stop_reason", definition: "TEXT" },
+        ],
+    },
+    Migration {
+        version: 3,
+        description: "track where each user's timezone came from",
+        steps: &[
+            // 'default' until detected from the system, 'user' once chosen explicitly
+            Step::AddColumn { table: "users", column: "timezone_source", definition: "TEXT DEFAULT 'default'" },
+        ],
+    },
+    Migration {
+        version: 4,
+        description: "link tasks to goals",
+        steps: &[
+            Step::AddColumn {
+                table: "tasks",
+                column: "goal_id",
+                definition: "TEXT REFERENCES goals(id) ON DELETE SET NULL",
+            },
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_tasks_goal ON tasks(goal_id)"),
+        ],
+    },
+    Migration {
+        version: 5,
+        description: "local HTTP API settings",
+        steps: &[
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS api_settings (
+                    id INTEGER PRIMARY KEY CHECK (id = 1),
+                    enabled BOOLEAN DEFAULT 0,
+                    port INTEGER NOT NULL,
+                    token TEXT NOT NULL,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )
+                "#,
+            ),
+        ],
+    },
+    Migration {
+        version: 6,
+        description: "index sessions for range queries",
+        steps: &[
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_sessions_user_start ON pomodoro_sessions(user_id, start_time)"),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_sessions_task ON pomodoro_sessions(task_id)"),
+        ],
+    },
+    Migration {
+        version: 7,
+        description: "daily focus targets",
+        steps: &[
+            Step::AddColumn { table: "pomodoro_settings", column: "daily_target_unit", definition: "TEXT DEFAULT 'pomodoros'" },
+            Step::AddColumn { table: "pomodoro_settings", column: "daily_target", definition: "INTEGER DEFAULT 0" },
+            Step::AddColumn { table: "pomodoro_settings", column: "rest_days", definition: "TEXT DEFAULT ''" },
+        ],
+    },
+    Migration {
+        version: 8,
+        description: "session pause intervals",
+        steps: &[
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS session_pauses (
+                    id TEXT PRIMARY KEY,
+                    session_id TEXT NOT NULL,
+                    paused_at DATETIME NOT NULL,
+                    resumed_at DATETIME,
+                    reason TEXT,
+                    FOREIGN KEY (session_id) REFERENCES pomodoro_sessions(id) ON DELETE CASCADE
+                )
+                "#,
+            ),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_session_pauses_session ON session_pauses(session_id)"),
+            Step::AddColumn { table: "pomodoro_sessions", column: "pause_count", definition: "INTEGER DEFAULT 0" },
+            Step::AddColumn { table: "pomodoro_sessions", column: "paused_seconds", definition: "INTEGER DEFAULT 0" },
+        ],
+    },
+    Migration {
+        version: 9,
+        description: "interruption log",
+        steps: &[
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS session_interruptions (
+                    id TEXT PRIMARY KEY,
+                    session_id TEXT NOT NULL,
+                    user_id TEXT NOT NULL,
+                    occurred_at DATETIME NOT NULL,
+                    kind TEXT CHECK(kind IN ('internal', 'external')) NOT NULL,
+                    reason TEXT,
+                    category TEXT,
+                    FOREIGN KEY (session_id) REFERENCES pomodoro_sessions(id) ON DELETE CASCADE,
+                    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+                )
+                "#,
+            ),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_interruptions_user_time ON session_interruptions(user_id, occurred_at)"),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_interruptions_session ON session_interruptions(session_id)"),
+        ],
+    },
+    Migration {
+        version: 10,
+        description: "session history audit trail",
+        steps: &[
+            // No foreign key on session_id, so entries outlive the sessions they describe
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS session_audit (
+                    id TEXT PRIMARY KEY,
+                    session_id TEXT NOT NULL,
+                    user_id TEXT NOT NULL,
+                    action TEXT CHECK(action IN ('edit', 'delete', 'split', 'merge')) NOT NULL,
+                    before_data TEXT NOT NULL,
+                    after_data TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+                )
+                "#,
+            ),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_session_audit_user ON session_audit(user_id, created_at)"),
+        ],
+    },
+    Migration {
+        version: 11,
+        description: "projects and tags",
+        steps: &[
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS projects (
+                    id TEXT PRIMARY KEY,
+                    user_id TEXT NOT NULL,
+                    name TEXT NOT NULL COLLATE NOCASE,
+                    color TEXT,
+                    archived BOOLEAN DEFAULT 0,
+                    hourly_rate REAL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+                    UNIQUE(user_id, name)
+                )
+                "#,
+            ),
+            Step::AddColumn {
+                table: "tasks",
+                column: "project_id",
+                definition: "TEXT REFERENCES projects(id) ON DELETE SET NULL",
+            },
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_tasks_project ON tasks(project_id)"),
+            // Tag names, like project names, are unique per user regardless of case
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS tags (
+                    id TEXT PRIMARY KEY,
+                    user_id TEXT NOT NULL,
+                    name TEXT NOT NULL COLLATE NOCASE,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+                    UNIQUE(user_id, name)
+                )
+                "#,
+            ),
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS task_tags (
+                    task_id TEXT NOT NULL,
+                    tag_id TEXT NOT NULL,
+                    PRIMARY KEY (task_id, tag_id),
+                    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+                    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+                )
+                "#,
+            ),
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS session_tags (
+                    session_id TEXT NOT NULL,
+                    tag_id TEXT NOT NULL,
+                    PRIMARY KEY (session_id, tag_id),
+                    FOREIGN KEY (session_id) REFERENCES pomodoro_sessions(id) ON DELETE CASCADE,
+                    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+                )
+                "#,
+            ),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_task_tags_tag ON task_tags(tag_id)"),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_session_tags_tag ON session_tags(tag_id)"),
+        ],
+    },
+    Migration {
+        version: 12,
+        description: "subtasks and checklists",
+        steps: &[
+            // Deleting a task deletes its subtasks with it
+            Step::AddColumn {
+                table: "tasks",
+                column: "parent_task_id",
+                definition: "TEXT REFERENCES tasks(id) ON DELETE CASCADE",
+            },
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_tasks_parent ON tasks(parent_task_id)"),
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS checklist_items (
+                    id TEXT PRIMARY KEY,
+                    task_id TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    completed BOOLEAN DEFAULT 0,
+                    position INTEGER NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+                )
+                "#,
+            ),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_checklist_items_task ON checklist_items(task_id, position)"),
+        ],
+    },
+    Migration {
+        version: 13,
+        description: "task due dates, priorities and planning",
+        steps: &[
+            // Dates are local to the user, stored as YYYY-MM-DD
+            Step::AddColumn { table: "tasks", column: "due_date", definition: "TEXT" },
+            // 0 = none, 1 = low, 2 = medium, 3 = high
+            Step::AddColumn { table: "tasks", column: "priority", definition: "INTEGER NOT NULL DEFAULT 0" },
+            Step::AddColumn { table: "tasks", column: "position", definition: "INTEGER NOT NULL DEFAULT 0" },
+            Step::AddColumn { table: "tasks", column: "planned_for", definition: "TEXT" },
+            // Keeps the newest-first order tasks were listed in before positions existed
+            Step::Sql(
+                r#"
+                UPDATE tasks SET position = (
+                    SELECT COUNT(*) FROM tasks newer
+                    WHERE newer.user_id = tasks.user_id
+                    AND (newer.created_at > tasks.created_at OR (newer.created_at = tasks.created_at AND newer.id < tasks.id))
+                )
+                "#,
+            ),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_tasks_planned ON tasks(user_id, planned_for)"),
+        ],
+    },
+    Migration {
+        version: 14,
+        description: "task completion times",
+        steps: &[
+            Step::AddColumn { table: "tasks", column: "completed_at", definition: "DATETIME" },
+            // Best guess for tasks completed before this was recorded: the end of their last
+            // session, or failing that when they were created
+            Step::Sql(
+                r#"
+                UPDATE tasks SET completed_at = COALESCE(
+                    (SELECT MAX(ps.end_time) FROM pomodoro_sessions ps WHERE ps.task_id = tasks.id AND ps.end_time IS NOT NULL),
+                    created_at
+                )
+                WHERE completed = 1 AND completed_at IS NULL
+                "#,
+            ),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_tasks_completed_at ON tasks(user_id, completed_at)"),
+        ],
+    },
+    Migration {
+        version: 15,
+        description: "recurring tasks",
+        steps: &[
+            // Normalized RRULE; the task's due date is the occurrence it stands for
+            Step::AddColumn { table: "tasks", column: "recurrence", definition: "TEXT" },
+            // The previous instance of the routine, so each instance is followed at most once
+            Step::AddColumn {
+                table: "tasks",
+                column: "recurs_from",
+                definition: "TEXT REFERENCES tasks(id) ON DELETE SET NULL",
+            },
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_tasks_recurs_from ON tasks(recurs_from)"),
+        ],
+    },
+];
+
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+pub async fn current_version(pool: &Pool<Sqlite>) -> Result<i64, sqlx::Error> {
+    ensure_version_table(pool).await?;
+
+    let version: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM schema_version")
+        .fetch_one(pool)
+        .await?;
+
+    Ok(version.unwrap_or(0))
+}
+
+/// Applies every migration newer than the recorded schema version, each in its own transaction.
+pub async fn run(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    let current = current_version(pool).await?;
+
+    if current > latest_version() {
+        return Err(sqlx::Error::Configuration(
+            format!(
+                "Database schema version {} is newer than this app supports ({})",
+                current,
+                latest_version()
+            )
+            .into(),
+        ));
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let mut tx = pool.begin().await?;
+
+        for step in migration.steps {
+            apply_step(&mut tx, step).await?;
+        }
+
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES (?, ?)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+    }
+
+    Ok(())
+}
+
+async fn ensure_version_table(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn apply_step(conn: &mut SqliteConnection, step: &Step) -> Result<(), sqlx::Error> {
+    match step {
+        Step::Sql(sql) => {
+            sqlx::query(sql).execute(&mut *conn).await?;
+        }
+        Step::AddColumn { table, column, definition } => {
+            let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+                .bind(table)
+                .bind(column)
+                .fetch_one(&mut *conn)
+                .await?;
+
+            if exists == 0 {
+                let sql = format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition);
+                sqlx::query(&sql).execute(&mut *conn).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Row;
+    use crate::database::Database;
+
+    // Tables and data as the v0.1.3 release left them, before schema versioning existed
+    const V0_1_3_SCHEMA: &[&str] = &[
+        "CREATE TABLE users (id TEXT PRIMARY KEY, name TEXT NOT NULL, timezone TEXT DEFAULT 'UTC', created_at DATETIME DEFAULT CURRENT_TIMESTAMP)",
+        "CREATE TABLE pomodoro_settings (user_id TEXT PRIMARY KEY, focus_minutes INTEGER DEFAULT 25, short_break_minutes INTEGER DEFAULT 5, long_break_minutes INTEGER DEFAULT 15, cycles_before_long_break INTEGER DEFAULT 4, strict_mode BOOLEAN DEFAULT 0, auto_start_breaks BOOLEAN DEFAULT 0, sound_enabled BOOLEAN DEFAULT 1, sound_volume INTEGER DEFAULT 70, FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE)",
+        "CREATE TABLE tasks (id TEXT PRIMARY KEY, user_id TEXT NOT NULL, title TEXT NOT NULL, estimated_pomodoros INTEGER DEFAULT 1, completed BOOLEAN DEFAULT 0, created_at DATETIME DEFAULT CURRENT_TIMESTAMP, FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE)",
+        "CREATE TABLE pomodoro_sessions (id TEXT PRIMARY KEY, user_id TEXT NOT NULL, task_id TEXT, session_type TEXT CHECK(session_type IN ('FOCUS', 'SHORT_BREAK', 'LONG_BREAK')) NOT NULL, start_time DATETIME NOT NULL, end_time DATETIME, duration_seconds INTEGER, interrupted BOOLEAN DEFAULT 0, interruption_count INTEGER DEFAULT 0, manual_override BOOLEAN DEFAULT 0, created_at DATETIME DEFAULT CURRENT_TIMESTAMP, FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE, FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE SET NULL)",
+        "CREATE TABLE goals (id TEXT PRIMARY KEY, user_id TEXT NOT NULL, title TEXT NOT NULL, target_pomodoros INTEGER NOT NULL, completed_pomodoros INTEGER DEFAULT 0, completed BOOLEAN DEFAULT 0, category TEXT, motivation TEXT, target_date DATETIME, description TEXT, created_at DATETIME DEFAULT CURRENT_TIMESTAMP, FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE)",
+        "CREATE TABLE daily_reflections (id TEXT PRIMARY KEY, user_id TEXT NOT NULL, reflection_date DATE NOT NULL, title TEXT, duration_reflection TEXT, purpose_reflection TEXT, general_notes TEXT, mood_rating INTEGER, productivity_rating INTEGER, created_at DATETIME DEFAULT CURRENT_TIMESTAMP, updated_at DATETIME DEFAULT CURRENT_TIMESTAMP, FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE, UNIQUE(user_id, reflection_date))",
+        "INSERT INTO users (id, name) VALUES ('default_user', 'Default User')",
+        "INSERT INTO pomodoro_settings (user_id, focus_minutes, sound_volume) VALUES ('default_user', 50, 30)",
+        "INSERT INTO tasks (id, user_id, title) VALUES ('task-1', 'default_user', 'Write report')",
+        "INSERT INTO pomodoro_sessions (id, user_id, task_id, session_type, start_time, end_time, duration_seconds) VALUES ('session-1', 'default_user', 'task-1', 'FOCUS', '2025-01-06T09:00:00+00:00', '2025-01-06T09:25:00+00:00', 1500)",
+        "INSERT INTO goals (id, user_id, title, target_pomodoros, category) VALUES ('goal-1', 'default_user', 'Ship it', 10, 'Work')",
+        "INSERT INTO daily_reflections (id, user_id, reflection_date, title, mood_rating) VALUES ('reflection-1', 'default_user', '2025-01-06', 'Good day', 4)",
+    ];
+
+    // Early releases created goals and settings without the columns later added by ALTER TABLE
+    const PRE_ALTER_SCHEMA: &[&str] = &[
+        "CREATE TABLE users (id TEXT PRIMARY KEY, name TEXT NOT NULL, timezone TEXT DEFAULT 'UTC', created_at DATETIME DEFAULT CURRENT_TIMESTAMP)",
+        "CREATE TABLE pomodoro_settings (user_id TEXT PRIMARY KEY, focus_minutes INTEGER DEFAULT 25, short_break_minutes INTEGER DEFAULT 5, long_break_minutes INTEGER DEFAULT 15, cycles_before_long_break INTEGER DEFAULT 4, strict_mode BOOLEAN DEFAULT 0, auto_start_breaks BOOLEAN DEFAULT 0, FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE)",
+        "CREATE TABLE goals (id TEXT PRIMARY KEY, user_id TEXT NOT NULL, title TEXT NOT NULL, target_pomodoros INTEGER NOT NULL, completed_pomodoros INTEGER DEFAULT 0, completed BOOLEAN DEFAULT 0, created_at DATETIME DEFAULT CURRENT_TIMESTAMP, FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE)",
+        "INSERT INTO users (id, name) VALUES ('default_user', 'Default User')",
+        "INSERT INTO pomodoro_settings (user_id, focus_minutes) VALUES ('default_user', 45)",
+        "INSERT INTO goals (id, user_id, title, target_pomodoros) VALUES ('goal-1', 'default_user', 'Ship it', 10)",
+    ];
+
+    async fn legacy_database_url(schema: &[&str]) -> String {
+        let path = std::env::temp_dir().join(format!("timebox-migration-{}.db", uuid::Uuid::new_v4()));
+        std::fs::File::create(&path).expect("Failed to create database file");
+        let url = format!("sqlite://{}", path.to_str().expect("Invalid path"));
+
+        let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+        for sql in schema {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool.close().await;
+
+        url
+    }
+
+    #[tokio::test]
+    async fn migrates_v0_1_3_database_without_data_loss() {
+        let url = legacy_database_url(V0_1_3_SCHEMA).await;
+        let db = Database::new(&url).await.unwrap();
+
+        let settings = db.get_settings("default_user").await.unwrap().unwrap();
+        assert_eq!(settings.focus_minutes, 50);
+        assert_eq!(settings.sound_volume, 30);
+
+        let sessions = db.get_sessions("default_user", None).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].duration_seconds, Some(1500));
+        assert_eq!(sessions[0].task_title.as_deref(), Some("Write report"));
+
+        let goals = db.get_goals("default_user").await.unwrap();
+        assert_eq!(goals.len(), 1);
+        assert_eq!(goals[0].category.as_deref(), Some("Work"));
+
+        let reflections = db.get_reflections_by_month("default_user", 2025, 1).await.unwrap();
+        assert_eq!(reflections.len(), 1);
+        assert_eq!(reflections[0].mood_rating, Some(4));
+    }
+
+    #[tokio::test]
+    async fn adds_columns_missing_from_early_databases() {
+        let url = legacy_database_url(PRE_ALTER_SCHEMA).await;
+        let db = Database::new(&url).await.unwrap();
+
+        let settings = db.get_settings("default_user").await.unwrap().unwrap();
+        assert_eq!(settings.focus_minutes, 45);
+        assert!(settings.sound_enabled);
+        assert_eq!(settings.sound_volume, 70);
+
+        let goals = db.get_goals("default_user").await.unwrap();
+        assert_eq!(goals.len(), 1);
+        assert!(goals[0].category.is_none());
+    }
+
+    #[tokio::test]
+    async fn backfills_completion_times() {
+        let schema = [V0_1_3_SCHEMA, &[
+            "UPDATE tasks SET completed = 1 WHERE id = 'task-1'",
+            "INSERT INTO tasks (id, user_id, title, completed, created_at) VALUES ('task-2', 'default_user', 'Plan week', 1, '2025-01-03 08:00:00')",
+            "INSERT INTO tasks (id, user_id, title) VALUES ('task-3', 'default_user', 'Still open')",
+        ]].concat();
+        let url = legacy_database_url(&schema).await;
+        let db = Database::new(&url).await.unwrap();
+
+        // The end of the last session on the task, else its creation time
+        let expected = [
+            ("task-1", Some("2025-01-06T09:25:00+00:00")),
+            ("task-2", Some("2025-01-03T08:00:00+00:00")),
+            ("task-3", None),
+        ];
+        for (id, completed_at) in expected {
+            let task = db.get_task(id).await.unwrap().unwrap();
+            assert_eq!(task.completed_at.map(|at| at.to_rfc3339()).as_deref(), completed_at, "{}", id);
+        }
+    }
+
+    #[tokio::test]
+    async fn records_each_migration_once() {
+        let url = legacy_database_url(V0_1_3_SCHEMA).await;
+        Database::new(&url).await.unwrap();
+        // Reopening must not re-apply anything
+        Database::new(&url).await.unwrap();
+
+        let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+        assert_eq!(current_version(&pool).await.unwrap(), latest_version());
+
+        let rows = sqlx::query("SELECT version FROM schema_version ORDER BY version")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let versions: Vec<i64> = rows.iter().map(|row| row.get("version")).collect();
+        let expected: Vec<i64> = MIGRATIONS.iter().map(|m| m.version).collect();
+        assert_eq!(versions, expected);
+    }
+
+    #[tokio::test]
+    async fn rejects_database_from_newer_app() {
+        let url = legacy_database_url(V0_1_3_SCHEMA).await;
+        Database::new(&url).await.unwrap();
+
+        let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES (?, 'future')")
+            .bind(latest_version() + 1)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(run(&pool).await.is_err());
+    }
+}
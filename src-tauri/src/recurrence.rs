@@ -0,0 +1,383 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use crate::database::Database;
+use crate::error::TimeboxError;
+
+/// How often the scheduler looks for routines whose next day has started.
+const ROLLOVER_INTERVAL: StdDuration = StdDuration::from_secs(15 * 60);
+
+// How far ahead to look for the next occurrence, in days per step of the rule's interval.
+// Covers rules that skip months, like the 31st of every other month.
+const SEARCH_DAYS_PER_INTERVAL: i64 = 400;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Frequency {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        }
+    }
+}
+
+/// When a routine repeats: the FREQ, INTERVAL, BYDAY, BYMONTHDAY and UNTIL parts of an iCalendar RRULE.
+/// Occurrences are counted from an anchor date, the due date of the routine's current instance.
+#[derive(Clone, PartialEq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+    // Empty for weekly rules means the anchor's weekday; for daily rules, any day
+    pub by_day: Vec<Weekday>,
+    // Day of the month for monthly rules, -1 for the last one; None means the anchor's day
+    pub by_month_day: Option<i32>,
+    pub until: Option<NaiveDate>,
+}
+
+impl Recurrence {
+    /// Parses `daily`, `weekdays`, `weekly`, `weekly:mon,thu`, `monthly` or an RRULE such as
+    /// `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH`, with or without the `RRULE:` prefix.
+    pub fn parse(rule: &str) -> Result<Self, TimeboxError> {
+        let rule = rule.trim();
+        let preset = |frequency| Recurrence { frequency, interval: 1, by_day: Vec::new(), by_month_day: None, until: None };
+        let lower = rule.to_lowercase();
+        match lower.as_str() {
+            "daily" => return Ok(preset(Frequency::Daily)),
+            "weekly" => return Ok(preset(Frequency::Weekly)),
+            "monthly" => return Ok(preset(Frequency::Monthly)),
+            "weekdays" => {
+                let mut recurrence = preset(Frequency::Weekly);
+                recurrence.by_day = vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+                return Ok(recurrence);
+            }
+            _ => {}
+        }
+        if let Some(days) = lower.strip_prefix("weekly:") {
+            let mut recurrence = preset(Frequency::Weekly);
+            recurrence.by_day = parse_weekdays(days)?;
+            return Ok(recurrence);
+        }
+
+        let body = match rule.get(..6) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &rule[6..],
+            _ => rule,
+        };
+        let mut frequency = None;
+        let mut recurrence = preset(Frequency::Daily);
+        for part in body.split(';').filter(|part| !part.trim().is_empty()) {
+            let (key, value) = part.split_once('=')
+                .ok_or_else(|| invalid(rule, &format!("\"{}\" is not KEY=VALUE", part)))?;
+            let value = value.trim();
+            match key.trim().to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err(invalid(rule, &format!("FREQ={} is not supported, use DAILY, WEEKLY or MONTHLY", value))),
+                    });
+                }
+                "INTERVAL" => {
+                    recurrence.interval = value.parse().ok()
+                        .filter(|interval| (1..=366).contains(interval))
+                        .ok_or_else(|| invalid(rule, "INTERVAL must be between 1 and 366"))?;
+                }
+                "BYDAY" => recurrence.by_day = parse_weekdays(value)?,
+                "BYMONTHDAY" => {
+                    recurrence.by_month_day = Some(
+                        value.parse().ok()
+                            .filter(|day| (1..=31).contains(day) || *day == -1)
+                            .ok_or_else(|| invalid(rule, "BYMONTHDAY must be between 1 and 31, or -1 for the last day"))?,
+                    );
+                }
+                "UNTIL" => {
+                    recurrence.until = Some(
+                        value.get(..8)
+                            .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+                            .ok_or_else(|| invalid(rule, "UNTIL must be a date like 20251231"))?,
+                    );
+                }
+                other => return Err(invalid(rule, &format!("{} is not supported", other))),
+            }
+        }
+
+        recurrence.frequency = frequency.ok_or_else(|| invalid(rule, "FREQ is required"))?;
+        if recurrence.frequency == Frequency::Monthly && !recurrence.by_day.is_empty() {
+            return Err(invalid(rule, "BYDAY is not supported on monthly rules"));
+        }
+        if recurrence.frequency != Frequency::Monthly && recurrence.by_month_day.is_some() {
+            return Err(invalid(rule, "BYMONTHDAY is only supported on monthly rules"));
+        }
+        Ok(recurrence)
+    }
+
+    /// Parses `rule` for a routine starting on `start`, returning the anchored rule
+    /// and its first occurrence, which becomes the routine's due date.
+    pub fn schedule(rule: &str, start: NaiveDate) -> Result<(Self, NaiveDate), TimeboxError> {
+        let recurrence = Recurrence::parse(rule)?.anchored(start);
+        let first = recurrence.first_on_or_after(start, start)
+            .ok_or_else(|| TimeboxError::Validation(format!("The repeat rule has no occurrence on or after {}", start)))?;
+        Ok((recurrence, first))
+    }
+
+    /// Spells out the weekday or day of the month the rule leaves to its anchor.
+    pub fn anchored(mut self, anchor: NaiveDate) -> Self {
+        match self.frequency {
+            Frequency::Weekly if self.by_day.is_empty() => self.by_day = vec![anchor.weekday()],
+            Frequency::Monthly if self.by_month_day.is_none() => self.by_month_day = Some(anchor.day() as i32),
+            _ => {}
+        }
+        self
+    }
+
+    pub fn matches(&self, anchor: NaiveDate, date: NaiveDate) -> bool {
+        if date < anchor || self.until.is_some_and(|until| date > until) {
+            return false;
+        }
+        let interval = self.interval as i64;
+        match self.frequency {
+            Frequency::Daily => {
+                (date - anchor).num_days() % interval == 0
+                    && (self.by_day.is_empty() || self.by_day.contains(&date.weekday()))
+            }
+            Frequency::Weekly => {
+                let weeks = (week_start(date) - week_start(anchor)).num_days() / 7;
+                let day_matches = if self.by_day.is_empty() {
+                    date.weekday() == anchor.weekday()
+                } else {
+                    self.by_day.contains(&date.weekday())
+                };
+                weeks % interval == 0 && day_matches
+            }
+            Frequency::Monthly => {
+                let months = (date.year() as i64 * 12 + date.month0() as i64) - (anchor.year() as i64 * 12 + anchor.month0() as i64);
+                let day_matches = match self.by_month_day {
+                    Some(-1) => (date + Duration::days(1)).month() != date.month(),
+                    Some(day) => date.day() as i32 == day,
+                    None => date.day() == anchor.day(),
+                };
+                months % interval == 0 && day_matches
+            }
+        }
+    }
+
+    /// First occurrence on or after `date`, or None once the rule has run out.
+    pub fn first_on_or_after(&self, anchor: NaiveDate, date: NaiveDate) -> Option<NaiveDate> {
+        let start = date.max(anchor);
+        (0..=SEARCH_DAYS_PER_INTERVAL * self.interval as i64)
+            .map(|offset| start + Duration::days(offset))
+            .take_while(|day| self.until.is_none_or(|until| *day <= until))
+            .find(|day| self.matches(anchor, *day))
+    }
+
+    /// Occurrences in [from, to], in order.
+    pub fn occurrences_between(&self, anchor: NaiveDate, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        let mut dates = Vec::new();
+        let mut day = from.max(anchor);
+        while day <= to {
+            if self.matches(anchor, day) {
+                dates.push(day);
+            }
+            day += Duration::days(1);
+        }
+        dates
+    }
+}
+
+// Normalized RRULE, the form rules are stored in
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency.as_str())?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<&str> = self.by_day.iter().map(|day| weekday_code(*day)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if let Some(day) = self.by_month_day {
+            write!(f, ";BYMONTHDAY={}", day)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Spawns the task that creates the next instance of routines once their day has started.
+pub fn spawn_scheduler(db: Arc<Database>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = db.roll_over_recurring_tasks().await {
+                eprintln!("Failed to roll over recurring tasks: {}", e);
+            }
+            tokio::time::sleep(ROLLOVER_INTERVAL).await;
+        }
+    });
+}
+
+fn invalid(rule: &str, reason: &str) -> TimeboxError {
+    TimeboxError::Validation(format!("Invalid repeat rule \"{}\": {}", rule, reason))
+}
+
+// Accepts RRULE codes (MO) as well as English names (mon, monday); sorted Monday first
+fn parse_weekdays(value: &str) -> Result<Vec<Weekday>, TimeboxError> {
+    let mut days = Vec::new();
+    for name in value.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let day = match name.to_lowercase().as_str() {
+            "mo" => Weekday::Mon,
+            "tu" => Weekday::Tue,
+            "we" => Weekday::Wed,
+            "th" => Weekday::Thu,
+            "fr" => Weekday::Fri,
+            "sa" => Weekday::Sat,
+            "su" => Weekday::Sun,
+            other => other.parse::<Weekday>()
+                .map_err(|_| TimeboxError::Validation(format!("Unknown weekday \"{}\"", name)))?,
+        };
+        if !days.contains(&day) {
+            days.push(day);
+        }
+    }
+    if days.is_empty() {
+        return Err(TimeboxError::Validation("List at least one weekday".to_string()));
+    }
+    days.sort_by_key(|day| day.num_days_from_monday());
+    Ok(days)
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn dates(values: &[&str]) -> Vec<NaiveDate> {
+        values.iter().map(|value| date(value)).collect()
+    }
+
+    #[test]
+    fn normalizes_presets_and_rrules() {
+        let normalized = |rule: &str| Recurrence::parse(rule).unwrap().to_string();
+
+        assert_eq!(normalized("daily"), "FREQ=DAILY");
+        assert_eq!(normalized("Monthly"), "FREQ=MONTHLY");
+        assert_eq!(normalized("weekdays"), "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR");
+        assert_eq!(normalized("weekly:thu,Mon"), "FREQ=WEEKLY;BYDAY=MO,TH");
+        assert_eq!(normalized("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO"), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO");
+        assert_eq!(normalized("rrule:freq=weekly;interval=2;byday=MO"), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO");
+        assert_eq!(normalized("FREQ=DAILY;UNTIL=20250206T000000Z"), "FREQ=DAILY;UNTIL=20250206");
+    }
+
+    #[test]
+    fn rejects_unsupported_rules() {
+        for rule in [
+            "FREQ=YEARLY",
+            "FREQ=MONTHLY;BYDAY=MO",
+            "FREQ=DAILY;BYMONTHDAY=1",
+            "INTERVAL=2",
+            "FREQ=DAILY;COUNT=3",
+            "FREQ=DAILY;INTERVAL=0",
+            "weekly:someday",
+            "é",
+        ] {
+            assert!(Recurrence::parse(rule).is_err(), "{} should be rejected", rule);
+        }
+    }
+
+    #[test]
+    fn keeps_interval_aligned_across_weeks() {
+        let rule = Recurrence::parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR").unwrap();
+        let anchor = date("2025-01-31");
+
+        assert_eq!(
+            rule.occurrences_between(anchor, anchor, date("2025-02-28")),
+            dates(&["2025-01-31", "2025-02-10", "2025-02-14", "2025-02-24", "2025-02-28"]),
+        );
+        // Mondays in the weeks the rule skips never match
+        assert!(!rule.matches(anchor, date("2025-02-03")));
+        assert_eq!(rule.first_on_or_after(anchor, date("2025-02-01")), Some(date("2025-02-10")));
+    }
+
+    #[test]
+    fn weekdays_skip_the_weekend() {
+        let rule = Recurrence::parse("weekdays").unwrap();
+        let anchor = date("2025-01-31");
+
+        assert_eq!(rule.first_on_or_after(anchor, date("2025-02-01")), Some(date("2025-02-03")));
+    }
+
+    #[test]
+    fn last_day_of_the_month() {
+        let rule = Recurrence::parse("FREQ=MONTHLY;BYMONTHDAY=-1").unwrap();
+        let anchor = date("2025-01-31");
+
+        assert_eq!(
+            rule.occurrences_between(anchor, anchor, date("2025-04-30")),
+            dates(&["2025-01-31", "2025-02-28", "2025-03-31", "2025-04-30"]),
+        );
+        assert_eq!(rule.first_on_or_after(anchor, date("2028-02-01")), Some(date("2028-02-29")));
+    }
+
+    #[test]
+    fn thirty_first_skips_short_months() {
+        let anchor = date("2025-01-31");
+        let rule = Recurrence::parse("monthly").unwrap().anchored(anchor);
+
+        assert_eq!(rule.to_string(), "FREQ=MONTHLY;BYMONTHDAY=31");
+        assert_eq!(rule.first_on_or_after(anchor, date("2025-02-01")), Some(date("2025-03-31")));
+        assert_eq!(rule.first_on_or_after(anchor, date("2025-04-01")), Some(date("2025-05-31")));
+    }
+
+    #[test]
+    fn stops_at_until() {
+        let rule = Recurrence::parse("FREQ=DAILY;INTERVAL=3;UNTIL=20250206").unwrap();
+        let anchor = date("2025-02-01");
+
+        assert_eq!(rule.first_on_or_after(anchor, date("2025-02-02")), Some(date("2025-02-04")));
+        assert_eq!(rule.first_on_or_after(anchor, date("2025-02-05")), None);
+        assert_eq!(
+            rule.occurrences_between(anchor, anchor, date("2025-02-28")),
+            dates(&["2025-02-01", "2025-02-04"]),
+        );
+    }
+
+    #[test]
+    fn schedule_fails_without_an_occurrence() {
+        assert!(Recurrence::schedule("FREQ=DAILY;UNTIL=20241231", date("2025-01-01")).is_err());
+        assert!(Recurrence::schedule("FREQ=MONTHLY;INTERVAL=12;BYMONTHDAY=30", date("2025-02-01")).is_err());
+
+        let (rule, first) = Recurrence::schedule("weekly", date("2025-01-29")).unwrap();
+        assert_eq!(rule.to_string(), "FREQ=WEEKLY;BYDAY=WE");
+        assert_eq!(first, date("2025-01-29"));
+    }
+}
@@ -0,0 +1,204 @@
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Listener};
+use tokio::sync::broadcast;
+
+use crate::commands::{AppState, StartSessionRequest, begin_session, build_timer_status, sync_with_database};
+use crate::cycle;
+use crate::database::{PomodoroSession, SessionType};
+use crate::error::TimeboxError;
+
+pub const TICK_EVENT: &str = "timer://tick";
+pub const COMPLETED_EVENT: &str = "timer://completed";
+pub const PHASE_CHANGED_EVENT: &str = "timer://phase-changed";
+pub const DAILY_TARGET_EVENT: &str = "timer://daily-target-reached";
+
+const TICK_INTERVAL: StdDuration = StdDuration::from_secs(1);
+// How often an idle or paused engine checks for timer changes made by the CLI
+const SYNC_INTERVAL: StdDuration = StdDuration::from_secs(2);
+// Timer events queued per external subscriber before the slowest ones start missing ticks
+const EVENT_BUFFER: usize = 64;
+
+#[derive(Serialize, Clone)]
+pub struct TimerCompletedPayload {
+    pub session: PomodoroSession,
+}
+
+#[derive(Serialize, Clone)]
+pub struct PhaseChangedPayload {
+    pub session_id: Option<String>,
+    pub session_type: Option<SessionType>,
+    pub is_paused: bool,
+}
+
+// A timer event as delivered to control socket and HTTP API clients
+#[derive(Clone)]
+pub struct TimerEvent {
+    pub name: &'static str,
+    pub payload: Value,
+}
+
+/// Re-broadcasts the timer events sent to the webview so clients outside of it can subscribe.
+pub fn forward_events(app: &AppHandle) -> broadcast::Sender<TimerEvent> {
+    let (events, _) = broadcast::channel(EVENT_BUFFER);
+    for name in [TICK_EVENT, COMPLETED_EVENT, PHASE_CHANGED_EVENT, DAILY_TARGET_EVENT] {
+        let events = events.clone();
+        app.listen_any(name, move |event| {
+            if let Ok(payload) = serde_json::from_str(event.payload()) {
+                // Fails only when nobody is subscribed
+                let _ = events.send(TimerEvent { name, payload });
+            }
+        });
+    }
+    events
+}
+
+/// Spawns the background task that drives the active session.
+/// It runs independently of the webview, so sessions complete on time even when the window is hidden.
+pub fn spawn(app: AppHandle, state: Arc<AppState>) {
+    tauri::async_runtime::spawn(run(app, state));
+}
+
+async fn run(app: AppHandle, state: Arc<AppState>) {
+    let mut last_phase: Option<(String, bool)> = None;
+    // User of the most recent session, whose daily target is watched
+    let mut user_id = "default_user".to_string();
+    // Local date the target was last reached on; a target already met at startup is not announced again
+    let mut target_reached_on = match state.db.get_daily_target_progress(&user_id).await {
+        Ok(progress) if progress.target_met => Some(progress.date),
+        _ => None,
+    };
+
+    loop {
+        {
+            let mut active_session = state.active_session.write().await;
+            if let Err(e) = sync_with_database(&state.db, &mut active_session).await {
+                eprintln!("Failed to sync active timer: {}", e);
+            }
+        }
+
+        if let Some(session) = finalize_if_due(&state).await {
+            let _ = app.emit(COMPLETED_EVENT, TimerCompletedPayload { session: session.clone() });
+
+            if let Err(e) = auto_start_break(&state, &session).await {
+                eprintln!("Failed to auto-start break: {}", e);
+            }
+        }
+
+        let (phase, payload, wait) = {
+            let active_session = state.active_session.read().await;
+            match active_session.as_ref() {
+                Some(active) => {
+                    user_id.clone_from(&active.session.user_id);
+                    let phase = Some((active.session.id.clone(), active.is_paused));
+                    let payload = PhaseChangedPayload {
+                        session_id: Some(active.session.id.clone()),
+                        session_type: Some(active.session.session_type.clone()),
+                        is_paused: active.is_paused,
+                    };
+                    // Wake up on the next second boundary, or exactly at the deadline if sooner
+                    let wait = active.deadline().map(|deadline| {
+                        (deadline - Utc::now())
+                            .to_std()
+                            .unwrap_or(StdDuration::ZERO)
+                            .min(TICK_INTERVAL)
+                    });
+                    (phase, payload, wait)
+                }
+                None => {
+                    let payload = PhaseChangedPayload {
+                        session_id: None,
+                        session_type: None,
+                        is_paused: false,
+                    };
+                    (None, payload, None)
+                }
+            }
+        };
+
+        if phase != last_phase {
+            let _ = app.emit(PHASE_CHANGED_EVENT, payload);
+            last_phase = phase;
+
+            // Every completed or stopped session changes the phase, so this catches them all
+            match state.db.get_daily_target_progress(&user_id).await {
+                Ok(progress) if progress.target_met && target_reached_on.as_ref() != Some(&progress.date) => {
+                    target_reached_on = Some(progress.date.clone());
+                    let _ = app.emit(DAILY_TARGET_EVENT, progress);
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Failed to check the daily target: {}", e),
+            }
+        }
+
+        if last_phase.is_some() {
+            let status = {
+                let active_session = state.active_session.read().await;
+                build_timer_status(&state.db, active_session.as_ref()).await
+            };
+            let _ = app.emit(TICK_EVENT, status);
+        }
+
+        // Paused and idle timers have nothing to count down, but may still be changed from the CLI
+        let wait = wait.unwrap_or(SYNC_INTERVAL);
+        tokio::select! {
+            _ = state.timer_notify.notified() => {}
+            _ = tokio::time::sleep(wait) => {}
+        }
+    }
+}
+
+// Closes the active session in the database once its deadline has passed
+async fn finalize_if_due(state: &AppState) -> Option<PomodoroSession> {
+    let mut active_session = state.active_session.write().await;
+    let deadline = active_session.as_ref()?.deadline()?;
+    if deadline > Utc::now() {
+        return None;
+    }
+
+    let active = active_session.take()?;
+    let session_id = active.session.id.clone();
+    let duration = active.total_duration.num_seconds() as i32;
+
+    if let Err(e) = state.db.update_session(&session_id, deadline, duration).await {
+        // The persisted timer is kept, so the session is closed out on the next startup instead
+        eprintln!("Failed to finalize session {}: {}", session_id, e);
+        return None;
+    }
+    if let Err(e) = state.db.clear_active_timer().await {
+        eprintln!("Failed to clear active timer: {}", e);
+    }
+
+    match state.db.get_session(&session_id).await {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("Failed to load completed session {}: {}", session_id, e);
+            None
+        }
+    }
+}
+
+// Starts the break the cycle calls for after a focus session, when the user opted into it
+async fn auto_start_break(state: &AppState, completed: &PomodoroSession) -> Result<(), TimeboxError> {
+    if !matches!(completed.session_type, SessionType::Focus) {
+        return Ok(());
+    }
+
+    let settings = state.db.get_or_create_settings(&completed.user_id).await?;
+    if !settings.auto_start_breaks {
+        return Ok(());
+    }
+
+    let cycle_state = cycle::current_cycle_state(&state.db, &completed.user_id).await?;
+
+    begin_session(state, StartSessionRequest {
+        user_id: Some(completed.user_id.clone()),
+        task_id: None,
+        session_type: cycle_state.next_phase,
+    }).await?;
+
+    Ok(())
+}
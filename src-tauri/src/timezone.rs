@@ -0,0 +1,78 @@
+use chrono::{DateTime, Duration, NaiveDate, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::error::TimeboxError;
+
+pub fn parse_timezone(name: &str) -> Result<Tz, TimeboxError> {
+    name.parse::<Tz>()
+        .map_err(|_| TimeboxError::Validation(format!("Unknown timezone: {}", name)))
+}
+
+// IANA name of the OS timezone, or UTC when it can't be determined or isn't a known zone
+pub fn detect_system_timezone() -> String {
+    iana_time_zone::get_timezone()
+        .ok()
+        .filter(|name| name.parse::<Tz>().is_ok())
+        .unwrap_or_else(|| "UTC".to_string())
+}
+
+pub fn today(tz: Tz) -> NaiveDate {
+    local_date(tz, Utc::now())
+}
+
+// Calendar date of `instant` as seen in `tz`
+pub fn local_date(tz: Tz, instant: DateTime<Utc>) -> NaiveDate {
+    instant.with_timezone(&tz).date_naive()
+}
+
+/// First instant of `date` in `tz`.
+/// Some zones skip midnight on DST changes, in which case the day starts at the first hour that exists.
+pub fn start_of_day(tz: Tz, date: NaiveDate) -> DateTime<Utc> {
+    (0..24)
+        .find_map(|hour| tz.from_local_datetime(&date.and_hms_opt(hour, 0, 0)?).earliest())
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+}
+
+// Half-open UTC range [start, end) covering `date` in `tz`
+pub fn day_bounds(tz: Tz, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    (start_of_day(tz, date), start_of_day(tz, date + Duration::days(1)))
+}
+
+// Seconds to add to UTC to get wall-clock time in `tz` at `instant`
+pub fn utc_offset_seconds(tz: Tz, instant: DateTime<Utc>) -> i32 {
+    tz.offset_from_utc_datetime(&instant.naive_utc()).fix().local_minus_utc()
+}
+
+/// Splits [start, end) into consecutive ranges over which `tz` keeps the same UTC offset,
+/// so SQL can shift timestamps to local time with a fixed offset per range.
+pub fn offset_segments(tz: Tz, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<(DateTime<Utc>, DateTime<Utc>, i32)> {
+    let mut segments = Vec::new();
+    let mut segment_start = start;
+    let mut offset = utc_offset_seconds(tz, start);
+
+    // Offsets change at most a few times a year, so probing hourly and narrowing down is plenty
+    let mut probe = start;
+    while probe < end {
+        let next = (probe + Duration::hours(1)).min(end);
+        if utc_offset_seconds(tz, next) != offset {
+            let (mut before, mut after) = (probe, next);
+            while after - before > Duration::seconds(1) {
+                let middle = before + (after - before) / 2;
+                if utc_offset_seconds(tz, middle) == offset {
+                    before = middle;
+                } else {
+                    after = middle;
+                }
+            }
+
+            segments.push((segment_start, after, offset));
+            segment_start = after;
+            offset = utc_offset_seconds(tz, after);
+        }
+        probe = next;
+    }
+    segments.push((segment_start, end, offset));
+
+    segments
+}
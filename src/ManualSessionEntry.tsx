@@ -10,6 +10,7 @@ import {
 import { useAtom } from 'jotai';
 import { tasksAtom, fetchTasksAtom, activeTabAtom } from './atoms';
 import { apiService } from './apiService';
+import { isTimeboxError } from './types';
 import { format, differenceInMinutes, parse } from 'date-fns';
 import { toast } from 'sonner';
 
@@ -88,7 +89,8 @@ const ManualSessionEntry: React.FC = () => {
             setActiveTab('history');
         } catch (error) {
             console.error('Error logging manual session:', error);
-            toast.error("Failed to log session");
+            // Overlaps and future times are rejected by the backend with a readable message
+            toast.error(isTimeboxError(error) ? error.message : "Failed to log session");
         } finally {
             setIsSubmitting(false);
         }
@@ -6,6 +6,7 @@ import { motion, AnimatePresence } from 'framer-motion';
 import { PlayIcon, PauseIcon, StopIcon, ExclamationTriangleIcon } from '@heroicons/react/24/solid';
 import { useTimer } from './useTimer';
 import CustomTaskSelector from './CustomTaskSelector';
+import { isStrictModeViolation } from './types';
 import { tasksAtom, fetchTasksAtom, dailyStatsAtom, activeTabAtom, taskShowAddFormAtom } from './atoms';
 
 interface PomodoroTimerProps {
@@ -60,13 +61,13 @@ const PomodoroTimer: React.FC<PomodoroTimerProps> = ({ selectedTaskId, onTaskSel
         },
       });
 
-      // If a focus session just completed, switch to break page
+      // If a focus session just completed, switch to break page.
+      // The backend has already closed the session and may have auto-started the break.
       if (timerStatus.session_type === 'FOCUS') {
-        stopTimer();
         setActiveTab('break');
       }
     }
-  }, [isCompleted, dismissCompletion, timerStatus.session_type, setActiveTab, stopTimer]);
+  }, [isCompleted, dismissCompletion, timerStatus.session_type, setActiveTab]);
 
   // Calculate progress for the circular indicator
   const progress = useMemo(() => {
@@ -80,14 +81,22 @@ const PomodoroTimer: React.FC<PomodoroTimerProps> = ({ selectedTaskId, onTaskSel
   const circumference = 2 * Math.PI * 45;
   const strokeDashoffset = circumference - (circumference * progress) / 100;
 
-  const handleStart = () => {
-    startTimer(localSelectedTask, timerStatus.session_type || 'FOCUS');
-    toast.success('Timer Started');
+  const handleStart = async () => {
+    try {
+      await startTimer(localSelectedTask, timerStatus.session_type || 'FOCUS');
+      toast.success('Timer Started');
+    } catch (error) {
+      if (isStrictModeViolation(error)) toast.error(error.message);
+    }
   };
 
-  const handlePause = () => {
-    pauseTimer();
-    toast.info('Timer Paused');
+  const handlePause = async () => {
+    try {
+      await pauseTimer();
+      toast.info('Timer Paused');
+    } catch (error) {
+      if (isStrictModeViolation(error)) toast.error(error.message);
+    }
   };
 
   const handleResume = () => {
@@ -95,9 +104,23 @@ const PomodoroTimer: React.FC<PomodoroTimerProps> = ({ selectedTaskId, onTaskSel
     toast.success('Timer Resumed');
   };
 
-  const handleStop = () => {
-    stopTimer();
-    toast.info('Timer Stopped');
+  const handleStop = async () => {
+    try {
+      await stopTimer();
+      toast.info('Timer Stopped');
+    } catch (error) {
+      if (!isStrictModeViolation(error, 'stop_reason_required')) return;
+
+      // Strict mode only lets a focus session end early with a reason
+      const reason = window.prompt(error.message);
+      if (!reason || !reason.trim()) return;
+      try {
+        await stopTimer(reason);
+        toast.info('Timer Stopped');
+      } catch (retryError) {
+        if (isStrictModeViolation(retryError)) toast.error(retryError.message);
+      }
+    }
   };
 
   const handleTaskSelect = (taskId: string) => {
@@ -1,14 +1,41 @@
 // apiService.ts
 import { invoke } from '@tauri-apps/api/core';
 import {
+  User,
   PomodoroSettings,
   Task,
   PomodoroSession,
+  SessionPause,
+  ManualSessionOptions,
+  SessionAuditEntry,
+  UpdateSessionRequest,
+  Interruption,
+  InterruptionSource,
+  RecordInterruptionRequest,
   Goal,
+  GoalProgress,
   TimerStatus,
   SettingsUpdateRequest,
   DailyReflection,
-  DayActivities
+  DayActivities,
+  CycleState,
+  ExportSummary,
+  ImportRequest,
+  ImportReport,
+  BackupInfo,
+  ApiSettings,
+  UpdateApiSettingsRequest,
+  FocusStats,
+  StatsGranularity,
+  TaskCounts,
+  TaskUpdates,
+  DailyPlan,
+  UpcomingOccurrence,
+  ChecklistItem,
+  Project,
+  Tag,
+  ProjectTagFilter,
+  StreakSummary
 } from './types';
 
 export const apiService = {
@@ -31,24 +58,28 @@ export const apiService = {
     });
   },
 
-  async pauseSession(): Promise<void> {
-    return await invoke('pause_session');
+  async pauseSession(reason?: string): Promise<void> {
+    return await invoke('pause_session', { reason });
   },
 
   async resumeSession(): Promise<void> {
     return await invoke('resume_session');
   },
 
-  async stopSession(): Promise<void> {
-    return await invoke('stop_session');
+  async stopSession(reason?: string): Promise<void> {
+    return await invoke('stop_session', { reason });
   },
 
   async getTimerStatus(): Promise<TimerStatus> {
     return await invoke('get_timer_status');
   },
 
-  async recordInterruption(): Promise<number> {
-    return await invoke('record_interruption');
+  async recordInterruption(details?: RecordInterruptionRequest): Promise<number> {
+    return await invoke('record_interruption', { req: details });
+  },
+
+  async getCycleState(userId: string): Promise<CycleState> {
+    return await invoke('get_cycle_state', { userId: userId });
   },
 
   // Settings functions
@@ -60,33 +91,81 @@ export const apiService = {
     return await invoke('update_settings', { req: request });
   },
 
+  async setUserTimezone(userId: string, timezone: string): Promise<User> {
+    return await invoke('set_user_timezone', { userId: userId, timezone: timezone });
+  },
+
   // Task functions
   async createTask(
     userId: string,
     title: string,
-    estimatedPomodoros?: number
+    estimatedPomodoros?: number,
+    goalId?: string,
+    projectId?: string,
+    parentTaskId?: string
   ): Promise<Task> {
     return await invoke('create_task', {
       userId: userId,
       title,
-      estimatedPomodoros: estimatedPomodoros
+      estimatedPomodoros: estimatedPomodoros,
+      goalId: goalId,
+      projectId: projectId,
+      parentTaskId: parentTaskId
     });
   },
 
-  async getTasks(userId: string): Promise<Task[]> {
-    return await invoke('get_tasks', { userId: userId });
+  async assignTaskToGoal(taskId: string, goalId: string | null): Promise<Task> {
+    return await invoke('assign_task_to_goal', { taskId: taskId, goalId: goalId });
   },
 
-  async updateTask(
-    taskId: string,
-    updates: { title?: string; estimated_pomodoros?: number; completed?: boolean }
-  ): Promise<Task> {
+  async assignTaskToProject(taskId: string, projectId: string | null): Promise<Task> {
+    return await invoke('assign_task_to_project', { taskId: taskId, projectId: projectId });
+  },
+
+  // Replaces all of the task's tags; unknown names are created
+  async setTaskTags(taskId: string, tags: string[]): Promise<Task> {
+    return await invoke('set_task_tags', { taskId: taskId, tags: tags });
+  },
+
+  async setTaskParent(taskId: string, parentTaskId: string | null): Promise<Task> {
+    return await invoke('set_task_parent', { taskId: taskId, parentTaskId: parentTaskId });
+  },
+
+  async getTasks(userId: string, filter: ProjectTagFilter = {}): Promise<Task[]> {
+    return await invoke('get_tasks', { userId: userId, projectId: filter.project_id, tag: filter.tag });
+  },
+
+  async updateTask(taskId: string, updates: TaskUpdates): Promise<Task> {
     return await invoke('update_task', {
       req: {
         task_id: taskId,
-        title: updates.title,
-        estimated_pomodoros: updates.estimated_pomodoros,
-        completed: updates.completed
+        ...updates
+      }
+    });
+  },
+
+  // Listed tasks swap into the places they held between them; the rest keep theirs
+  async reorderTasks(userId: string, taskIds: string[]): Promise<Task[]> {
+    return await invoke('reorder_tasks', { userId: userId, taskIds: taskIds });
+  },
+
+  // date is YYYY-MM-DD and defaults to today; availableMinutes defaults to 8 hours
+  async getDailyPlan(userId: string, date?: string, availableMinutes?: number): Promise<DailyPlan> {
+    return await invoke('get_daily_plan', {
+      req: {
+        user_id: userId,
+        date: date,
+        available_minutes: availableMinutes
+      }
+    });
+  },
+
+  // days defaults to 14
+  async getUpcomingOccurrences(userId: string, days?: number): Promise<UpcomingOccurrence[]> {
+    return await invoke('get_upcoming_occurrences', {
+      req: {
+        user_id: userId,
+        days: days
       }
     });
   },
@@ -95,10 +174,40 @@ export const apiService = {
     return await invoke('delete_task', { taskId: taskId });
   },
 
-  async getTasksWithPomodoroCounts(userId: string): Promise<Array<Task & { actual_pomodoros: number }>> {
+  async getTasksWithPomodoroCounts(userId: string): Promise<Array<Task & TaskCounts>> {
     return await invoke('get_tasks_with_pomodoro_counts', { userId: userId });
   },
 
+  // Checklist functions
+  async getChecklistItems(taskId: string): Promise<ChecklistItem[]> {
+    return await invoke('get_checklist_items', { taskId: taskId });
+  },
+
+  async addChecklistItem(taskId: string, title: string): Promise<ChecklistItem> {
+    return await invoke('add_checklist_item', { taskId: taskId, title });
+  },
+
+  async updateChecklistItem(
+    itemId: string,
+    updates: { title?: string; completed?: boolean }
+  ): Promise<ChecklistItem> {
+    return await invoke('update_checklist_item', {
+      req: {
+        item_id: itemId,
+        ...updates
+      }
+    });
+  },
+
+  // itemIds must list every item of the task once, in the new order
+  async reorderChecklistItems(taskId: string, itemIds: string[]): Promise<ChecklistItem[]> {
+    return await invoke('reorder_checklist_items', { taskId: taskId, itemIds: itemIds });
+  },
+
+  async deleteChecklistItem(itemId: string): Promise<void> {
+    return await invoke('delete_checklist_item', { itemId: itemId });
+  },
+
   // Session functions
   async getSessions(userId: string): Promise<PomodoroSession[]> {
     return await invoke('get_sessions', { userId: userId });
@@ -157,21 +266,152 @@ export const apiService = {
     return await invoke('delete_goal', { goalId: goalId });
   },
 
+  async getGoalProgress(goalId: string): Promise<GoalProgress> {
+    return await invoke('get_goal_progress', { goalId: goalId });
+  },
+
+  // Project functions
+  async createProject(
+    userId: string,
+    name: string,
+    color?: string,
+    hourlyRate?: number
+  ): Promise<Project> {
+    return await invoke('create_project', {
+      req: {
+        user_id: userId,
+        name,
+        color,
+        hourly_rate: hourlyRate
+      }
+    });
+  },
+
+  async getProjects(userId: string, includeArchived = false): Promise<Project[]> {
+    return await invoke('get_projects', { userId: userId, includeArchived: includeArchived });
+  },
+
+  async updateProject(
+    projectId: string,
+    updates: {
+      name?: string;
+      color?: string;
+      clear_color?: boolean;
+      archived?: boolean;
+      hourly_rate?: number;
+      clear_hourly_rate?: boolean;
+    }
+  ): Promise<Project> {
+    return await invoke('update_project', {
+      req: {
+        project_id: projectId,
+        ...updates
+      }
+    });
+  },
+
+  // Tasks of a deleted project are kept without a project
+  async deleteProject(projectId: string): Promise<void> {
+    return await invoke('delete_project', { projectId: projectId });
+  },
+
+  // Tag functions
+  async createTag(userId: string, name: string): Promise<Tag> {
+    return await invoke('create_tag', { userId: userId, name });
+  },
+
+  async getTags(userId: string): Promise<Tag[]> {
+    return await invoke('get_tags', { userId: userId });
+  },
+
+  async renameTag(tagId: string, name: string): Promise<Tag> {
+    return await invoke('rename_tag', { tagId: tagId, name });
+  },
+
+  async deleteTag(tagId: string): Promise<void> {
+    return await invoke('delete_tag', { tagId: tagId });
+  },
+
   // Manual session logging
+  // Returns one session per untracked gap when fillGaps is set
   async logManualSession(
     userId: string,
     taskId: string | undefined,
     startTime: Date,
     endTime: Date,
-    durationSeconds: number
-  ): Promise<PomodoroSession> {
+    durationSeconds?: number,
+    options: ManualSessionOptions = {}
+  ): Promise<PomodoroSession[]> {
     return await invoke('log_manual_session', {
       req: {
         user_id: userId,
         task_id: taskId || null,
         start_time: startTime.toISOString(),
         end_time: endTime.toISOString(),
-        duration_seconds: durationSeconds
+        duration_seconds: durationSeconds,
+        session_type: options.sessionType,
+        tags: options.tags ?? [],
+        fill_gaps: options.fillGaps ?? false
+      }
+    });
+  },
+
+  async updateSession(request: UpdateSessionRequest): Promise<PomodoroSession> {
+    return await invoke('update_session', { req: request });
+  },
+
+  async deleteSession(sessionId: string): Promise<void> {
+    return await invoke('delete_session', { sessionId: sessionId });
+  },
+
+  async splitSession(sessionId: string, splitAt: Date): Promise<PomodoroSession[]> {
+    return await invoke('split_session', {
+      req: {
+        session_id: sessionId,
+        split_at: splitAt.toISOString()
+      }
+    });
+  },
+
+  async mergeSessions(firstSessionId: string, secondSessionId: string): Promise<PomodoroSession> {
+    return await invoke('merge_sessions', {
+      req: {
+        first_session_id: firstSessionId,
+        second_session_id: secondSessionId
+      }
+    });
+  },
+
+  async getSessionAudit(userId: string, sessionId?: string): Promise<SessionAuditEntry[]> {
+    return await invoke('get_session_audit', { userId: userId, sessionId: sessionId });
+  },
+
+  async getSessionPauses(sessionId: string): Promise<SessionPause[]> {
+    return await invoke('get_session_pauses', { sessionId: sessionId });
+  },
+
+  async getInterruptions(userId: string, startDate: Date, endDate: Date): Promise<Interruption[]> {
+    return await invoke('get_interruptions', {
+      req: {
+        user_id: userId,
+        start_date: startDate.toISOString(),
+        end_date: endDate.toISOString()
+      }
+    });
+  },
+
+  async getInterruptionSources(
+    userId: string,
+    startDate: Date,
+    endDate: Date,
+    limit?: number
+  ): Promise<InterruptionSource[]> {
+    return await invoke('get_interruption_sources', {
+      req: {
+        user_id: userId,
+        start_date: startDate.toISOString(),
+        end_date: endDate.toISOString(),
+        limit: limit
       }
     });
   },
@@ -181,18 +421,43 @@ export const apiService = {
     userId: string,
     startDate: Date,
     endDate: Date,
-    sessionType?: 'FOCUS' | 'SHORT_BREAK' | 'LONG_BREAK'
+    sessionType?: 'FOCUS' | 'SHORT_BREAK' | 'LONG_BREAK',
+    filter: ProjectTagFilter = {}
   ): Promise<PomodoroSession[]> {
     return await invoke('get_sessions_by_date_range', {
       req: {
         user_id: userId,
         start_date: startDate.toISOString(),
         end_date: endDate.toISOString(),
-        session_type: sessionType
+        session_type: sessionType,
+        ...filter
       }
     });
   },
 
+  // Aggregated in SQL; prefer this over loading every session of a long range
+  async getFocusStats(
+    userId: string,
+    startDate: Date,
+    endDate: Date,
+    granularity: StatsGranularity = 'day',
+    filter: ProjectTagFilter = {}
+  ): Promise<FocusStats> {
+    return await invoke('get_focus_stats', {
+      req: {
+        user_id: userId,
+        start_date: startDate.toISOString(),
+        end_date: endDate.toISOString(),
+        granularity: granularity,
+        ...filter
+      }
+    });
+  },
+
+  async getStreaks(userId: string): Promise<StreakSummary> {
+    return await invoke('get_streaks', { userId: userId });
+  },
+
   // Daily Reflection functions
   async saveDailyReflection(
     userId: string,
@@ -254,5 +519,43 @@ export const apiService = {
         date: date.toISOString()
       }
     });
+  },
+
+  // Export functions
+  // Writes JSON, CSV and iCalendar files; defaults to a new folder in Downloads
+  async exportData(directory?: string): Promise<ExportSummary> {
+    return await invoke('export_data', { directory: directory });
+  },
+
+  async importData(request: ImportRequest): Promise<ImportReport> {
+    return await invoke('import_data', { req: request });
+  },
+
+  // Backup functions
+  async createBackup(): Promise<BackupInfo> {
+    return await invoke('create_backup');
+  },
+
+  async listBackups(): Promise<BackupInfo[]> {
+    return await invoke('list_backups');
+  },
+
+  // Restarts the app once the backup is staged, so the returned promise does not settle
+  async restoreBackup(name: string): Promise<void> {
+    return await invoke('restore_backup', { name: name });
+  },
+
+  // HTTP API functions
+  async getApiSettings(): Promise<ApiSettings> {
+    return await invoke('get_api_settings');
+  },
+
+  async updateApiSettings(request: UpdateApiSettingsRequest): Promise<ApiSettings> {
+    return await invoke('update_api_settings', { req: request });
+  },
+
+  // Invalidates the current token for every connected client
+  async regenerateApiToken(): Promise<ApiSettings> {
+    return await invoke('regenerate_api_token');
   }
 };
\ No newline at end of file
@@ -1,5 +1,5 @@
 import { atom } from 'jotai';
-import { Task, StopwatchSession } from './types';
+import { Task, TaskCounts, StopwatchSession } from './types';
 import { apiService } from './apiService';
 
 // UI State
@@ -116,7 +116,7 @@ export const themeAtom = atom(
 );
 
 // Data State
-export type TaskWithPomodoros = Task & { actual_pomodoros: number };
+export type TaskWithPomodoros = Task & TaskCounts;
 export const tasksAtom = atom<TaskWithPomodoros[]>([]);
 export const tasksLoadingAtom = atom<boolean>(false);
 
@@ -294,7 +294,7 @@ export const fetchDayActivitiesAtom = atom(
           id: task.id,
           type: 'task' as const,
           title: task.title,
-          startTime: task.created_at,
+          startTime: task.completed_at ?? task.created_at,
           completed: task.completed
         }))
       ].sort((a, b) => new Date(a.startTime).getTime() - new Date(b.startTime).getTime());
@@ -16,17 +16,110 @@ export interface PomodoroSettings {
   auto_start_breaks: boolean;
   sound_enabled: boolean;
   sound_volume: number;
+  daily_target_unit: DailyTargetUnit;
+  daily_target: number; // 0 turns the daily target and streaks off
+  rest_days: number[]; // 0 = Monday ... 6 = Sunday
 }
 
+export type DailyTargetUnit = 'minutes' | 'pomodoros';
+
 export interface Task {
   id: string;
   user_id: string;
   title: string;
   estimated_pomodoros: number;
   completed: boolean;
+  completed_at?: string | null; // set while the task is completed
+  goal_id?: string | null;
+  project_id?: string | null;
+  tags: string[];
+  parent_task_id?: string | null;
+  due_date?: string | null; // YYYY-MM-DD in the user's timezone
+  priority: TaskPriority;
+  position: number; // manual list order, lowest first
+  planned_for?: string | null; // YYYY-MM-DD of the daily plan the task is queued in
+  recurrence?: string | null; // RRULE of a routine; due_date is the occurrence this instance stands for
+  recurs_from?: string | null; // previous instance of the routine
   created_at: string;
 }
 
+export type TaskPriority = 'none' | 'low' | 'medium' | 'high';
+
+// Fields of a task to change; omitted ones stay as they are
+export interface TaskUpdates {
+  title?: string;
+  estimated_pomodoros?: number;
+  completed?: boolean;
+  due_date?: string;
+  clear_due_date?: boolean;
+  priority?: TaskPriority;
+  planned_for?: string;
+  clear_planned_for?: boolean; // takes the task out of its plan
+  recurrence?: string; // daily, weekdays, weekly, weekly:mon,thu, monthly or an RRULE
+  clear_recurrence?: boolean; // stops the routine
+}
+
+// A day a routine comes up on; task_id is set once its instance for that day exists
+export interface UpcomingOccurrence {
+  date: string; // YYYY-MM-DD
+  task_id?: string | null;
+  title: string;
+  recurrence: string;
+}
+
+export interface DailyPlan {
+  date: string; // YYYY-MM-DD
+  tasks: Task[]; // in queue order, completed ones included
+  planned_pomodoros: number; // estimates of the open tasks
+  available_minutes: number;
+  capacity_pomodoros: number; // focus sessions that fit with the user's session and break lengths
+  overcommitted: boolean;
+  warning?: string;
+}
+
+// Focus counts from get_tasks_with_pomodoro_counts; totals cover the task and its subtasks at any depth
+export interface TaskCounts {
+  actual_pomodoros: number; // the task's own focus sessions
+  total_estimated_pomodoros: number;
+  total_pomodoros: number;
+  subtasks: number;
+  completed_subtasks: number;
+  checklist_items: number;
+  completed_checklist_items: number;
+}
+
+export interface ChecklistItem {
+  id: string;
+  task_id: string;
+  title: string;
+  completed: boolean;
+  position: number;
+  created_at: string;
+}
+
+export interface Project {
+  id: string;
+  user_id: string;
+  name: string;
+  color?: string | null;
+  archived: boolean; // hidden from pickers, history is kept
+  hourly_rate?: number | null;
+  created_at: string;
+}
+
+export interface Tag {
+  id: string;
+  user_id: string;
+  name: string;
+  created_at: string;
+}
+
+// Narrows tasks, sessions and stats; a tag matches a session's own tags or its task's
+export interface ProjectTagFilter {
+  project_id?: string;
+  tag?: string;
+}
+
 export interface PomodoroSession {
   id: string;
   user_id: string;
@@ -37,9 +130,78 @@ export interface PomodoroSession {
   duration_seconds?: number;
   interrupted: boolean;
   interruption_count: number;
+  pause_count: number;
+  paused_seconds: number;
   manual_override: boolean;
   created_at: string;
   task_title?: string;
+  tags: string[]; // the session's own tags, not its task's
+}
+
+export interface ManualSessionOptions {
+  sessionType?: 'FOCUS' | 'SHORT_BREAK' | 'LONG_BREAK'; // defaults to FOCUS
+  tags?: string[];
+  fillGaps?: boolean; // log only the parts of the range no session covers yet
+}
+
+// Fields of a past session to change; omitted ones stay as they are
+export interface UpdateSessionRequest {
+  session_id: string;
+  task_id?: string;
+  clear_task?: boolean; // detaches the session from its task
+  session_type?: 'FOCUS' | 'SHORT_BREAK' | 'LONG_BREAK';
+  start_time?: string;
+  end_time?: string;
+  duration_seconds?: number;
+  tags?: string[]; // replaces all of the session's tags
+}
+
+export type SessionAuditAction = 'edit' | 'delete' | 'split' | 'merge';
+
+export interface SessionAuditEntry {
+  id: string;
+  session_id: string;
+  user_id: string;
+  action: SessionAuditAction;
+  before: PomodoroSession[];
+  after: PomodoroSession[];
+  created_at: string;
+}
+
+export interface SessionPause {
+  id: string;
+  session_id: string;
+  paused_at: string;
+  resumed_at?: string; // missing while the session is still paused
+  reason?: string;
+}
+
+// Internal interruptions come from yourself, external ones from someone or something else
+export type InterruptionKind = 'internal' | 'external';
+
+export interface Interruption {
+  id: string;
+  session_id: string;
+  user_id: string;
+  occurred_at: string;
+  kind: InterruptionKind;
+  reason?: string;
+  category?: string; // e.g. 'Slack', 'meeting', 'self'
+  task_title?: string;
+}
+
+export interface InterruptionSource {
+  category?: string; // missing for interruptions logged without a category
+  interruptions: number;
+  internal: number;
+  external: number;
+  last_occurred_at: string;
+}
+
+export interface RecordInterruptionRequest {
+  kind?: InterruptionKind; // defaults to 'external'
+  reason?: string;
+  category?: string;
 }
 
 export interface Goal {
@@ -56,6 +218,17 @@ export interface Goal {
   created_at: string;
 }
 
+export interface GoalDayProgress {
+  date: string; // YYYY-MM-DD in the user's timezone
+  completed_pomodoros: number;
+  focus_seconds: number;
+}
+
+export interface GoalProgress {
+  goal: Goal;
+  days: GoalDayProgress[];
+}
+
 export interface TimerStatus {
   time_remaining: number; // seconds
   is_running: boolean;
@@ -66,6 +239,166 @@ export interface TimerStatus {
   interruption_count: number;
 }
 
+export interface ExportSummary {
+  directory: string;
+  files: string[];
+  exported_at: string;
+}
+
+export type ImportFormat = 'archive' | 'toggl_csv' | 'pomodoro_csv';
+export type ImportMode = 'merge' | 'replace';
+
+export interface ImportRequest {
+  path: string;
+  format?: ImportFormat; // detected from the file when omitted
+  mode?: ImportMode; // defaults to merge; replace only applies to archives
+  user_id?: string;
+}
+
+export interface ImportTableReport {
+  table: string;
+  inserted: number;
+  skipped: number;
+  conflicting: number;
+}
+
+export interface ImportReport {
+  format: ImportFormat;
+  mode: ImportMode;
+  tables: ImportTableReport[];
+  messages: string[];
+}
+
+export type StatsGranularity = 'day' | 'week' | 'month';
+
+export interface FocusTotals {
+  focus_seconds: number;
+  sessions: number;
+  completed_sessions: number;
+  interrupted_sessions: number;
+  interruptions: number;
+  pauses: number;
+  paused_seconds: number;
+  wall_clock_seconds: number; // focus plus paused time
+  average_session_seconds: number;
+}
+
+export interface FocusPeriodStats extends FocusTotals {
+  period_start: string; // YYYY-MM-DD in the user's timezone
+}
+
+export interface HeatmapBucket {
+  weekday: number; // 0 = Monday ... 6 = Sunday
+  hour: number;
+  focus_seconds: number;
+  sessions: number;
+}
+
+export interface TaskFocusStats {
+  task_id?: string;
+  title?: string;
+  focus_seconds: number;
+  sessions: number;
+}
+
+export interface GoalFocusStats {
+  goal_id: string;
+  title: string;
+  focus_seconds: number;
+  sessions: number;
+}
+
+export interface ProjectFocusStats {
+  project_id?: string; // missing for time outside any project
+  name?: string;
+  color?: string;
+  focus_seconds: number;
+  sessions: number;
+  hourly_rate?: number;
+  amount?: number; // focus time at the hourly rate, rounded to cents
+}
+
+export interface FocusStats {
+  totals: FocusTotals;
+  periods: FocusPeriodStats[];
+  heatmap: HeatmapBucket[];
+  tasks: TaskFocusStats[];
+  goals: GoalFocusStats[];
+  projects: ProjectFocusStats[];
+}
+
+// Payload of the timer://daily-target-reached event
+export interface DailyTargetProgress {
+  date: string; // YYYY-MM-DD in the user's timezone
+  focus_seconds: number;
+  pomodoros: number;
+  target_unit: DailyTargetUnit;
+  target: number;
+  progress: number; // in target_unit
+  target_met: boolean;
+  rest_day: boolean;
+}
+
+export interface StreakSummary {
+  today: DailyTargetProgress;
+  current_streak: number;
+  longest_streak: number;
+}
+
+// 'before_restore' is the database a restore replaced
+export type BackupKind = 'regular' | 'exit' | 'before_restore';
+
+export interface BackupInfo {
+  name: string;
+  path: string;
+  kind: BackupKind;
+  created_at: string;
+  size_bytes: number;
+}
+
+// Local HTTP API; the server listens on 127.0.0.1:<port> while enabled
+export interface ApiSettings {
+  enabled: boolean;
+  port: number;
+  token: string;
+}
+
+export interface UpdateApiSettingsRequest {
+  enabled?: boolean;
+  port?: number;
+}
+
+export type TimeboxErrorCode =
+  | 'NOT_FOUND'
+  | 'VALIDATION'
+  | 'CONFLICT'
+  | 'NO_ACTIVE_SESSION'
+  | 'STRICT_MODE_VIOLATION'
+  | 'DATABASE_LOCKED'
+  | 'DATABASE'
+  | 'IO';
+
+// Structured error returned by every backend command
+export interface TimeboxError {
+  code: TimeboxErrorCode;
+  message: string;
+  details?: Record<string, unknown> | null;
+}
+
+export const isTimeboxError = (error: unknown): error is TimeboxError =>
+  typeof error === 'object' && error !== null && 'code' in error && 'message' in error;
+
+export const isStrictModeViolation = (error: unknown, rule?: string): error is TimeboxError =>
+  isTimeboxError(error) &&
+  error.code === 'STRICT_MODE_VIOLATION' &&
+  (rule === undefined || error.details?.rule === rule);
+
+export interface CycleState {
+  completed_in_cycle: number;
+  cycles_before_long_break: number;
+  next_phase: 'FOCUS' | 'SHORT_BREAK' | 'LONG_BREAK';
+}
+
 export interface SettingsUpdateRequest {
   user_id: string;
   focus_minutes?: number;
@@ -76,6 +409,9 @@ export interface SettingsUpdateRequest {
   auto_start_breaks?: boolean;
   sound_enabled?: boolean;
   sound_volume?: number;
+  daily_target_unit?: DailyTargetUnit;
+  daily_target?: number;
+  rest_days?: number[];
 }
 
 export interface StopwatchSession {
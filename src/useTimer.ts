@@ -1,7 +1,9 @@
 // useTimer.ts
 import { useEffect, useCallback } from 'react';
 import { useAtom } from 'jotai';
+import { listen } from '@tauri-apps/api/event';
 import { apiService } from './apiService';
+import { RecordInterruptionRequest, TimerStatus, isStrictModeViolation } from './types';
 import {
   timerStatusAtom,
   timerMinutesAtom,
@@ -13,13 +15,17 @@ import {
 
 export const useTimerPolling = () => {
   const [, setTimerStatus] = useAtom(timerStatusAtom);
-  const [timerStatus] = useAtom(timerStatusAtom);
 
-  // Poll for timer status updates
+  // Pick up a session restored by the backend after a crash or restart
   useEffect(() => {
-    let interval: ReturnType<typeof setInterval> | null = null;
+    apiService.getTimerStatus()
+      .then(setTimerStatus)
+      .catch(error => console.error('Error getting timer status:', error));
+  }, [setTimerStatus]);
 
-    const pollTimerStatus = async () => {
+  // Follow timer state pushed by the backend timer engine
+  useEffect(() => {
+    const refreshStatus = async () => {
       try {
         const status = await apiService.getTimerStatus();
         setTimerStatus(status);
@@ -28,15 +34,19 @@ export const useTimerPolling = () => {
       }
     };
 
-    // Poll every second when timer is active
-    if (timerStatus.is_running) {
-      interval = setInterval(pollTimerStatus, 1000);
-    }
+    const unlisteners = Promise.all([
+      listen<TimerStatus>('timer://tick', event => setTimerStatus(event.payload)),
+      listen('timer://completed', () => {
+        // Keep showing the session as finished so the completion effect fires
+        setTimerStatus(prev => ({ ...prev, time_remaining: 0 }));
+      }),
+      listen('timer://phase-changed', refreshStatus),
+    ]);
 
     return () => {
-      if (interval) clearInterval(interval);
+      unlisteners.then(fns => fns.forEach(unlisten => unlisten()));
     };
-  }, [timerStatus.is_running, setTimerStatus]);
+  }, [setTimerStatus]);
 };
 
 export const useTimer = () => {
@@ -76,6 +86,8 @@ export const useTimer = () => {
       const status = await apiService.getTimerStatus();
       setTimerStatus(status);
     } catch (error) {
+      // Strict mode rejections are for the caller to present
+      if (isStrictModeViolation(error)) throw error;
       console.error('Error starting timer:', error);
     }
   }, [setIsCompleted, setTimerStatus]);
@@ -87,6 +99,8 @@ export const useTimer = () => {
       const status = await apiService.getTimerStatus();
       setTimerStatus(status);
     } catch (error) {
+      // Strict mode rejections are for the caller to present
+      if (isStrictModeViolation(error)) throw error;
       console.error('Error pausing timer:', error);
     }
   }, [setTimerStatus]);
@@ -102,21 +116,23 @@ export const useTimer = () => {
     }
   }, [setTimerStatus]);
 
-  const stopTimer = useCallback(async () => {
+  const stopTimer = useCallback(async (reason?: string) => {
     try {
-      await apiService.stopSession();
+      await apiService.stopSession(reason);
       setIsCompleted(false);
       // Immediately fetch status to update UI
       const status = await apiService.getTimerStatus();
       setTimerStatus(status);
     } catch (error) {
+      // Strict mode rejections are for the caller to present
+      if (isStrictModeViolation(error)) throw error;
       console.error('Error stopping timer:', error);
     }
   }, [setIsCompleted, setTimerStatus]);
 
-  const recordInterruption = useCallback(async () => {
+  const recordInterruption = useCallback(async (details?: RecordInterruptionRequest) => {
     try {
-      const count = await apiService.recordInterruption();
+      const count = await apiService.recordInterruption(details);
       // Update local state to reflect the new interruption count
       setTimerStatus(prev => ({
         ...prev,